@@ -153,7 +153,7 @@ impl CodeGenBuffer {
         self.param_buffer.push_parameter(Parameter::from_value(z));
         self.param_buffer.push_tag(Tag::new_value(Pitch, pitch));
         self.param_buffer.push_tag(Tag::new_value(Yaw, yaw));
-        self.idents_number_hash.insert(key.to_owned(), param_id);
+        self.idents_location_hash.insert(key.to_owned(), param_id);
         param_id
     }
 
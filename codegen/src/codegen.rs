@@ -2,15 +2,28 @@ use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use dfbin::enums::{Instruction, Parameter, ParameterValue};
-use dfbin::{instruction, tag};
+use dfbin::{instruction, tag, DFBin};
 use dfbin::Constants::Tags::DP;
 use lexer::compiler::Compiler;
 use lexer::types::{Keyword, Position, Range, Token, TokenType, ValuedKeyword};
-use esh_parser::parser::Node;
+use esh_parser::parser::{Node, NodeType, Visibility};
 use crate::buffer::CodeGenBuffer;
 use crate::errors::{CodegenError, ErrorRepr};
 use crate::context::{CodeDefinition, CodeScope, Context, ContextType, EventStructType, FunctionType};
-use crate::types::{CodegenBodyStackMode, CodegenExpressionResult, CodegenExpressionStack, CodegenExpressionType, CodegenLocationCoordinate, CodegenTrace, CodegenTraceCrumb, CodegenTraceCrumbIdent, CodegenValue, CodegenVectorCoordinate, ComptimeType, Field, FieldDefinition, GenerateExpressionSettings, IdentifierCategory, PrimitiveType, RealtimeValueType, RuntimeVariable, ValueType};
+use crate::docs::DocItem;
+use crate::modules::{ModuleError, ModuleGraph};
+use crate::types::{CodegenBodyStackMode, CodegenCast, CodegenComptimeFlow, CodegenConstant, Constant, CodegenExpressionResult, CodegenExpressionStack, CodegenExpressionType, CodegenLocationCoordinate, CodegenTrace, CodegenTraceCrumb, CodegenTraceCrumbIdent, CodegenValue, CodegenVectorCoordinate, ComptimeType, EnumVariant, Field, FieldDefinition, GenerateExpressionSettings, IdentifierCategory, MapFunction, PrimitiveType, RealtimeValueType, RuntimeVariable, ValueType};
+
+/// Matches on strings with at least this many arms are dispatched through a dictionary instead of an if/else chain.
+const MATCH_DISPATCH_ARMS: usize = 8;
+/// How many statements and loop iterations a `const func` call (along with the calls it makes) can run at compile time.
+const CONST_FUNC_STEP_LIMIT: usize = 100_000;
+/// How deeply `const func`s can call each other at compile time.
+const CONST_FUNC_CALL_DEPTH: usize = 64;
+/// How deeply generic instances can need new instances while their types are worked out, like a `Node<T>` with a `Node<T[]>` field.
+const GENERIC_INSTANCE_DEPTH: usize = 16;
+/// The value `none` is stored as, since optionals are kept as plain values of the type inside them.
+const NONE_SENTINEL: &str = "__esh_none";
 
 pub struct CodeGen {
     pub context_map: HashMap<String, usize>,
@@ -30,6 +43,27 @@ pub struct CodeGen {
     context_listeners: HashMap<usize, Vec<usize>>,
     context_listening: HashMap<usize, usize>,
     run: usize,
+    match_count: usize,
+    /// The constants whose values are being worked out right now, as (owner context, constant id).
+    constants_evaluating: Vec<(usize, usize)>,
+    /// The variables of the `const func`s being run at compile time, a stack of block scopes for each call.
+    comptime_locals: Vec<Vec<HashMap<String, CodegenConstant>>>,
+    comptime_steps: usize,
+    /// The `const func`s that are called with values only known at runtime, so they need code generated for them.
+    runtime_const_functions: HashSet<usize>,
+    /// The functions split out of dispatched matches, added after the function they were split out of.
+    match_arm_code: DFBin,
+    /// The imports found while scanning, as (importing context, path of the imported definition, import node, alias).
+    /// They're resolved once every context is known, since they can point anywhere.
+    pending_imports: Vec<(usize, Rc<Node>, Rc<Node>, String)>,
+    /// The declaration each generic struct or func was scanned from, so an instance can be scanned out of it for each set of type arguments.
+    generic_declarations: HashMap<usize, Rc<Node>>,
+    /// The instances made of each generic struct or func.
+    generic_instances: HashMap<usize, Vec<usize>>,
+    /// How many instances are being made inside of each other right now.
+    generic_depth: usize,
+    /// The target of the compound assignment being generated and what was read from it, so its operation doesn't generate the target again.
+    compound_target: Option<(Rc<Node>, CodegenExpressionResult)>,
 }
 
 #[allow(dead_code)]
@@ -51,18 +85,29 @@ impl CodeGen {
             block_runtime_vars_add: Vec::new(),
             context_listeners: HashMap::new(),
             context_listening: HashMap::new(),
-            run: 0
+            run: 0,
+            match_count: 0,
+            constants_evaluating: Vec::new(),
+            comptime_locals: Vec::new(),
+            comptime_steps: 0,
+            runtime_const_functions: HashSet::new(),
+            match_arm_code: DFBin::new(),
+            pending_imports: Vec::new(),
+            generic_declarations: HashMap::new(),
+            generic_instances: HashMap::new(),
+            generic_depth: 0,
+            compound_target: None,
         }
     }
 
     fn scan_block_outline(&mut self, node_block: Rc<Node>, context_type: ContextType, mut depth: u32, parent_id: usize, scope: CodeScope, fields_base: Vec<String>, context_name: String) -> Result<usize, CodegenError> {
         self.run += 1;
-        let Node::Block(node_block) = node_block.as_ref() else {
+        let NodeType::Block(block_nodes) = &node_block.node_type else {
             return CodegenError::err(node_block, ErrorRepr::ExpectedBlock);
         };
         let current_id = self.current_id;
         //##println!("{:?}, {:?}", current_id, depth);
-        let current_context_cell = Rc::new(RefCell::new(Context::new_empty(context_type.clone(), parent_id, current_id, depth, Rc::new(Vec::new()), scope)));
+        let current_context_cell = Rc::new(RefCell::new(Context::new_empty(context_type.clone(), parent_id, current_id, depth, Rc::new(Vec::new()), scope, node_block.clone())));
         depth += 1;
         self.contexts.push(current_context_cell.clone());
         self.parents.push(parent_id);
@@ -93,55 +138,73 @@ impl CodeGen {
         let mut current_context = current_context_cell.borrow_mut();
         self.current_id += 1;
         let mut body = Vec::new();
-        for node in node_block {
-            match (node.as_ref(), &context_type) {
-                (Node::Struct(..) | Node::Func(..) | Node::Event(..) | Node::Domain(..), ContextType::Function(..)) => {
-                    return CodegenError::err(node.clone(), match node.as_ref() {
-                        Node::Struct(..) => ErrorRepr::StructNestedInFunction,
-                        Node::Func(..) => ErrorRepr::FunctionNestedInFunction,
-                        Node::Event(..) => ErrorRepr::FunctionNestedInFunction,
-                        Node::Domain(..) => ErrorRepr::DomainNestedInFunction,
+        for node in block_nodes {
+            let (node, member_scope) = match &node.node_type {
+                NodeType::Visibility(visibility, declaration) => {
+                    if !matches!(context_type, ContextType::Struct | ContextType::Domain)
+                        || !matches!(&declaration.node_type, NodeType::Func(..) | NodeType::ConstFunc(..) | NodeType::Struct(..) | NodeType::Enum(..) | NodeType::Domain(..) | NodeType::Declaration(..) | NodeType::Const(..) | NodeType::EventDecl(..))
+                        && !matches!(&declaration.node_type, NodeType::Assignment(field, _) if matches!(field.node_type, NodeType::Declaration(..))) {
+                        return CodegenError::err(node.clone(), ErrorRepr::UnexpectedVisibility);
+                    }
+                    (declaration, match visibility {
+                        Visibility::Public => CodeScope::Public,
+                        Visibility::Private => CodeScope::Private,
+                    })
+                },
+                _ => (node, CodeScope::Public),
+            };
+            let (node, comptime) = match &node.node_type {
+                NodeType::ConstFunc(function) => (function, true),
+                _ => (node, false),
+            };
+            // A struct field with an initializer is declared like any other field, the initializer is kept for constructs
+            let (node, default_value) = match (&node.node_type, &context_type) {
+                (NodeType::Assignment(field, value), ContextType::Struct) if matches!(field.node_type, NodeType::Declaration(..)) => (field, Some(value.clone())),
+                _ => (node, None),
+            };
+            match (&node.node_type, &context_type) {
+                (NodeType::Struct(..) | NodeType::Enum(..) | NodeType::Func(..) | NodeType::Event(..) | NodeType::Domain(..), ContextType::Function(..)) => {
+                    return CodegenError::err(node.clone(), match &node.node_type {
+                        NodeType::Struct(..) => ErrorRepr::StructNestedInFunction,
+                        NodeType::Enum(..) => ErrorRepr::EnumNestedInFunction,
+                        NodeType::Func(..) => ErrorRepr::FunctionNestedInFunction,
+                        NodeType::Event(..) => ErrorRepr::FunctionNestedInFunction,
+                        NodeType::Domain(..) => ErrorRepr::DomainNestedInFunction,
                         _ => ErrorRepr::Generic
                     });
                 }
-                (Node::Func(ident, params, return_type, body), ContextType::Struct | ContextType::Domain) => {
-                    let return_type_field = ValueType::Ident(return_type.clone());
-                    let params = Self::extract_declaration_vec(params)?;
-                    let func_fields_base = {
-                        let mut res = Vec::new();
-                        for (_param_type, param_name) in params.iter() {
-                            let param_name_ident = Self::get_primary_as_ident(param_name, ErrorRepr::ExpectedFunctionParamIdent)?;
-                            res.push(param_name_ident.clone());
-                        }
-                        res
-                    };
-                    let ident_string = Self::get_primary_as_ident(ident, ErrorRepr::ExpectedFunctionIdentifier)?;
-                    let child_id = self.scan_block_outline(body.clone(), ContextType::Function(FunctionType::Func(return_type_field)), depth, current_id, CodeScope::Public, func_fields_base, ident_string.clone())?;
-                    let mut child_modify = self.context_borrow_mut(child_id)?;
-                    for (param_type, param_name) in params {
-                        let param_name_ident = Self::get_primary_as_ident(param_name, ErrorRepr::ExpectedFunctionParamIdent)?;
-                        let field_id = child_modify.fields.len();
-                        child_modify.fields.push(Field {
-                            field_type: ValueType::Ident(param_type.clone()),
-                            scope: CodeScope::Public,
-                        });
-                        Self::add_definition(&mut child_modify, param_name_ident.clone(), CodeDefinition::Field(field_id))?;
+                (NodeType::Func(ident, _, return_type, ..), ContextType::Struct | ContextType::Domain) if Self::is_self_identity(ident) => {
+                    if !matches!(context_type, ContextType::Struct) {
+                        return CodegenError::err(ident.clone(), ErrorRepr::ConstructorOutsideStruct);
                     }
-                    drop(child_modify);
-                    Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
+                    if current_context.constructor.is_some() {
+                        return CodegenError::err(ident.clone(), ErrorRepr::ConstructorAlreadyDefined);
+                    }
+                    if !matches!(return_type.node_type, NodeType::None) {
+                        return CodegenError::err(return_type.clone(), ErrorRepr::UnexpectedConstructorReturnType);
+                    }
+                    let child_id = self.scan_function(node, depth, current_id, member_scope, comptime, "self".to_owned())?;
+                    current_context.constructor = Some(child_id);
                     current_context.children.push(child_id);
                 },
-                (Node::EventDecl(ident, params_node), ContextType::Domain) => {
+                (NodeType::Func(ident, ..), ContextType::Struct | ContextType::Domain) => {
+                    let (ident_string, type_parameters) = Self::get_generic_declaration_ident(ident, ErrorRepr::ExpectedFunctionIdentifier)?;
+                    let child_id = self.scan_function(node, depth, current_id, member_scope, comptime, ident_string.clone())?;
+                    self.declare_generic(child_id, node, type_parameters)?;
+                    Self::add_definition(&mut current_context, ident_string, CodeDefinition::Context(child_id))?;
+                    current_context.children.push(child_id);
+                },
+                (NodeType::EventDecl(ident, params_node, doc), ContextType::Domain) => {
                     let mut params_node = params_node.clone();
-                    // let empty_string_node_str = Rc::new(Node::Primary(Rc::new(Token{token_type: TokenType::String("".to_owned()), range: Range{start: Position{char: 0, line: 0}, end: Position{line: 0, char: 0}}})));
-                    let empty_string_node = Rc::new(Node::None);
-                    let Node::Tuple(params_tuple_node) = params_node.as_ref() else {
+                    // let empty_string_node_str = Rc::new(NodeType::Primary(Rc::new(Token{token_type: TokenType::String("".to_owned()), range: Range{start: Position{char: 0, line: 0}, end: Position{line: 0, char: 0}}})));
+                    let empty_string_node = Rc::new(Node::new(NodeType::None, params_node.range.clone()));
+                    let NodeType::Tuple(params_tuple_node) = &params_node.node_type else {
                         return CodegenError::err(params_node.clone(), ErrorRepr::UnexpectedEventParameter);
                     };
                     let params_tuple_node = params_tuple_node.clone();
                     let mut first_param_non_decl = false;
                     if let Some(g) = params_tuple_node.get(0) {
-                        if !matches!(g.as_ref(), Node::Declaration(..)) {
+                        if !matches!(g.node_type, NodeType::Declaration(..)) {
                             first_param_non_decl = true;
                             params_node = g.clone();
                         }
@@ -154,7 +217,7 @@ impl CodeGen {
                     let func_fields_base = {
                         let mut res = Vec::new();
                         for (_param_type, param_name) in params.iter() {
-                            if matches!(param_name.as_ref(), Node::None) {
+                            if matches!(param_name.node_type, NodeType::None) {
                                 res.push("event_data".to_owned());
                                 continue;
                             }
@@ -168,9 +231,10 @@ impl CodeGen {
                     let event_struct_id = if autobuilt_struct {
                         let mut generated_struct_block_node = Vec::new();
                         for (param_type, param_name) in params.clone() {
-                            generated_struct_block_node.push(Rc::new(Node::Declaration(param_type.clone(), param_name.clone())));
+                            let declaration_range = Range { start: param_type.range.start.clone(), end: param_name.range.end.clone() };
+                            generated_struct_block_node.push(Rc::new(Node::new(NodeType::Declaration(param_type.clone(), param_name.clone()), declaration_range)));
                         }
-                        let struct_child_id = self.scan_block_outline(Rc::new(Node::Block(generated_struct_block_node)), ContextType::Struct, depth, current_id, CodeScope::Public, Vec::new(), ident_string.clone())?;
+                        let struct_child_id = self.scan_block_outline(Rc::new(Node::new(NodeType::Block(generated_struct_block_node), params_node.range.clone())), ContextType::Struct, depth, current_id, CodeScope::Public, Vec::new(), ident_string.clone())?;
                         Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(struct_child_id))?;
                         current_context.children.push(struct_child_id);
                         EventStructType::Struct(struct_child_id, true)
@@ -178,15 +242,17 @@ impl CodeGen {
                         EventStructType::Undecided(params_node.clone())
                     };
                     
-                    let child_id = self.scan_block_outline(Rc::new(Node::Block(Vec::new())), ContextType::Function(FunctionType::Event(event_struct_id)), depth, current_id, CodeScope::Public, func_fields_base, ident_string.clone())?;
+                    let child_id = self.scan_block_outline(Rc::new(Node::new(NodeType::Block(Vec::new()), node.range.clone())), ContextType::Function(FunctionType::Event(event_struct_id)), depth, current_id, member_scope, func_fields_base, ident_string.clone())?;
                     let mut child_modify = self.context_borrow_mut(child_id)?;
+                    child_modify.doc = doc.clone();
                     for (param_type, param_name) in params {
                         let field_id = child_modify.fields.len();
                         child_modify.fields.push(Field {
                             field_type: ValueType::Ident(param_type.clone()),
                             scope: CodeScope::Public,
+                            default_value: None,
                         });
-                        if matches!(param_name.as_ref(), Node::None) {
+                        if matches!(param_name.node_type, NodeType::None) {
                             continue;
                         }
                         let param_name_ident = Self::get_primary_as_ident(param_name, ErrorRepr::ExpectedFunctionParamIdent)?;
@@ -196,18 +262,27 @@ impl CodeGen {
                     Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
                     current_context.children.push(child_id);
                 },
-                (Node::Event(ident, body), ContextType::Domain) => {
+                (NodeType::Event(ident, body), ContextType::Domain) => {
                     let child_id = self.scan_block_outline(body.clone(), ContextType::Function(FunctionType::EventListener(ident.clone())), depth, current_id, CodeScope::Public, Vec::new(), "L:".to_owned())?;
                     // Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
                     current_context.children.push(child_id);
                 },
-                (Node::Struct(ident, body), _) => {
-                    let ident_string = Self::get_primary_as_ident(ident, ErrorRepr::ExpectedStructIdentifier)?;
-                    let child_id = self.scan_block_outline(body.clone(), ContextType::Struct, depth, current_id, CodeScope::Public, Vec::new(), ident_string.clone())?;
+                (NodeType::Struct(ident, body, doc), _) => {
+                    let (ident_string, type_parameters) = Self::get_generic_declaration_ident(ident, ErrorRepr::ExpectedStructIdentifier)?;
+                    let child_id = self.scan_block_outline(body.clone(), ContextType::Struct, depth, current_id, member_scope, Vec::new(), ident_string.clone())?;
+                    self.context_borrow_mut(child_id)?.doc = doc.clone();
+                    self.declare_generic(child_id, node, type_parameters)?;
+                    Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
+                    current_context.children.push(child_id);
+                },
+                (NodeType::Enum(ident, variants, doc), _) => {
+                    let ident_string = Self::get_primary_as_ident(ident, ErrorRepr::ExpectedEnumIdentifier)?;
+                    let child_id = self.scan_enum(node, variants, depth, current_id, member_scope, ident_string.clone())?;
+                    self.context_borrow_mut(child_id)?.doc = doc.clone();
                     Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
                     current_context.children.push(child_id);
                 },
-                (Node::Declaration(field_type, field_name), ContextType::Struct | ContextType::Domain) => {
+                (NodeType::Declaration(field_type, field_name), ContextType::Struct | ContextType::Domain) => {
                     let field_name_ident = Self::get_primary_as_ident(field_name, ErrorRepr::ExpectedStructFieldIdentifier)?;
                     let field_id = current_context.fields.len();
                     if field_names_hash.contains(field_name_ident) {
@@ -218,7 +293,8 @@ impl CodeGen {
                     Self::add_definition(&mut current_context, field_name_ident.clone(), CodeDefinition::Field(field_id))?;
                     current_context.fields.push(Field{
                         field_type: ValueType::Ident(field_type.clone()),
-                        scope: CodeScope::Public,
+                        scope: member_scope,
+                        default_value,
                     });
 
                     if matches!(&context_type, ContextType::Domain) { //Fields in domains are domain variables
@@ -237,15 +313,52 @@ impl CodeGen {
                         );
                     }
                 },
-                (Node::Domain(ident, body), ContextType::Domain) => {
+                (NodeType::Domain(ident, body, doc), ContextType::Domain) => {
                     let ident_string = Self::get_primary_as_ident(ident, ErrorRepr::ExpectedDomainIdentifier)?;
-                    let child_id = self.scan_block_outline(body.clone(), ContextType::Domain, depth, current_id, CodeScope::Public, Vec::new(), ident_string.clone())?;
+                    let child_id = self.scan_block_outline(body.clone(), ContextType::Domain, depth, current_id, member_scope, Vec::new(), ident_string.clone())?;
+                    self.context_borrow_mut(child_id)?.doc = doc.clone();
                     Self::add_definition(&mut current_context, ident_string.clone(), CodeDefinition::Context(child_id))?;
                     current_context.children.push(child_id);
                 },
-                (Node::Domain(..), ContextType::Struct) => {
+                (NodeType::Domain(..), ContextType::Struct) => {
                     return CodegenError::err(node.clone(), ErrorRepr::DomainNestedInStruct);
                 },
+                (NodeType::Const(declaration, value_node), _) => {
+                    let NodeType::Declaration(constant_type, constant_name) = &declaration.node_type else {
+                        return CodegenError::err(declaration.clone(), ErrorRepr::ExpectedVariableIdentifier);
+                    };
+                    let constant_name_ident = Self::get_primary_as_ident(constant_name, ErrorRepr::ExpectedVariableIdentifier)?;
+                    if field_names_hash.contains(constant_name_ident)
+                        || matches!(current_context.definition_lookup.get(constant_name_ident), Some(CodeDefinition::Constant(..))) {
+                        return CodegenError::err(constant_name.clone(), ErrorRepr::FieldAlreadyDefined);
+                    }
+                    let constant_id = current_context.constants.len();
+                    current_context.constants.push(Constant {
+                        constant_type: constant_type.clone(),
+                        value_node: value_node.clone(),
+                        scope: member_scope,
+                        value: None,
+                    });
+                    Self::add_definition(&mut current_context, constant_name_ident.clone(), CodeDefinition::Constant(constant_id))?;
+                },
+                (NodeType::Package(..), ContextType::Domain) => {}, // Checked against the file's location by the module resolver
+                (NodeType::Import(path, alias), ContextType::Domain) => {
+                    let alias = Self::import_alias(path, alias)?;
+                    self.pending_imports.push((current_id, path.clone(), node.clone(), alias));
+                },
+                (NodeType::FromImport(path, names), ContextType::Domain) => {
+                    for name_node in names {
+                        let NodeType::Import(name, alias) = &name_node.node_type else {
+                            return CodegenError::err(name_node.clone(), ErrorRepr::ExpectedDefinitionIdent);
+                        };
+                        let alias = Self::import_alias(name, alias)?;
+                        let imported = Rc::new(Node::new(NodeType::Access(path.clone(), name.clone()), name_node.range.clone()));
+                        self.pending_imports.push((current_id, imported, name_node.clone(), alias));
+                    }
+                },
+                (NodeType::Package(..) | NodeType::Import(..) | NodeType::FromImport(..), _) => {
+                    return CodegenError::err(node.clone(), ErrorRepr::UnexpectedImport);
+                },
                 (_, ContextType::Struct) => {
                     return CodegenError::err(node.clone(), ErrorRepr::UnstructuredStructCode);
                 }
@@ -263,8 +376,13 @@ impl CodeGen {
         Ok(current_id)
     }
 
+    /// Whether the node is the `self` keyword, which is the name a struct's constructor is declared with (`func self(...)`).
+    fn is_self_identity(node: &Rc<Node>) -> bool {
+        matches!(&node.node_type, NodeType::Primary(token) if token.token_type == TokenType::Keyword(Keyword::Value(ValuedKeyword::SelfIdentity)))
+    }
+
     fn get_primary_as_ident(node: &Rc<Node>, err: ErrorRepr) -> Result<&String, CodegenError> {
-        let Node::Primary(node_token) = node.as_ref() else {
+        let NodeType::Primary(node_token) = &node.node_type else {
             return CodegenError::err(node.clone(), err);
         };
         let TokenType::Ident(node_ident) = &node_token.token_type else {
@@ -273,6 +391,96 @@ impl CodeGen {
         Ok(node_ident)
     }
 
+    /// Scans an enum declaration into a new enum context, the payloads of all of its variants are kept as its fields.
+    fn scan_enum(&mut self, node: &Rc<Node>, variants: &[Rc<Node>], depth: u32, parent_id: usize, scope: CodeScope, name: String) -> Result<usize, CodegenError> {
+        let enum_id = self.scan_block_outline(Rc::new(Node::new(NodeType::Block(Vec::new()), node.range.clone())), ContextType::Enum, depth, parent_id, scope, Vec::new(), name)?;
+        let mut enum_modify = self.context_borrow_mut(enum_id)?;
+        let mut field_names = Vec::new();
+        for variant_node in variants {
+            let NodeType::EnumVariant(variant_ident, payload) = &variant_node.node_type else {
+                return CodegenError::err(variant_node.clone(), ErrorRepr::ExpectedEnumVariantIdentifier);
+            };
+            let variant_name = Self::get_primary_as_ident(variant_ident, ErrorRepr::ExpectedEnumVariantIdentifier)?;
+            if enum_modify.variants.iter().any(|variant| &variant.name == variant_name) {
+                return CodegenError::err(variant_ident.clone(), ErrorRepr::EnumVariantAlreadyDefined);
+            }
+            let mut fields = Vec::new();
+            for (payload_type, payload_name) in Self::extract_declaration_vec(payload)? {
+                let payload_name = Self::get_primary_as_ident(payload_name, ErrorRepr::ExpectedFunctionParamIdent)?;
+                fields.push(enum_modify.fields.len());
+                enum_modify.fields.push(Field {
+                    field_type: ValueType::Ident(payload_type.clone()),
+                    scope: CodeScope::Public,
+                    default_value: None,
+                });
+                field_names.push(payload_name.clone());
+            }
+            enum_modify.variants.push(EnumVariant { name: variant_name.clone(), fields });
+        }
+        drop(enum_modify);
+        self.field_names[enum_id] = field_names;
+        Ok(enum_id)
+    }
+
+    /// Scans a func declaration into a new function context, with a field for each of its parameters.
+    fn scan_function(&mut self, node: &Rc<Node>, depth: u32, parent_id: usize, scope: CodeScope, comptime: bool, name: String) -> Result<usize, CodegenError> {
+        let NodeType::Func(_, params, return_type, body, doc) = &node.node_type else {
+            return CodegenError::err(node.clone(), ErrorRepr::ExpectedFunctionIdentifier);
+        };
+        let return_type_field = ValueType::Ident(return_type.clone());
+        let params = Self::extract_declaration_vec(params)?;
+        let func_fields_base = {
+            let mut res = Vec::new();
+            for (_param_type, param_name) in params.iter() {
+                let param_name_ident = Self::get_primary_as_ident(param_name, ErrorRepr::ExpectedFunctionParamIdent)?;
+                res.push(param_name_ident.clone());
+            }
+            res
+        };
+        let child_id = self.scan_block_outline(body.clone(), ContextType::Function(FunctionType::Func(return_type_field)), depth, parent_id, scope, func_fields_base, name)?;
+        let mut child_modify = self.context_borrow_mut(child_id)?;
+        child_modify.doc = doc.clone();
+        child_modify.comptime = comptime;
+        for (param_type, param_name) in params {
+            let param_name_ident = Self::get_primary_as_ident(param_name, ErrorRepr::ExpectedFunctionParamIdent)?;
+            let field_id = child_modify.fields.len();
+            child_modify.fields.push(Field {
+                field_type: ValueType::Ident(param_type.clone()),
+                scope: CodeScope::Public,
+                default_value: None,
+            });
+            Self::add_definition(&mut child_modify, param_name_ident.clone(), CodeDefinition::Field(field_id))?;
+        }
+        drop(child_modify);
+        Ok(child_id)
+    }
+
+    /// The name a struct or func is declared with, along with the names of its type parameters if it's generic (`struct Pair<A, B>`).
+    fn get_generic_declaration_ident(node: &Rc<Node>, err: ErrorRepr) -> Result<(String, Vec<String>), CodegenError> {
+        let NodeType::Generic(ident, parameters) = &node.node_type else {
+            return Ok((Self::get_primary_as_ident(node, err)?.clone(), Vec::new()));
+        };
+        let mut type_parameters: Vec<String> = Vec::new();
+        for parameter in parameters {
+            let parameter_ident = Self::get_primary_as_ident(parameter, ErrorRepr::ExpectedTypeParameterIdentifier)?;
+            if type_parameters.contains(parameter_ident) {
+                return CodegenError::err(parameter.clone(), ErrorRepr::TypeParameterAlreadyDefined);
+            }
+            type_parameters.push(parameter_ident.clone());
+        }
+        Ok((Self::get_primary_as_ident(ident, err)?.clone(), type_parameters))
+    }
+
+    /// Marks a scanned struct or func as generic, keeping its declaration around to scan its instances from.
+    fn declare_generic(&mut self, context: usize, declaration: &Rc<Node>, type_parameters: Vec<String>) -> Result<(), CodegenError> {
+        if type_parameters.is_empty() {
+            return Ok(());
+        }
+        self.context_borrow_mut(context)?.type_parameters = type_parameters;
+        self.generic_declarations.insert(context, declaration.clone());
+        Ok(())
+    }
+
     fn add_definition(context: &mut Context, ident: String, definition: CodeDefinition) -> Result<(), CodegenError> {
         match context.definition_lookup.get_mut(&ident) {
             Some(CodeDefinition::Multiple(mult)) => {
@@ -291,14 +499,71 @@ impl CodeGen {
         Ok(())
     }
 
+    /// The name an import is known by, which is the one given with `as`, or else the last name in its path.
+    fn import_alias(path: &Rc<Node>, alias: &Rc<Node>) -> Result<String, CodegenError> {
+        let name = match (&alias.node_type, &path.node_type) {
+            (NodeType::None, NodeType::Access(_, name)) => name,
+            (NodeType::None, _) => path,
+            _ => alias,
+        };
+        Ok(Self::get_primary_as_ident(name, ErrorRepr::ExpectedDefinitionIdent)?.clone())
+    }
+
+    /// Adds every import to the context it's in, under its alias, as the same definition it points at.
+    ///
+    /// Imports can go through other imports, so the ones that can't be found are tried again until no more can be.
+    /// Every import that's left over is given back as an error, along with any import that couldn't be added.
+    fn resolve_all_imports(&mut self) -> Vec<CodegenError> {
+        let mut errors = Vec::new();
+        let mut pending = std::mem::take(&mut self.pending_imports);
+        loop {
+            let pending_len = pending.len();
+            let mut unresolved = Vec::new();
+            for (context, path, import_node, alias) in pending {
+                let definition = match self.find_definition_owner_by_node(&path, context) {
+                    Ok((_, definition)) => definition,
+                    Err(CodegenError { source: ErrorRepr::PrivateDefinitionAccess, .. }) => {
+                        errors.push(CodegenError::new(import_node, ErrorRepr::PrivateDefinitionAccess));
+                        continue;
+                    },
+                    Err(_) => {
+                        unresolved.push((context, path, import_node, alias));
+                        continue;
+                    }
+                };
+                if let Err(e) = self.import_definition(context, definition, alias) {
+                    errors.push(e.or_node(&import_node));
+                }
+            }
+            if unresolved.is_empty() || unresolved.len() == pending_len {
+                errors.extend(unresolved.into_iter().map(|(_, _, import_node, _)| CodegenError::new(import_node, ErrorRepr::UnresolvedImport)));
+                return errors;
+            }
+            pending = unresolved;
+        }
+    }
+
+    fn import_definition(&self, context: usize, definition: CodeDefinition, alias: String) -> Result<(), CodegenError> {
+        let importable = match &definition {
+            CodeDefinition::Context(..) => true,
+            CodeDefinition::Multiple(definitions) => definitions.iter().all(|definition| matches!(definition, CodeDefinition::Context(..))),
+            CodeDefinition::Field(..) | CodeDefinition::Constant(..) => false,
+        };
+        if !importable {
+            return CodegenError::err_headless(ErrorRepr::UnimportableDefinition);
+        }
+        let mut context_modify = self.context_borrow_mut(context)?;
+        Self::add_definition(&mut context_modify, alias, definition)
+    }
+
     fn extract_declaration_vec(node: &Rc<Node>) -> Result<Vec<(&Rc<Node>, &Rc<Node>)>, CodegenError> {
-        match node.as_ref() {
-            Node::None => Ok(Vec::new()),
-            Node::Declaration(node_type, node_ident) => Ok(vec![(node_type, node_ident)]),
-            Node::Tuple(node_children) => {
+        match &node.node_type {
+            NodeType::None => Ok(Vec::new()),
+            NodeType::Declaration(node_type, node_ident) => Ok(vec![(node_type, node_ident)]),
+            NodeType::Tuple(node_children) => {
                 let mut declarations = Vec::new();
                 for node_child in node_children {
-                    let Node::Declaration(node_child_type, node_child_ident) = node_child.as_ref() else {
+                    let NodeType::Declaration(node_child_type, node_child_ident) = &node_child.node_type else {
                         return CodegenError::err(node_child.clone(), ErrorRepr::ExpectedFunctionParamDeclaration);
                     };
                     declarations.push((node_child_type, node_child_ident));
@@ -310,10 +575,10 @@ impl CodeGen {
     }
 
     fn extract_parameter_vec(node: &Rc<Node>) -> Result<Vec<Rc<Node>>, CodegenError> {
-        match node.as_ref() {
-            Node::None => Ok(Vec::new()),
-            Node::Primary(..) => Ok(vec![node.clone()]),
-            Node::Tuple(node_children) => {
+        match &node.node_type {
+            NodeType::None => Ok(Vec::new()),
+            NodeType::Primary(..) => Ok(vec![node.clone()]),
+            NodeType::Tuple(node_children) => {
                 Ok(node_children.clone())
             }
             _ => CodegenError::err(node.clone(), ErrorRepr::ExpectedFunctionParamDeclaration)
@@ -323,79 +588,264 @@ impl CodeGen {
     
     fn fill_all_field_types(&mut self) -> Result<(), CodegenError> {
         for context_id in 0..self.contexts.len() {
-            let context_get = self.context_borrow(context_id)?;
-            let context_type = context_get.context_type.clone();
-            let fields_len = context_get.fields.len();
-            drop(context_get);
+            if self.is_generic_template(context_id)? {
+                continue;
+            }
+            self.fill_field_types(context_id)?;
+        }
+        Ok(())
+    }
 
-            if let ContextType::Function(func_return_type) = context_type {
-                match func_return_type {
-                    FunctionType::Func(ValueType::Ident(func_return_type_node)) => {
-                        let return_type_set = if !matches!(func_return_type_node.as_ref(), Node::None) {
-                            self.get_type(&func_return_type_node, context_id)?
-                        } else { // No return type
-                            ValueType::Primitive(PrimitiveType::None)
-                        };
-                        let mut context_get_mut = self.context_borrow_mut(context_id)?;
-                        context_get_mut.context_type = ContextType::Function(FunctionType::Func(return_type_set));
-                        drop(context_get_mut);
-                    },
-                    FunctionType::Event(EventStructType::Undecided(event_struct_node)) => {
-                        let event_struct_set = if !matches!(event_struct_node.as_ref(), Node::None) {
-                            self.get_type(&event_struct_node, context_id)?
-                        } else { // No return type
-                            ValueType::Primitive(PrimitiveType::None)
-                        };
-                        let ValueType::Struct(event_struct_id) = event_struct_set else {
-                            return CodegenError::err(event_struct_node.clone(), ErrorRepr::ExpectedStruct);
-                        };
-                        let mut context_get_mut = self.context_borrow_mut(context_id)?;
-                        context_get_mut.context_type = ContextType::Function(FunctionType::Event(EventStructType::Struct(event_struct_id, false)));
-                        drop(context_get_mut);
-                    }
-                    FunctionType::EventListener(event_ident) => {
-                        let event_ident_evaluation = self.generate_expression(context_id, &event_ident, GenerateExpressionSettings::comptime().prefer_category(IdentifierCategory::Function))?;
-                        let ValueType::Comptime(ComptimeType::Function(event_context_id)) = event_ident_evaluation.value.value_type else {
-                            return CodegenError::err(event_ident.clone(), ErrorRepr::ExpectedEventIdentifier);
-                        };
-                        let event_context_get = self.context_borrow(event_context_id)?;
-                        let event_context_type = event_context_get.context_type.clone();
-                        drop(event_context_get);
-                        if !matches!(event_context_type, ContextType::Function(FunctionType::Event(..))) {
-                            return CodegenError::err(event_ident.clone(), ErrorRepr::ExpectedEventIdentifier);
-                        }
-                        if let Some(m) = self.context_listeners.get_mut(&event_context_id) {
-                            m.push(context_id);
-                        } else {
-                            self.context_listeners.insert(event_context_id, vec![context_id]);
-                        }
-                        self.context_listening.insert(context_id, event_context_id);
-                        let event_name = self.context_names[event_context_id].clone();
-                        self.context_names[context_id].push_str(&event_name);
-                        self.context_full_names[context_id].push_str(&event_name);
+    /// Works out the types of a context's fields and parameters, along with its return type or the event it listens to.
+    fn fill_field_types(&mut self, context_id: usize) -> Result<(), CodegenError> {
+        let context_get = self.context_borrow(context_id)?;
+        let context_type = context_get.context_type.clone();
+        let fields_len = context_get.fields.len();
+        drop(context_get);
+
+        if let ContextType::Function(func_return_type) = context_type {
+            match func_return_type {
+                FunctionType::Func(ValueType::Ident(func_return_type_node)) => {
+                    let return_type_set = if !matches!(func_return_type_node.node_type, NodeType::None) {
+                        self.get_type(&func_return_type_node, context_id)?
+                    } else { // No return type
+                        ValueType::Primitive(PrimitiveType::None)
+                    };
+                    let mut context_get_mut = self.context_borrow_mut(context_id)?;
+                    context_get_mut.context_type = ContextType::Function(FunctionType::Func(return_type_set));
+                    drop(context_get_mut);
+                },
+                FunctionType::Event(EventStructType::Undecided(event_struct_node)) => {
+                    let event_struct_set = if !matches!(event_struct_node.node_type, NodeType::None) {
+                        self.get_type(&event_struct_node, context_id)?
+                    } else { // No return type
+                        ValueType::Primitive(PrimitiveType::None)
+                    };
+                    let ValueType::Struct(event_struct_id) = event_struct_set else {
+                        return CodegenError::err(event_struct_node.clone(), ErrorRepr::ExpectedStruct);
+                    };
+                    let mut context_get_mut = self.context_borrow_mut(context_id)?;
+                    context_get_mut.context_type = ContextType::Function(FunctionType::Event(EventStructType::Struct(event_struct_id, false)));
+                    drop(context_get_mut);
+                }
+                FunctionType::EventListener(event_ident) => {
+                    let event_ident_evaluation = self.generate_expression(context_id, &event_ident, GenerateExpressionSettings::comptime().prefer_category(IdentifierCategory::Function))?;
+                    let ValueType::Comptime(ComptimeType::Function(event_context_id)) = event_ident_evaluation.value.value_type else {
+                        return CodegenError::err(event_ident.clone(), ErrorRepr::ExpectedEventIdentifier);
+                    };
+                    let event_context_get = self.context_borrow(event_context_id)?;
+                    let event_context_type = event_context_get.context_type.clone();
+                    drop(event_context_get);
+                    if !matches!(event_context_type, ContextType::Function(FunctionType::Event(..))) {
+                        return CodegenError::err(event_ident.clone(), ErrorRepr::ExpectedEventIdentifier);
                     }
-                    _ => {}
+                    if let Some(m) = self.context_listeners.get_mut(&event_context_id) {
+                        m.push(context_id);
+                    } else {
+                        self.context_listeners.insert(event_context_id, vec![context_id]);
+                    }
+                    self.context_listening.insert(context_id, event_context_id);
+                    let event_name = self.context_names[event_context_id].clone();
+                    self.context_names[context_id].push_str(&event_name);
+                    self.context_full_names[context_id].push_str(&event_name);
                 }
+                _ => {}
             }
+        }
 
-            let domain_vars_len = self.domain_vars[context_id].len();
-            
-            for field in 0..fields_len {
-                let context_get = self.context_borrow(context_id)?;
-                let ValueType::Ident(field_ident) = context_get.fields.get(field).expect("Field should be defined").field_type.clone() else {
-                    continue;
-                };
-                drop(context_get);
-                let field_type_set =  self.get_type(&field_ident, context_id)?;
-                if field < domain_vars_len { // Also replace the domain_var ident
-                    self.domain_vars[context_id][field].variable.value_type = field_type_set.clone();
+        let domain_vars_len = self.domain_vars[context_id].len();
+        
+        for field in 0..fields_len {
+            let context_get = self.context_borrow(context_id)?;
+            let ValueType::Ident(field_ident) = context_get.fields.get(field).expect("Field should be defined").field_type.clone() else {
+                continue;
+            };
+            drop(context_get);
+            let field_type_set =  self.get_type(&field_ident, context_id)?;
+            if field < domain_vars_len { // Also replace the domain_var ident
+                self.domain_vars[context_id][field].variable.value_type = field_type_set.clone();
+            }
+            let mut context_get_mut = self.context_borrow_mut(context_id)?;
+            context_get_mut.fields[field].field_type = field_type_set;
+            drop(context_get_mut);
+
+        }
+        Ok(())
+    }
+
+    /// Whether the context is a generic struct or func, or inside of one. Those are only templates for their instances,
+    /// so their types aren't worked out and they don't get any code.
+    fn is_generic_template(&self, mut context: usize) -> Result<bool, CodegenError> {
+        loop {
+            let context_borrow = self.context_borrow(context)?;
+            if !context_borrow.type_parameters.is_empty() {
+                return Ok(true);
+            }
+            if context_borrow.id == context_borrow.parent_id {
+                return Ok(false);
+            }
+            context = context_borrow.parent_id;
+        }
+    }
+
+    fn is_generic(&self, context: usize) -> Result<bool, CodegenError> {
+        Ok(!self.context_borrow(context)?.type_parameters.is_empty())
+    }
+
+    /// Gives back the instance of a generic struct or func for these type arguments, scanning it out of the generic declaration the first time.
+    ///
+    /// An instance is a copy of the declaration, named after its type arguments (`Pair<num, string>`), in which every type parameter stands for its type argument.
+    fn instantiate_generic(&mut self, template: usize, type_arguments: Vec<ValueType>, node: &Rc<Node>) -> Result<usize, CodegenError> {
+        if let Some(instances) = self.generic_instances.get(&template) {
+            for instance in instances {
+                if self.context_borrow(*instance)?.instance_of.as_ref().is_some_and(|(_, instance_arguments)| *instance_arguments == type_arguments) {
+                    return Ok(*instance);
                 }
-                let mut context_get_mut = self.context_borrow_mut(context_id)?;
-                context_get_mut.fields[field].field_type = field_type_set;
-                drop(context_get_mut);
+            }
+        }
+        let (parameter_count, parent_id, depth, scope, comptime, doc) = {
+            let template_borrow = self.context_borrow(template)?;
+            (template_borrow.type_parameters.len(), template_borrow.parent_id, template_borrow.depth, template_borrow.scope.clone(), template_borrow.comptime, template_borrow.doc.clone())
+        };
+        if type_arguments.len() < parameter_count {
+            return CodegenError::err(node.clone(), ErrorRepr::ExpectedTypeArguments);
+        }
+        if type_arguments.len() > parameter_count {
+            return CodegenError::err(node.clone(), ErrorRepr::UnexpectedTypeArguments);
+        }
+        if self.generic_depth >= GENERIC_INSTANCE_DEPTH {
+            return CodegenError::err(node.clone(), ErrorRepr::GenericDepthReached);
+        }
+        let mut argument_names = Vec::new();
+        for type_argument in &type_arguments {
+            argument_names.push(DocItem::type_name(self, type_argument)?);
+        }
+        let name = format!("{}<{}>", self.get_context_name(template), argument_names.join(", "));
+        let declaration = self.generic_declarations.get(&template).expect("A generic context should have its declaration.").clone();
+        let first_context = self.contexts.len();
+        let instance = match &declaration.node_type {
+            NodeType::Struct(_, body, _) => self.scan_block_outline(body.clone(), ContextType::Struct, depth, parent_id, scope, Vec::new(), name)?,
+            _ => self.scan_function(&declaration, depth, parent_id, scope, comptime, name)?,
+        };
+        {
+            let mut instance_modify = self.context_borrow_mut(instance)?;
+            instance_modify.doc = doc;
+            instance_modify.instance_of = Some((template, type_arguments));
+        }
+        // Known before its types are worked out, so the instance can refer to itself
+        self.generic_instances.entry(template).or_default().push(instance);
+
+        self.generic_depth += 1;
+        let filled = self.fill_instance(first_context);
+        self.generic_depth -= 1;
+        filled?;
+        Ok(instance)
+    }
+
+    /// Works out the types and constants of the contexts scanned for a new instance, which are all the ones from `first_context` on.
+    fn fill_instance(&mut self, first_context: usize) -> Result<(), CodegenError> {
+        let last_context = self.contexts.len();
+        for context_id in first_context..last_context {
+            if self.is_generic_template(context_id)? {
+                continue;
+            }
+            self.fill_field_types(context_id)?;
+        }
+        for context_id in first_context..last_context {
+            if self.is_generic_template(context_id)? {
+                continue;
+            }
+            let constant_count = self.context_borrow(context_id)?.constants.len();
+            for constant_id in 0..constant_count {
+                self.constant_value(context_id, constant_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The type a name stands for inside an instance of a generic struct or func, which is one of its type arguments,
+    /// or the instance itself for the name of the struct.
+    fn get_type_argument(&self, context: usize, name: &str) -> Result<Option<RealtimeValueType>, CodegenError> {
+        let context_borrow = self.context_borrow(context)?;
+        let Some((template, type_arguments)) = &context_borrow.instance_of else {
+            return Ok(None);
+        };
+        if matches!(context_borrow.context_type, ContextType::Struct) && self.get_context_name(*template) == name {
+            return Ok(Some(RealtimeValueType::Struct(context)));
+        }
+        let parameter = self.context_borrow(*template)?.type_parameters.iter().position(|type_parameter| type_parameter == name);
+        Ok(parameter.and_then(|parameter| type_arguments[parameter].realtime_type()))
+    }
 
+    /// Instantiates a generic func for the types of the values it's called with, matching them against the types its parameters are declared with.
+    fn infer_generic_function(&mut self, template: usize, argument_types: &[ValueType], node: &Rc<Node>) -> Result<usize, CodegenError> {
+        let (type_parameters, fields) = {
+            let template_borrow = self.context_borrow(template)?;
+            (template_borrow.type_parameters.clone(), template_borrow.fields.clone())
+        };
+        if argument_types.len() > fields.len() {
+            return CodegenError::err(node.clone(), ErrorRepr::UnexpectedFunctionParameter);
+        }
+        if argument_types.len() < fields.len() {
+            return CodegenError::err(node.clone(), ErrorRepr::ExpectedFunctionParameter);
+        }
+        let mut inferred = HashMap::new();
+        for (field, argument_type) in fields.iter().zip(argument_types) {
+            if let ValueType::Ident(type_node) = &field.field_type {
+                self.infer_type_parameters(&type_parameters, type_node, argument_type, &mut inferred)?;
             }
         }
+        let mut type_arguments = Vec::new();
+        for type_parameter in &type_parameters {
+            let Some(type_argument) = inferred.remove(type_parameter) else {
+                return CodegenError::err(node.clone(), ErrorRepr::CantInferTypeArguments);
+            };
+            type_arguments.push(type_argument);
+        }
+        self.instantiate_generic(template, type_arguments, node)
+    }
+
+    /// Finds the type parameters used in a declared type, and the types they line up with in `value_type`.
+    /// The first type found for a parameter is kept, any other one has to be cast to it.
+    fn infer_type_parameters(&self, type_parameters: &[String], type_node: &Rc<Node>, value_type: &ValueType, inferred: &mut HashMap<String, ValueType>) -> Result<(), CodegenError> {
+        match (&type_node.node_type, value_type) {
+            (NodeType::Primary(..), _) => {
+                if let Ok(ident) = Self::get_primary_as_ident(type_node, ErrorRepr::ExpectedType) {
+                    if type_parameters.contains(ident) && value_type.realtime_type().is_some() {
+                        inferred.entry(ident.clone()).or_insert(value_type.clone());
+                    }
+                }
+            },
+            (NodeType::ListCall(inside_node, index_node), ValueType::Primitive(PrimitiveType::List(inside_type))) if matches!(index_node.node_type, NodeType::None) => {
+                self.infer_type_parameters(type_parameters, inside_node, inside_type, inferred)?;
+            },
+            (NodeType::Optional(inside_node), ValueType::Primitive(PrimitiveType::Optional(inside_type))) => {
+                self.infer_type_parameters(type_parameters, inside_node, inside_type, inferred)?;
+            },
+            (NodeType::Optional(inside_node), _) => {
+                self.infer_type_parameters(type_parameters, inside_node, value_type, inferred)?;
+            },
+            (NodeType::ListCall(mapped_node, mapping_node), ValueType::Primitive(PrimitiveType::Map(mapped_type, mapping_type))) => {
+                self.infer_type_parameters(type_parameters, mapped_node, mapped_type, inferred)?;
+                self.infer_type_parameters(type_parameters, mapping_node, mapping_type, inferred)?;
+            },
+            (NodeType::Generic(generic_ident, argument_nodes), ValueType::Primitive(PrimitiveType::Map(entry_type, key_type)))
+                if Self::get_primary_as_ident(generic_ident, ErrorRepr::ExpectedType).is_ok_and(|ident| ident == "map") => {
+                if let [key_node, entry_node] = argument_nodes.as_slice() {
+                    self.infer_type_parameters(type_parameters, key_node, key_type, inferred)?;
+                    self.infer_type_parameters(type_parameters, entry_node, entry_type, inferred)?;
+                }
+            },
+            (NodeType::Generic(_, argument_nodes), ValueType::Struct(struct_id)) => {
+                let instance_of = self.context_borrow(*struct_id)?.instance_of.clone();
+                if let Some((_, type_arguments)) = instance_of {
+                    for (argument_node, type_argument) in argument_nodes.iter().zip(type_arguments.iter()) {
+                        self.infer_type_parameters(type_parameters, argument_node, type_argument, inferred)?;
+                    }
+                }
+            },
+            _ => {}
+        }
         Ok(())
     }
 
@@ -411,12 +861,17 @@ impl CodeGen {
         let ValueType::Comptime(ComptimeType::Type(construct_field_type_realtime)) = self.generate_expression(context, node, GenerateExpressionSettings::comptime().prefer_category(IdentifierCategory::Type))?.value.value_type else {
             return CodegenError::err(node.clone(), ErrorRepr::ExpectedType);
         };
+        if let RealtimeValueType::Struct(struct_id) = construct_field_type_realtime {
+            if self.is_generic(struct_id)? {
+                return CodegenError::err(node.clone(), ErrorRepr::ExpectedTypeArguments);
+            }
+        }
         return Ok(construct_field_type_realtime.normalize());
     }
 
     fn find_function_by_node(&self, node: &Rc<Node>, context: usize) -> Result<usize, CodegenError> {
         let definition = self.find_definition_by_node(node, context)?;
-        Ok(self.extract_definition_function(&definition)?)
+        self.extract_definition_function(&definition).map_err(|e| e.or_node(node))
     }
 
     fn find_function_by_ident(&self, ident: &Rc<Node>, context: usize) -> Result<usize, CodegenError> {
@@ -482,6 +937,23 @@ impl CodeGen {
         }
     }
 
+    fn extract_definition_constant(&self, definition: &CodeDefinition) -> Result<usize, CodegenError> {
+        match definition {
+            CodeDefinition::Constant(constant) => Ok(*constant),
+            CodeDefinition::Multiple(definitions) => {
+                let mut result = CodegenError::err_headless(ErrorRepr::ExpectedConstantExpression);
+                for check_definition in definitions {
+                    if let CodeDefinition::Constant(constant) = check_definition {
+                        result = Ok(*constant);
+                        break;
+                    }
+                }
+                result
+            },
+            _ => CodegenError::err_headless(ErrorRepr::ExpectedConstantExpression)
+        }
+    }
+
     fn extract_definition_struct(&self, definition: &CodeDefinition) -> Result<usize, CodegenError> {
         let context = self.extract_definition_context(definition, |f| matches!(f, ContextType::Struct))?;
         
@@ -491,6 +963,10 @@ impl CodeGen {
         Ok(context)
     }
 
+    fn extract_definition_enum(&self, definition: &CodeDefinition) -> Result<usize, CodegenError> {
+        self.extract_definition_context(definition, |f| matches!(f, ContextType::Enum))
+    }
+
     fn extract_definition_domain(&self, definition: &CodeDefinition) -> Result<usize, CodegenError> {
         let context = self.extract_definition_context(definition, |f| matches!(f, ContextType::Domain))?;
         
@@ -508,9 +984,15 @@ impl CodeGen {
         Ok(context)
     }
 
-    fn find_definition_by_node(&self, mut node: &Rc<Node>, mut context: usize) -> Result<CodeDefinition, CodegenError> {
+    fn find_definition_by_node(&self, node: &Rc<Node>, context: usize) -> Result<CodeDefinition, CodegenError> {
+        Ok(self.find_definition_owner_by_node(node, context)?.1)
+    }
+
+    /// Same as [Self::find_definition_by_node], but also gives back the context the definition was found in.
+    fn find_definition_owner_by_node(&self, mut node: &Rc<Node>, mut context: usize) -> Result<(usize, CodeDefinition), CodegenError> {
+        let origin_context = context;
         // access stuff
-        let no_depth = if let Node::Access(access_parent, access_child) = node.as_ref() {
+        let no_depth = if let NodeType::Access(access_parent, access_child) = &node.node_type {
             context = self.find_full_context_by_ident(access_parent, context)?;
             node = access_child;
             true
@@ -524,7 +1006,10 @@ impl CodeGen {
             let context_borrow = self.context_borrow(context)?;
             match context_borrow.definition_lookup.get(ident_string) {
                 Some(def) => {
-                    return Ok(def.clone());
+                    if no_depth {
+                        self.check_definition_visible(origin_context, context, def, node)?;
+                    }
+                    return Ok((context, def.clone()));
                 }
                 None => {
                     if context_borrow.id == context_borrow.parent_id || no_depth {
@@ -537,6 +1022,45 @@ impl CodeGen {
         }
     }
 
+    /// Whether a definition looked up in the `owner` context was declared `private`.
+    pub(crate) fn is_definition_private(&self, owner: usize, definition: &CodeDefinition) -> Result<bool, CodegenError> {
+        Ok(match definition {
+            CodeDefinition::Field(field_id) => self.context_borrow(owner)?.fields[*field_id].scope == CodeScope::Private,
+            CodeDefinition::Context(context_id) => self.context_borrow(*context_id)?.scope == CodeScope::Private,
+            CodeDefinition::Constant(constant_id) => self.context_borrow(owner)?.constants[*constant_id].scope == CodeScope::Private,
+            CodeDefinition::Multiple(definitions) => {
+                for definition in definitions {
+                    if self.is_definition_private(owner, definition)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+        })
+    }
+
+    /// Whether the context is the `owner` context or nested somewhere inside of it.
+    fn is_inside_context(&self, mut context: usize, owner: usize) -> Result<bool, CodegenError> {
+        loop {
+            if context == owner {
+                return Ok(true);
+            }
+            let parent_id = self.context_borrow(context)?.parent_id;
+            if parent_id == context {
+                return Ok(false);
+            }
+            context = parent_id;
+        }
+    }
+
+    /// Private definitions can only be accessed by code inside the struct or domain (`owner`) that declared them.
+    fn check_definition_visible(&self, context: usize, owner: usize, definition: &CodeDefinition, node: &Rc<Node>) -> Result<(), CodegenError> {
+        if self.is_definition_private(owner, definition)? && !self.is_inside_context(context, owner)? {
+            return CodegenError::err(node.clone(), ErrorRepr::PrivateDefinitionAccess);
+        }
+        Ok(())
+    }
+
     fn find_definition_by_ident(&self, ident: &Rc<Node>, context: usize) -> Result<CodeDefinition, CodegenError> {
         let ident_string = Self::get_primary_as_ident(ident, ErrorRepr::ExpectedDefinitionIdent)?;
         let context_borrow = self.context_borrow(context)?;
@@ -546,11 +1070,11 @@ impl CodeGen {
     }
 
     fn find_full_context_by_ident(&self, ident: &Rc<Node>, mut context: usize) -> Result<usize, CodegenError> {
-        match ident.as_ref() {
-            Node::Primary(_token) => {
+        match &ident.node_type {
+            NodeType::Primary(_token) => {
                 Ok(self.find_domain_by_ident(ident, context, None)?)
             }
-            Node::Access(access_parent, access_field) => {
+            NodeType::Access(access_parent, access_field) => {
                 context = self.find_full_context_by_ident(access_parent, context)?;
                 Ok(self.find_domain_by_ident(access_field, context, Some(1))?)
             }
@@ -586,8 +1110,28 @@ impl CodeGen {
     }
 
     fn generate_all_code(&mut self) -> Result<(), CodegenError> {
-        for context_id in 0..self.contexts.len() {
-            self.generate_code(context_id)?;
+        // A `const func` only gets code if something calls it at runtime, which its own code can do as well.
+        // Generic instances can be made while generating code too, so the contexts added since are generated until there are none left.
+        let mut generated = HashSet::new();
+        let mut next_context = 0;
+        loop {
+            while next_context < self.contexts.len() {
+                let context_id = next_context;
+                next_context += 1;
+                if self.context_borrow(context_id)?.comptime || self.is_generic_template(context_id)? {
+                    continue;
+                }
+                self.generate_code(context_id)?;
+            }
+            let mut pending: Vec<usize> = self.runtime_const_functions.difference(&generated).cloned().collect();
+            if pending.is_empty() {
+                break;
+            }
+            pending.sort();
+            for context_id in pending {
+                generated.insert(context_id);
+                self.generate_code(context_id)?;
+            }
         }
         Ok(())
     }
@@ -611,7 +1155,7 @@ impl CodeGen {
             ContextType::Function(FunctionType::Event(..)) => {
                 self.generate_function_code(context, body, fields, ValueType::Primitive(PrimitiveType::None))?;
             },
-            ContextType::Domain => {
+            ContextType::Domain | ContextType::Enum => {
 
             },
         }
@@ -649,13 +1193,13 @@ impl CodeGen {
         let ValueType::Struct(struct_type) = construct_field_type else {
             return CodegenError::err(construct_ident.clone(), ErrorRepr::ExpectedStructIdentifier);
         };
-        let Node::Block(construct_body) = construct_body_node.as_ref() else {
+        let NodeType::Block(construct_body) = &construct_body_node.node_type else {
             return CodegenError::err(construct_body_node.clone(), ErrorRepr::ExpectedBlock);
         };
         let mut param_map = HashMap::new();
         let register_group = self.buffer.allocate_line_register_group();
         for construct_statement in construct_body {
-            let Node::Assignment(assigned_node, assigned_value) = construct_statement.as_ref() else {
+            let NodeType::Assignment(assigned_node, assigned_value) = &construct_statement.node_type else {
                 return CodegenError::err(construct_body_node.clone(), ErrorRepr::ExpectedFieldAssignment);
             };
             let assigned_ident = Self::get_primary_as_ident(assigned_node, ErrorRepr::ExpectedFieldAssignment)?;
@@ -672,12 +1216,54 @@ impl CodeGen {
             }
             param_map.insert(field, field_expression.value.ident);
         }
+        let fields = self.context_borrow(struct_type)?.fields.clone();
+        for (field_id, field) in fields.iter().enumerate() {
+            if param_map.contains_key(&field_id) {
+                continue;
+            }
+            if let Some(default_value) = &field.default_value {
+                let default_ident = self.generate_field_default(struct_type, default_value, &field.field_type, register_group)?;
+                param_map.insert(field_id, default_ident);
+            } else if matches!(field.field_type, ValueType::Primitive(PrimitiveType::Optional(..))) {
+                param_map.insert(field_id, self.buffer.use_string(NONE_SENTINEL));
+            }
+        }
         self.create_struct_instance(construct_body_node, struct_type, set_ident, param_map)?;
         //println!("Struct: {:#?}", construct_body);
         self.buffer.free_line_register_group(register_group);
         Ok(construct_field_type)
     }
 
+    /// Generates the initializer of a struct field, which is evaluated inside the struct rather than where it's constructed.
+    fn generate_field_default(&mut self, struct_type: usize, default_value: &Rc<Node>, field_type: &ValueType, register_group: u64) -> Result<u32, CodegenError> {
+        let default_expression = self.generate_expression(struct_type, default_value, GenerateExpressionSettings::parameter(register_group).expect_type(field_type))?;
+        if &default_expression.value.value_type != field_type {
+            return CodegenError::err(default_value.clone(), ErrorRepr::UnexpectedStructFieldType);
+        }
+        Ok(default_expression.value.ident)
+    }
+
+    /// Creates the instance a constructor is called on, with every field set to its initializer or the default value of its type.
+    fn create_default_struct_instance(&mut self, node: &Rc<Node>, struct_type: usize, set_ident: u32) -> Result<(), CodegenError> {
+        let register_group = self.buffer.allocate_line_register_group();
+        let fields = self.context_borrow(struct_type)?.fields.clone();
+        let mut field_map = HashMap::new();
+        for (field_id, field) in fields.iter().enumerate() {
+            let field_ident = match &field.default_value {
+                Some(default_value) => self.generate_field_default(struct_type, default_value, &field.field_type, register_group)?,
+                None => {
+                    let field_ident = self.buffer.allocate_grouped_line_register(register_group);
+                    self.get_default_type_value(&field.field_type, field_ident)?;
+                    field_ident
+                }
+            };
+            field_map.insert(field_id, field_ident);
+        }
+        self.create_struct_instance(node, struct_type, set_ident, field_map)?;
+        self.buffer.free_line_register_group(register_group);
+        Ok(())
+    }
+
     fn create_struct_instance(&mut self, node: &Rc<Node>, struct_type: usize, set_ident: u32, field_map: HashMap<usize, u32>) -> Result<(), CodegenError> {
         let mut instruction_push = instruction!(Var::CreateList, [
             (Ident, set_ident)
@@ -731,6 +1317,28 @@ impl CodeGen {
                     (Ident, set_ident)
                 ]))
             },
+            ValueType::Enum(enum_id) => {
+                // The first variant is the default, as long as it has no payload to fill in
+                let first_has_payload = self.context_borrow(*enum_id)?.variants.first().map_or(true, |variant| !variant.fields.is_empty());
+                if first_has_payload {
+                    return CodegenError::err_headless(ErrorRepr::ExpectedEnumDefault);
+                }
+                if self.enum_has_payloads(*enum_id) {
+                    self.buffer.code_buffer.push_instruction(instruction!(Var::CreateList, [
+                        (Ident, set_ident), (Int, 0)
+                    ]))
+                } else {
+                    self.buffer.code_buffer.push_instruction(instruction!(Var::Set, [
+                        (Ident, set_ident), (Int, 0)
+                    ]))
+                }
+            },
+            ValueType::Primitive(PrimitiveType::Optional(..)) => {
+                let none = self.buffer.use_string(NONE_SENTINEL);
+                self.buffer.code_buffer.push_instruction(instruction!(Var::Set, [
+                    (Ident, set_ident), (Ident, none)
+                ]))
+            },
             ValueType::Primitive(PrimitiveType::Vector) => {
                 self.buffer.code_buffer.push_instruction(instruction!(Var::Vector, [
                     (Ident, set_ident), (Int, 0), (Int, 0), (Int, 0)
@@ -773,16 +1381,20 @@ impl CodeGen {
         self.find_variable_by_name_full(context, var_name, node)
     }
 
-    fn get_context_name(&self, context: usize) -> &String {
+    pub(crate) fn get_context_name(&self, context: usize) -> &String {
         &self.context_names[context]
     }
 
+    pub(crate) fn get_field_name(&self, context: usize, field: usize) -> &String {
+        &self.field_names[context][field]
+    }
+
     fn get_context_full_name(&self, context: usize) -> &String {
         &self.context_full_names[context]
     }
 
     fn get_shallow_domain_access(&self, context: usize, access: &Rc<Node>) -> Result<Option<usize>, CodegenError> {
-        if let Node::Primary(token) = access.as_ref() {
+        if let NodeType::Primary(token) = &access.node_type {
             if matches!(token.token_type, TokenType::Ident(..)) {
                 if let CodeDefinition::Context(domain_context) = self.find_definition_by_ident(&access, context)?{
                     if matches!(self.context_borrow(domain_context)?.context_type, ContextType::Domain) {
@@ -852,6 +1464,22 @@ impl CodeGen {
                 CodegenTrace::root(var.variable.ident)
             ), IdentifierCategory::RuntimeVariable));
         }
+
+        // Constants, which are all worked out before any code is generated
+        let constant_value = {
+            let context_borrow = self.context_borrow(context)?;
+            context_borrow.definition_lookup.get(var_name)
+                .and_then(|definition| self.extract_definition_constant(definition).ok())
+                .and_then(|constant_id| context_borrow.constants[constant_id].value.clone())
+        };
+        if let Some(constant_value) = constant_value {
+            found.push((CodegenExpressionResult::value(self.use_constant(&constant_value, settings, register_group)), IdentifierCategory::Constant));
+        }
+
+        // Type parameters of generic instances
+        if let Some(type_argument) = self.get_type_argument(context, var_name)? {
+            found.push((CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(type_argument))), IdentifierCategory::Type));
+        }
         
         if matches!(self.get_context_type(context)?, ContextType::Domain) {
             if let Ok(definition) = &self.find_definition_by_ident(node, context) {
@@ -869,6 +1497,11 @@ impl CodeGen {
                 if let Ok(struct_id) = self.extract_definition_struct(definition) {
                     found.push((CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(RealtimeValueType::Struct(struct_id)))), IdentifierCategory::Type));
                 } 
+
+                // Enum (Type)
+                if let Ok(enum_id) = self.extract_definition_enum(definition) {
+                    found.push((CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(RealtimeValueType::Enum(enum_id)))), IdentifierCategory::Type));
+                }
                 // Fields (Global Variables a.k.a Domain Variables)
                 if let Ok(domain_definition) = self.find_context_field(context, node) {
                     let domain_var_value = &self.domain_vars[context][domain_definition.index].variable;
@@ -934,17 +1567,9 @@ impl CodeGen {
                 CodegenTraceCrumbIdent::Index(register_index)
             },
             CodegenTraceCrumb::EntryNode(node) => {
-                let index = self.generate_expression_inside(context, &node, GenerateExpressionSettings::parameter(register_group), register_group)?.value.clone();
-                let register_index = if !matches!(index.value_type, ValueType::Primitive(PrimitiveType::String)) {
-                    let temp_reg = self.buffer.allocate_grouped_line_register(register_group);
-                    self.buffer.code_buffer.push_instruction(instruction!(
-                        Var::String, [ (Ident, index.ident), (String, " "), (Ident, temp_reg) ]
-                    ));
-                    temp_reg
-                } else {
-                    index.ident
-                };
-                CodegenTraceCrumbIdent::Entry(register_index)
+                let settings = GenerateExpressionSettings::parameter(register_group);
+                let index = self.generate_expression_inside(context, &node, settings.clone(), register_group)?.value.clone();
+                CodegenTraceCrumbIdent::Entry(self.generate_map_key(index, &settings, register_group))
             }
         })
     }
@@ -1060,35 +1685,177 @@ impl CodeGen {
         }
     }
 
-    fn implicitly_cast(&mut self, context: usize, root_node: &Rc<Node>, mut result: CodegenExpressionResult, settings: &GenerateExpressionSettings, register_group: u64) -> Result<CodegenExpressionResult, CodegenError> {
+    /// Generates both sides of `==` or `!=`, where the right side is made into an optional to be compared with an optional left side, like `player == none`.
+    ///
+    /// Enum values with payloads are compared by their tags, so one of the sides has to be a variant without a payload, like `state == State.Waiting`.
+    fn generate_equality_sides(&mut self, context: usize, l_node: &Rc<Node>, r_node: &Rc<Node>, settings: &GenerateExpressionSettings, register_group: u64) -> Result<(CodegenValue, CodegenValue), CodegenError> {
+        let l = self.generate_expression_inside(context, l_node, settings.pass(), register_group)?.value.clone();
+        let r_settings = match &l.value_type {
+            ValueType::Primitive(PrimitiveType::Optional(..)) => settings.pass().expect_type(&l.value_type),
+            _ => settings.pass(),
+        };
+        let r = self.generate_expression_inside(context, r_node, r_settings, register_group)?.value.clone();
+        if let ValueType::Enum(enum_id) = l.value_type {
+            if l.value_type == r.value_type && self.enum_has_payloads(enum_id) {
+                if !self.is_unit_variant(context, l_node)? && !self.is_unit_variant(context, r_node)? {
+                    return CodegenError::err(r_node.clone(), ErrorRepr::EnumPayloadComparison);
+                }
+                return Ok((self.get_enum_tag(l, settings, register_group), self.get_enum_tag(r, settings, register_group)));
+            }
+        }
+        Ok((l, r))
+    }
+
+    fn is_unit_variant(&mut self, context: usize, node: &Rc<Node>) -> Result<bool, CodegenError> {
+        let Some((enum_id, variant_id)) = self.get_enum_variant(context, node)? else {
+            return Ok(false);
+        };
+        Ok(self.context_borrow(enum_id)?.variants[variant_id].fields.is_empty())
+    }
+
+    /// Dictionaries are keyed by strings, so a key of any other type is written as a string first.
+    fn generate_map_key(&mut self, key: CodegenValue, settings: &GenerateExpressionSettings, register_group: u64) -> u32 {
+        if matches!(key.value_type, ValueType::Primitive(PrimitiveType::String)) {
+            return key.ident;
+        }
+        let register = self.buffer.allocate_grouped_line_register(register_group);
+        self.push_expression_instruction(settings, instruction!(
+            Var::String, [ (Ident, register), (Ident, key.ident) ]
+        ));
+        register
+    }
+
+    fn implicitly_cast(&mut self, context: usize, root_node: &Rc<Node>, mut result: CodegenExpressionResult, settings: &GenerateExpressionSettings, register_group: u64) -> Result<CodegenExpressionResult, CodegenError> {
         let Some(value_type) = settings.expected_type.clone() else {
             return Ok(result);
         };
         if value_type == result.value.value_type {
             return Ok(result);
         }
-        match (&result.value.value_type, &value_type) { //variable we have vs variable we want (num -> string, vec -> location, etc)
-            (ValueType::Primitive(PrimitiveType::Number), ValueType::Primitive(PrimitiveType::String)) => {
-                let register = self.generate_expression_allocate_register(&settings, register_group);
-                self.push_expression_instruction(&settings, instruction!(
-                    Var::String, [(Ident, register), (Ident, result.value.ident)]
+        //variable we have vs variable we want (num -> string, vec -> location, etc)
+        let Some(cast) = CodegenCast::find(&result.value.value_type, &value_type) else {
+            if let ValueType::Primitive(PrimitiveType::Optional(inside_type)) = &result.value.value_type {
+                if CodegenCast::find(inside_type, &value_type).is_some() {
+                    return CodegenError::err(root_node.clone(), ErrorRepr::UnwrappedOptional);
+                }
+            }
+            return CodegenError::err(root_node.clone(), ErrorRepr::CantImplicitlyCast);
+        };
+        if !cast.is_implicit() {
+            return CodegenError::err(root_node.clone(), ErrorRepr::ExpectedExplicitCast);
+        }
+        let register = match cast {
+            CodegenCast::Retype => result.value.ident,
+            _ => self.generate_expression_allocate_register(settings, register_group),
+        };
+        result.value = self.cast_value(&cast, result.value, &value_type, register, settings, register_group).map_err(|e| e.or_node(root_node))?;
+
+        return Ok(result);
+    }
+
+    /// Converts a value into the type a [CodegenCast] was found for, writing it into `register`.
+    ///
+    /// The value may already be in `register`, so every conversion reads the value before it writes the register.
+    fn cast_value(&mut self, cast: &CodegenCast, value: CodegenValue, value_type: &ValueType, register: u32, settings: &GenerateExpressionSettings, register_group: u64) -> Result<CodegenValue, CodegenError> {
+        match cast {
+            CodegenCast::Retype => {
+                return Ok(CodegenValue::new(value.ident, value_type.clone()));
+            }
+            CodegenCast::NumberToString => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::String, [(Ident, register), (Ident, value.ident)]
                 ));
-                result.value.ident = register;
-                result.value.value_type = value_type.clone();
             }
-            (ValueType::Primitive(PrimitiveType::Vector), ValueType::Primitive(PrimitiveType::Location)) => {
-                let register = self.generate_expression_allocate_register(&settings, register_group);
+            CodegenCast::BoolToString => {
+                let true_string = self.buffer.use_string("true");
+                let false_string = self.buffer.use_string("false");
+                self.push_expression_instruction(settings, instruction!(
+                    Varif::Eq, [(Ident, value.ident), (Int, 1)]
+                ));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Set, [(Ident, register), (Ident, true_string)]
+                ));
+                self.push_expression_instruction(settings, instruction!(Else));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Set, [(Ident, register), (Ident, false_string)]
+                ));
+                self.push_expression_instruction(settings, instruction!(EndIf));
+            }
+            CodegenCast::StringToNumber => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::ParseNumber, [(Ident, register), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::NumberToBool => {
+                self.push_expression_instruction(settings, instruction!(
+                    Varif::Eq, [(Ident, value.ident), (Int, 0)]
+                ));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Set, [(Ident, register), (Int, 0)]
+                ));
+                self.push_expression_instruction(settings, instruction!(Else));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Set, [(Ident, register), (Int, 1)]
+                ));
+                self.push_expression_instruction(settings, instruction!(EndIf));
+            }
+            CodegenCast::VectorToLocation => {
                 let location = self.buffer.use_location(ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0));
-                self.push_expression_instruction(&settings, instruction!(
-                    Var::ShiftOnVector, [(Ident, register), (Ident, location), (Ident, result.value.ident)]
+                self.push_expression_instruction(settings, instruction!(
+                    Var::ShiftOnVector, [(Ident, register), (Ident, location), (Ident, value.ident)]
                 ));
-                result.value.ident = register;
-                result.value.value_type = value_type.clone();
             }
-            _ => { return CodegenError::err(root_node.clone(), ErrorRepr::CantImplicitlyCast); }
-        };
-
-        return Ok(result);
+            CodegenCast::LocationToVector => {
+                let location = self.buffer.use_location(ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0), ParameterValue::Int(0));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::VectorBetween, [(Ident, register), (Ident, location), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::StringToItem => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::SetItemType, [(Ident, register), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::StringToSound => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::SetSoundType, [(Ident, register), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::StringToParticle => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::SetParticleType, [(Ident, register), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::StringToPotion => {
+                self.push_expression_instruction(settings, instruction!(
+                    Var::SetPotionType, [(Ident, register), (Ident, value.ident)]
+                ));
+            }
+            CodegenCast::List(element_cast) => {
+                let ValueType::Primitive(PrimitiveType::List(element_type)) = value_type else {
+                    return CodegenError::err_headless(ErrorRepr::ExpectedListType);
+                };
+                // The list is built aside, since the register might be the list that's being read.
+                let cast_list = self.buffer.allocate_grouped_line_register(register_group);
+                let element = self.buffer.allocate_grouped_line_register(register_group);
+                let cast_element = self.buffer.allocate_grouped_line_register(register_group);
+                self.push_expression_instruction(settings, instruction!(
+                    Var::CreateList, [(Ident, cast_list)]
+                ));
+                self.push_expression_instruction(settings, instruction!(
+                    Rep::ForEach, [(Ident, element), (Ident, value.ident)]
+                ));
+                let cast_element = self.cast_value(element_cast, CodegenValue::new(element, ValueType::Primitive(PrimitiveType::None)), element_type, cast_element, settings, register_group)?;
+                self.push_expression_instruction(settings, instruction!(
+                    Var::AppendValue, [(Ident, cast_list), (Ident, cast_element.ident)]
+                ));
+                self.push_expression_instruction(settings, instruction!(EndRep));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Set, [(Ident, register), (Ident, cast_list)]
+                ));
+            }
+        }
+        Ok(CodegenValue::new(register, value_type.clone()))
     }
 
     fn generate_expression(&mut self, context: usize, root_node: &Rc<Node>, settings: GenerateExpressionSettings) -> Result<CodegenExpressionResult, CodegenError> {
@@ -1096,17 +1863,22 @@ impl CodeGen {
         expression_stack.push_back(CodegenExpressionStack::Node(root_node));
         let register_group = self.buffer.allocate_line_register_group();
         // println!("{:#?}", root_node.clone());
-        let result = self.generate_expression_inside(context, root_node, settings.clone(), register_group)?;
+        let result = self.generate_expression_inside(context, root_node, settings.clone(), register_group).map_err(|e| e.or_node(root_node))?;
         self.buffer.free_line_register_group(register_group);
         Ok(result)
     }
     
 
     fn generate_expression_inside(&mut self, context: usize, node: &Rc<Node>, settings: GenerateExpressionSettings, register_group: u64) -> Result<CodegenExpressionResult, CodegenError> {
+        if let Some((target, target_result)) = &self.compound_target {
+            if Rc::ptr_eq(target, node) {
+                return Ok(target_result.clone());
+            }
+        }
         let mut value = CodegenValue::default();
         let mut trace = None;
-        match node.as_ref() {
-            Node::Primary(token) => {
+        match &node.node_type {
+            NodeType::Primary(token) => {
                 let set_value = settings.depth == 0 && settings.variable_necessary; // if the depth is 0, that means this is the ONLY thing in the expression, hence we need to set the final variable.
                 value = match &token.token_type {
                     TokenType::Ident(_ident) => {
@@ -1115,15 +1887,15 @@ impl CodeGen {
                         if matches!(self.get_context_type(parent_context)?, ContextType::Struct) {
                             // This might be a stupid workaround or a genius one, but i am creating a fake node to pretend self is being accessed.
                             // This can be explained as when you type ``hp`` inside a Player { num hp; } struct, it first checks if you mean ``self.hp`` then does the other normal things.
-                            let fake_access_node = Rc::new(Node::Access(
-                                Rc::new(Node::Primary(
+                            let fake_access_node = Rc::new(Node::new(NodeType::Access(
+                                Rc::new(Node::new(NodeType::Primary(
                                     Rc::new(Token {
                                         token_type: TokenType::Keyword(Keyword::Value(ValuedKeyword::SelfIdentity)),
-                                        range: Range::new((0,0), (0,0))}
+                                        range: node.range.clone()}
                                     )
-                                )),     
+                                ), node.range.clone())),
                                 node.clone()
-                            ));
+                            ), node.range.clone()));
 
                             if let Ok(result) = self.generate_expression(context, &fake_access_node, settings.clone()) {
                                 return Ok(result);
@@ -1158,6 +1930,12 @@ impl CodeGen {
                                     ValueType::Struct(parent_context)
                                 )
                             }
+                            ValuedKeyword::None => {
+                                let Some(optional_type @ ValueType::Primitive(PrimitiveType::Optional(..))) = settings.expected_type.clone() else {
+                                    return CodegenError::err(node.clone(), ErrorRepr::CantInferNone);
+                                };
+                                CodegenValue::new(self.buffer.use_string(NONE_SENTINEL), optional_type)
+                            }
                             ValuedKeyword::Event => {
                                 let Some(event_context) = self.context_listening.get(&context) else {
                                     return CodegenError::err(node.clone(), ErrorRepr::EventInNonListenerCode);
@@ -1188,7 +1966,7 @@ impl CodeGen {
                     ))
                 }
             }
-            Node::Vector(xn, yn, zn) => {
+            NodeType::Vector(xn, yn, zn) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let temp_group = self.buffer.allocate_line_register_group();
                 let x = self.generate_expression_inside(context, xn, GenerateExpressionSettings::parameter(temp_group).expect_type(&ValueType::Primitive(PrimitiveType::Number)), register_group)?.value.clone();
@@ -1201,7 +1979,7 @@ impl CodeGen {
                 ));
                 self.buffer.free_line_register_group(temp_group);
             }
-            Node::Location(xn, yn, zn, pitchn, yawn) => {
+            NodeType::Location(xn, yn, zn, pitchn, yawn) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let temp_group = self.buffer.allocate_line_register_group();
                 let x = self.generate_expression_inside(context, xn, GenerateExpressionSettings::parameter(temp_group).expect_type(&ValueType::Primitive(PrimitiveType::Number)), register_group)?.value.clone();
@@ -1216,22 +1994,34 @@ impl CodeGen {
                 ));
                 self.buffer.free_line_register_group(temp_group);
             }
-            Node::Construct(construct_ident, construct_body) => {
+            NodeType::Construct(construct_ident, construct_body) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let created_struct = self.create_struct_instance_from_node(context, construct_ident, construct_body, register)?;
                 value = CodegenValue::new(register, created_struct);
             }
-            Node::Declaration(decl_type, decl_ident) => {
+            NodeType::Declaration(decl_type, decl_ident) => {
+                if Self::is_inferred_type(decl_type) { // A `let` without a value
+                    return CodegenError::err(node.clone(), ErrorRepr::CantInferType);
+                }
                 value = self.declare_runtime_variable(context, decl_type, decl_ident)?.0.variable.clone();
                 trace = Some(CodegenTrace::root(value.ident));
             }
-            Node::Assignment(assign_var_node, assign_value_node) => {
-                let assign_var = self.generate_expression_inside(context, assign_var_node, GenerateExpressionSettings::comptime(), register_group)?.clone();
+            NodeType::Assignment(assign_var_node, assign_value_node) => {
+                let assign_var = match &assign_var_node.node_type {
+                    NodeType::Declaration(decl_type, decl_ident) if Self::is_inferred_type(decl_type) => {
+                        let inferred_type = self.generate_expression_inside(context, assign_value_node, GenerateExpressionSettings::comptime(), register_group)?.value.value_type;
+                        if !Self::is_type_inferable(&inferred_type) {
+                            return CodegenError::err(assign_value_node.clone(), ErrorRepr::CantInferType);
+                        }
+                        let variable = self.declare_runtime_variable_typed(context, inferred_type, decl_ident)?.0.variable.clone();
+                        CodegenExpressionResult::trace(variable.clone(), CodegenTrace::root(variable.ident))
+                    }
+                    _ => self.generate_expression_inside(context, assign_var_node, GenerateExpressionSettings::comptime(), register_group)?.clone(),
+                };
                 value = assign_var.value.clone();
                 trace = assign_var.trace;
-                //TODO: Hardcoded casting options (to cast vectors to locations and whatnot)
                 let Some(trace_set) = trace.clone() else {
-                    return CodegenError::err(assign_var_node.clone(), ErrorRepr::NoTraceCantAssign)
+                    return CodegenError::err(assign_var_node.clone(), self.untraced_assignment_error(context, assign_var_node))
                 };
                 let assign_value = if trace_set.crumbs.len() == 0 { // No access variable, set_ident method
                     self.generate_expression_inside(context, assign_value_node, GenerateExpressionSettings::ident(value.ident).expect_type(&assign_var.value.value_type), register_group)?
@@ -1243,12 +2033,46 @@ impl CodeGen {
                     assign_value
                 };
             }
-            Node::FunctionCall(func_ident, func_params) => {
-                let register = self.generate_expression_allocate_register(&settings, register_group);
-                let func_type = self.call_function(context, func_ident, func_params, register)?;
-                value = CodegenValue::new(register, func_type);
+            NodeType::CompoundAssignment(assign_var_node, operation_node) => {
+                // The target is read once here, and the operation (which holds the same target node) reuses what was read
+                let param_group = self.buffer.allocate_line_register_group();
+                let assign_var = self.generate_expression_inside(context, assign_var_node, GenerateExpressionSettings::parameter(param_group).keep_trace_indices(), register_group)?.clone();
+                value = assign_var.value.clone();
+                trace = assign_var.trace.clone();
+                let Some(trace_set) = trace.clone() else {
+                    return CodegenError::err(assign_var_node.clone(), self.untraced_assignment_error(context, assign_var_node))
+                };
+                let outer_target = self.compound_target.replace((assign_var_node.clone(), assign_var.clone()));
+                let operation_value = if trace_set.crumbs.len() == 0 { // The operation writes straight into the variable, as a single action
+                    self.generate_expression_inside(context, operation_node, GenerateExpressionSettings::ident(value.ident).expect_type(&assign_var.value.value_type), register_group)
+                } else {
+                    self.generate_expression_inside(context, operation_node, GenerateExpressionSettings::parameter(param_group).expect_type(&assign_var.value.value_type), register_group)
+                };
+                self.compound_target = outer_target;
+                let operation_value = operation_value?;
+                if !trace_set.crumbs.is_empty() {
+                    self.set_trace_to_value(context, trace_set.clone(), operation_value.value.clone())?;
+                }
+                self.buffer.free_line_register_group(param_group);
+            }
+            NodeType::FunctionCall(func_ident, func_params) => {
+                if let Some(constant) = self.try_const_function_call(context, func_ident, func_params, node)? {
+                    value = self.use_constant(&constant, &settings, register_group);
+                    if settings.depth == 0 && settings.variable_necessary {
+                        let register = self.generate_expression_allocate_register(&settings, register_group);
+                        if value.ident != register {
+                            self.push_expression_instruction(&settings, instruction!(
+                                Var::Set, [ (Ident, register), (Ident, value.ident) ] ));
+                            value.ident = register;
+                        }
+                    }
+                } else {
+                    let register = self.generate_expression_allocate_register(&settings, register_group);
+                    let func_type = self.call_function(context, func_ident, func_params, register)?;
+                    value = CodegenValue::new(register, func_type);
+                }
             }
-            Node::Access(accessed, access_field) => {
+            NodeType::Access(accessed, access_field) => {
                 let s = settings.pass();
                 let accessed_expression: CodegenExpressionResult = self.generate_expression_inside(context, accessed, s.clone(), register_group)?.clone();
                 let accessed_value = accessed_expression.value;
@@ -1265,6 +2089,7 @@ impl CodeGen {
                             .definition_lookup
                             .get(access_field_ident)
                             .ok_or(CodegenError::new(access_field.clone(), ErrorRepr::InvalidStructDefinition))?;
+                        self.check_definition_visible(context, struct_id, definition, access_field)?;
                     
                         if let Ok(field_id) = self.extract_definition_field(definition) {
                             let field_type = struct_context.fields[field_id].field_type.clone();
@@ -1282,6 +2107,13 @@ impl CodeGen {
                         } else if let Ok(func_id) = self.extract_definition_function(definition) {
                             drop(struct_context);
                             return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::SelfFunction(func_id, accessed_value.ident))))
+                        } else if let Ok(constant_id) = self.extract_definition_constant(definition) {
+                            let constant_value = struct_context.constants[constant_id].value.clone();
+                            drop(struct_context);
+                            let Some(constant_value) = constant_value else {
+                                return CodegenError::err(access_field.clone(), ErrorRepr::ExpectedConstantExpression);
+                            };
+                            value = self.use_constant(&constant_value, &settings, register_group);
                         } else {
                             panic!("Invalid struct access found which *is* correctly looked up but is neither a field nor a function.");
                         }
@@ -1320,6 +2152,32 @@ impl CodeGen {
                         };
                         trace = None;
                     }
+                    ValueType::Primitive(PrimitiveType::Optional(..)) => {
+                        return CodegenError::err(accessed.clone(), ErrorRepr::UnwrappedOptional);
+                    }
+                    ValueType::Comptime(ComptimeType::Type(RealtimeValueType::Enum(enum_id))) => {
+                        let variant_id = self.find_enum_variant(enum_id, access_field)?;
+                        // A variant with a payload is only a value once it's called with it
+                        if !self.context_borrow(enum_id)?.variants[variant_id].fields.is_empty() {
+                            if settings.expected_type.is_some() {
+                                return CodegenError::err(node.clone(), ErrorRepr::ExpectedEnumPayload);
+                            }
+                            return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::EnumVariant(enum_id, variant_id))));
+                        }
+                        value = self.use_constant(&CodegenConstant::Enum(enum_id, variant_id, Vec::new()), &settings, register_group);
+                        if settings.depth == 0 && settings.variable_necessary && !self.enum_has_payloads(enum_id) {
+                            let register = self.generate_expression_allocate_register(&settings, register_group);
+                            self.push_expression_instruction(&settings, instruction!(
+                                Var::Set, [(Ident, register), (Ident, value.ident)]
+                            ));
+                        }
+                    }
+                    ValueType::Primitive(PrimitiveType::Map(..)) => {
+                        let Some(map_function) = MapFunction::from_name(access_field_ident) else {
+                            return CodegenError::err(node.clone(), ErrorRepr::InvalidMapAccess);
+                        };
+                        return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::MapFunction(map_function))));
+                    }
                     ValueType::Primitive(PrimitiveType::Vector) => {
                         let register = self.generate_expression_allocate_register(&settings, register_group);
                         value.ident = register;
@@ -1340,6 +2198,9 @@ impl CodeGen {
                     }
                     ValueType::Comptime(ComptimeType::Domain(domain_cid)) => {
                         let mut set_value = settings.depth == 0 && settings.variable_necessary;
+                        if let Ok(definition) = self.find_definition_by_ident(access_field, domain_cid) {
+                            self.check_definition_visible(context, domain_cid, &definition, access_field)?;
+                        }
                         let get_access = self.get_definition_access(domain_cid, &settings, access_field, register_group)?;
                         value = get_access.value;
                         if value.value_type.is_comptime() {
@@ -1356,7 +2217,7 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::ListCall(called, index_field) => {
+            NodeType::ListCall(called, index_field) => {
                 let mut passed_settings = settings.pass();
                 if settings.preferred_category == IdentifierCategory::Type {
                     passed_settings.preferred_category = IdentifierCategory::Type;
@@ -1381,7 +2242,10 @@ impl CodeGen {
                             Var::GetListValue, [ (Ident, register), (Ident, called_value.ident), (Ident, index_register) ]
                         ));
                         if let Some(mut trace_add) = called_expression.trace {
-                            trace_add.crumbs.push(CodegenTraceCrumb::IndexNode(index_field.clone()));
+                            trace_add.crumbs.push(match settings.keep_trace_indices {
+                                true => CodegenTraceCrumb::Ident(CodegenTraceCrumbIdent::Index(index_register)),
+                                false => CodegenTraceCrumb::IndexNode(index_field.clone()),
+                            });
                             trace = Some(trace_add);
                         }
 
@@ -1395,29 +2259,24 @@ impl CodeGen {
                         if &index.value_type != mapping_type.as_ref() {
                             return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion);
                         }
-                        let register_index = if !matches!(index.value_type, ValueType::Primitive(PrimitiveType::String)) {
-                            let temp_reg = self.buffer.allocate_grouped_line_register(register_group);
-                            self.push_expression_instruction(&settings, instruction!(
-                                Var::String, [ (Ident, temp_reg), (String, " "), (Ident, index.ident) ]
-                            ));
-                            temp_reg
-                        } else {
-                            index.ident
-                        };
+                        let register_index = self.generate_map_key(index, &settings, register_group);
 
                         self.push_expression_instruction(&settings, instruction!(
                             Var::GetDictValue, [ (Ident, register), (Ident, called_value.ident), (Ident, register_index) ]
                         ));
                         if let Some(mut trace_add) = called_expression.trace {
-                            trace_add.crumbs.push(CodegenTraceCrumb::EntryNode(index_field.clone()));
+                            trace_add.crumbs.push(match settings.keep_trace_indices {
+                                true => CodegenTraceCrumb::Ident(CodegenTraceCrumbIdent::Entry(register_index)),
+                                false => CodegenTraceCrumb::EntryNode(index_field.clone()),
+                            });
                             trace = Some(trace_add);
                         }
 
                         value.value_type = mapped_type.as_ref().clone();
                     }
                     ValueType::Comptime(ComptimeType::Type(called_type)) => {
-                        let value_type = match index_field.as_ref() {
-                            Node::None => RealtimeValueType::Primitive(PrimitiveType::List(Rc::new(called_type.normalize()))),
+                        let value_type = match &index_field.node_type {
+                            NodeType::None => RealtimeValueType::Primitive(PrimitiveType::List(Rc::new(called_type.normalize()))),
                             _ => RealtimeValueType::Primitive(PrimitiveType::Map(Rc::new(called_type.normalize()), Rc::new(self.get_type(index_field, context)?)))
                         };
                         return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(value_type))));
@@ -1425,10 +2284,10 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::List(tuple) => {
+            NodeType::List(tuple) => {
                 let expected_list_type = 
                     settings.expected_type
-                    .clone()
+                    .as_ref().map(|expected_type| expected_type.unwrap_optional().clone())
                     .unwrap_or(if tuple.len() > 0 {
                         ValueType::Primitive(PrimitiveType::List(Rc::new(self.generate_expression_inside(context, &tuple[0], GenerateExpressionSettings::comptime(), register_group)?.value.value_type)))
                     } else { 
@@ -1458,17 +2317,113 @@ impl CodeGen {
                     self.push_expression_parameter(&settings, Parameter::from_ident(value.ident));
                 }
             }
-            Node::DFASM(params, return_type, block) => {
+            NodeType::Coalesce(optional_node, fallback_node) => {
+                let register = self.generate_expression_allocate_register(&settings, register_group);
+                let optional = self.generate_expression_inside(context, optional_node, settings.pass(), register_group)?.value.clone();
+                let ValueType::Primitive(PrimitiveType::Optional(inside_type)) = &optional.value_type else {
+                    return CodegenError::err(optional_node.clone(), ErrorRepr::ExpectedOptionalType);
+                };
+                // The fallback can be optional too, which keeps the whole thing optional
+                let fallback_type = match self.generate_expression_inside(context, fallback_node, GenerateExpressionSettings::comptime(), register_group) {
+                    Ok(fallback) if matches!(fallback.value.value_type, ValueType::Primitive(PrimitiveType::Optional(..))) => optional.value_type.clone(),
+                    _ => inside_type.as_ref().clone(),
+                };
+                value = CodegenValue::new(register, fallback_type.clone());
+                self.push_expression_instruction(&settings, instruction!(
+                    Var::Set, [ (Ident, register), (Ident, optional.ident) ] ));
+                let none = self.buffer.use_string(NONE_SENTINEL);
+                self.push_expression_instruction(&settings, instruction!(
+                    Varif::Eq, [ (Ident, register), (Ident, none) ] ));
+                // The fallback is only worked out when it's needed
+                let fallback_settings = GenerateExpressionSettings::ident(register).keep_comptime(&settings).expect_type(&fallback_type);
+                self.generate_expression(context, fallback_node, fallback_settings)?;
+                self.push_expression_instruction(&settings, instruction!(EndIf));
+            }
+            NodeType::Optional(inside_node) => {
+                let optional_type = match self.get_type(inside_node, context)? {
+                    ValueType::Primitive(PrimitiveType::Optional(inside_type)) => PrimitiveType::Optional(inside_type),
+                    inside_type => PrimitiveType::Optional(Rc::new(inside_type)),
+                };
+                return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(RealtimeValueType::Primitive(optional_type)))));
+            }
+            NodeType::Map(entries) => {
+                let expected_map_type = match settings.expected_type.as_ref() {
+                    Some(expected_type) => expected_type.unwrap_optional().clone(),
+                    None => match entries.first() {
+                        Some((key, entry_value)) => {
+                            let key_type = self.generate_expression_inside(context, key, GenerateExpressionSettings::comptime(), register_group)?.value.value_type;
+                            let entry_type = self.generate_expression_inside(context, entry_value, GenerateExpressionSettings::comptime(), register_group)?.value.value_type;
+                            ValueType::Primitive(PrimitiveType::Map(Rc::new(entry_type), Rc::new(key_type)))
+                        },
+                        None => ValueType::Primitive(PrimitiveType::Map(Rc::new(ValueType::Primitive(PrimitiveType::None)), Rc::new(ValueType::Primitive(PrimitiveType::String)))),
+                    }
+                };
+                value.value_type = expected_map_type.clone();
+                let ValueType::Primitive(PrimitiveType::Map(entry_type, key_type)) = expected_map_type else {
+                    return CodegenError::err(node.clone(), ErrorRepr::ExpectedMapType);
+                };
+                let register = self.generate_expression_allocate_register(&settings, register_group);
+                value.ident = register;
+
+                let mut entry_values = Vec::new();
+                for (key, entry_value) in entries {
+                    let key = self.generate_expression_inside(context, key, settings.pass().expect_type(&key_type), register_group)?.value.clone();
+                    let key = self.generate_map_key(key, &settings, register_group);
+                    let entry_value = self.generate_expression_inside(context, entry_value, settings.pass().expect_type(&entry_type), register_group)?.value.clone();
+                    entry_values.push((key, entry_value.ident));
+                }
+                self.push_expression_instruction(&settings,
+                    instruction!( Var::CreateDict, [ (Ident, register) ])
+                );
+                for (key, entry_value) in entry_values {
+                    self.push_expression_instruction(&settings, instruction!(
+                        Var::SetDictValue, [ (Ident, register), (Ident, key), (Ident, entry_value) ]
+                    ));
+                }
+            }
+            NodeType::Generic(generic_ident, type_argument_nodes) if Self::get_primary_as_ident(generic_ident, ErrorRepr::ExpectedType).is_ok_and(|ident| ident == "map") => {
+                let [key_node, entry_node] = type_argument_nodes.as_slice() else {
+                    return CodegenError::err(node.clone(), if type_argument_nodes.len() < 2 { ErrorRepr::ExpectedTypeArguments } else { ErrorRepr::UnexpectedTypeArguments });
+                };
+                let key_type = self.get_type(key_node, context)?;
+                let entry_type = self.get_type(entry_node, context)?;
+                let map_type = RealtimeValueType::Primitive(PrimitiveType::Map(Rc::new(entry_type), Rc::new(key_type)));
+                return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), ComptimeType::Type(map_type))));
+            }
+            NodeType::Generic(generic_ident, type_argument_nodes) => {
+                let generic_evaluation = self.generate_expression_inside(context, generic_ident, GenerateExpressionSettings::comptime().prefer_category(settings.preferred_category), register_group)?;
+                let template = match generic_evaluation.value.value_type {
+                    ValueType::Comptime(ComptimeType::Type(RealtimeValueType::Struct(struct_id))) => struct_id,
+                    ValueType::Comptime(ComptimeType::Function(func_id)) => func_id,
+                    _ => { return CodegenError::err(generic_ident.clone(), ErrorRepr::UnexpectedTypeArguments); }
+                };
+                // Inside its own instances, the name of a generic struct is the instance
+                let template = self.context_borrow(template)?.instance_of.as_ref().map_or(template, |(template, _)| *template);
+                if !self.is_generic(template)? {
+                    return CodegenError::err(node.clone(), ErrorRepr::UnexpectedTypeArguments);
+                }
+                let mut type_arguments = Vec::new();
+                for type_argument_node in type_argument_nodes {
+                    type_arguments.push(self.get_type(type_argument_node, context)?);
+                }
+                let instance = self.instantiate_generic(template, type_arguments, node)?;
+                let instance_type = match self.get_context_type(instance)? {
+                    ContextType::Struct => ComptimeType::Type(RealtimeValueType::Struct(instance)),
+                    _ => ComptimeType::Function(instance),
+                };
+                return Ok(CodegenExpressionResult::value(CodegenValue::comptime(self.buffer.constant_void(), instance_type)));
+            }
+            NodeType::DFASM(params, return_type, block) => {
                 let dfasm_group = self.buffer.allocate_line_register_group();
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 value.ident = register;
                 
-                let return_type = if let Node::None = return_type.as_ref() {
+                let return_type = if let NodeType::None = &return_type.node_type {
                     settings.expected_type.clone().unwrap_or(ValueType::Primitive(PrimitiveType::None))
                 } else {
                     self.get_type(return_type, context)?
                 };
-                let Node::Primary(block_token) = block.as_ref() else {
+                let NodeType::Primary(block_token) = &block.node_type else {
                     return CodegenError::err(block.clone(), ErrorRepr::ExpectedBlock);
                 };
                 let TokenType::DFASM(dfasm_str) = block_token.as_ref().token_type.clone() else {
@@ -1490,7 +2445,62 @@ impl CodeGen {
                 value.value_type = return_type;
                 self.buffer.free_line_register_group(dfasm_group);
             }
-            Node::Sum(l, r) => {
+            NodeType::Template(parts) => {
+                let register = self.generate_expression_allocate_register(&settings, register_group);
+                value.ident = register;
+                value.value_type = ValueType::Primitive(PrimitiveType::String);
+
+                // Text is gathered up until a part can't be written into it. Plain variables are written in as `%var()` text codes,
+                // except inside struct functions, where an identifier might be a field of `self` instead.
+                let inline_variables = !matches!(self.get_context_type(self.parents[context])?, ContextType::Struct);
+                let mut text = String::new();
+                let mut params = Vec::new();
+                for part in parts {
+                    if let NodeType::Primary(token) = &part.node_type {
+                        match &token.token_type {
+                            TokenType::String(string) => {
+                                text.push_str(string);
+                                continue;
+                            }
+                            TokenType::Ident(ident) if inline_variables => {
+                                if let Ok(variable) = self.find_variable_by_name_full(context, ident, part) {
+                                    if matches!(variable.variable.value_type, ValueType::Primitive(PrimitiveType::String | PrimitiveType::Number)) {
+                                        text.push_str(&format!("%var({})", variable.name));
+                                        continue;
+                                    }
+                                }
+                            }
+                            _ => ()
+                        }
+                    }
+                    if !text.is_empty() {
+                        params.push(self.buffer.use_string(&text));
+                        text.clear();
+                    }
+                    let part_value = self.generate_expression_inside(context, part, settings.pass(), register_group)?.value;
+                    if !matches!(part_value.value_type, ValueType::Primitive(PrimitiveType::String | PrimitiveType::Number)) {
+                        return CodegenError::err(part.clone(), ErrorRepr::InvalidExpressionTypeConversion);
+                    }
+                    params.push(part_value.ident);
+                }
+                if !text.is_empty() || params.is_empty() {
+                    params.push(self.buffer.use_string(&text));
+                }
+
+                // A codeblock only fits 26 parameters, so long templates get joined onto the register in chunks.
+                let (first_params, rest_params) = params.split_at(params.len().min(25));
+                self.push_expression_instruction(&settings, instruction!(Var::String, [(Ident, register)]));
+                for param in first_params {
+                    self.push_expression_parameter(&settings, Parameter::from_ident(*param));
+                }
+                for chunk in rest_params.chunks(24) {
+                    self.push_expression_instruction(&settings, instruction!(Var::String, [(Ident, register), (Ident, register)]));
+                    for param in chunk {
+                        self.push_expression_parameter(&settings, Parameter::from_ident(*param));
+                    }
+                }
+            }
+            NodeType::Sum(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let l = self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone();
                 let r = self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone();
@@ -1524,7 +2534,7 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Difference(l, r) => {
+            NodeType::Difference(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let l = self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone();
                 let r = self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone();
@@ -1545,12 +2555,18 @@ impl CodeGen {
                             Var::MultiplyVector, [ (Ident, register), (Ident, r.ident), (Int, -1) ] ));
                         self.push_expression_instruction(&settings, instruction!(
                             Var::ShiftOnVector, [ (Ident, register), (Ident, l.ident), (Ident, register) ] ));
+                        value.value_type = ValueType::Primitive(PrimitiveType::Location);
+                    }
+                    (ValueType::Primitive(PrimitiveType::Location), ValueType::Primitive(PrimitiveType::Location)) => {
+                        // The vector that goes from the right location to the left one
+                        self.push_expression_instruction(&settings, instruction!(
+                            Var::VectorBetween, [ (Ident, register), (Ident, r.ident), (Ident, l.ident) ] ));
                         value.value_type = ValueType::Primitive(PrimitiveType::Vector);
                     }
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Product(l, r) => {
+            NodeType::Product(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let l = self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone();
                 let r = self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone();
@@ -1569,12 +2585,17 @@ impl CodeGen {
                     (ValueType::Primitive(PrimitiveType::Vector), ValueType::Primitive(PrimitiveType::Number)) => {
                         self.push_expression_instruction(&settings, instruction!(
                             Var::MultiplyVector, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ] ));
-                        value.value_type = ValueType::Primitive(PrimitiveType::String);
+                        value.value_type = ValueType::Primitive(PrimitiveType::Vector);
+                    }
+                    (ValueType::Primitive(PrimitiveType::Number), ValueType::Primitive(PrimitiveType::Vector)) => {
+                        self.push_expression_instruction(&settings, instruction!(
+                            Var::MultiplyVector, [ (Ident, register), (Ident, r.ident), (Ident, l.ident) ] ));
+                        value.value_type = ValueType::Primitive(PrimitiveType::Vector);
                     }
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::And(l, r) => {
+            NodeType::And(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1587,7 +2608,7 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Or(l, r) => {
+            NodeType::Or(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1600,7 +2621,7 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Not(n) => {
+            NodeType::Not(n) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let n = self.generate_expression_inside(context, n, settings.pass(), register_group)?.value.clone();
                 value.ident = register;
@@ -1613,7 +2634,21 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Negative(n) => {
+            NodeType::Cast(n, cast_type_node) => {
+                let cast_type = self.get_type(cast_type_node, context)?;
+                let register = self.generate_expression_allocate_register(&settings, register_group);
+                let n = self.generate_expression_inside(context, n, settings.pass(), register_group)?.value.clone();
+                let Some(cast) = CodegenCast::find(&n.value_type, &cast_type) else {
+                    return CodegenError::err(node.clone(), ErrorRepr::CantCast);
+                };
+                value = self.cast_value(&cast, n, &cast_type, register, &settings, register_group).map_err(|e| e.or_node(node))?;
+                if value.ident != register && settings.depth == 0 && settings.variable_necessary {
+                    self.push_expression_instruction(&settings, instruction!(
+                        Var::Set, [ (Ident, register), (Ident, value.ident) ] ));
+                    value.ident = register;
+                }
+            }
+            NodeType::Negative(n) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let n = self.generate_expression_inside(context, n, settings.pass(), register_group)?.value.clone();
                 value.ident = register;
@@ -1626,14 +2661,14 @@ impl CodeGen {
                     ValueType::Primitive(PrimitiveType::Vector) => {
                         self.push_expression_instruction(&settings, instruction!(
                             Var::MultiplyVector, [ (Ident, register), (Ident, n.ident), (Int, -1) ]));
-                        value.value_type = ValueType::Primitive(PrimitiveType::Number);
+                        value.value_type = ValueType::Primitive(PrimitiveType::Vector);
                     }
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
-            Node::Equal(l, r) => {
+            NodeType::Equal(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
-                let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
+                let (l, r) = self.generate_equality_sides(context, l, r, &settings, register_group)?;
                 value.ident = register;
                 if l.value_type != r.value_type {
                     return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion);
@@ -1647,9 +2682,9 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::NotEqual(l, r) => {
+            NodeType::NotEqual(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
-                let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
+                let (l, r) = self.generate_equality_sides(context, l, r, &settings, register_group)?;
                 value.ident = register;
                 if l.value_type != r.value_type {
                     return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion);
@@ -1663,7 +2698,7 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::LessThan(l, r) => {
+            NodeType::LessThan(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1679,7 +2714,7 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::GreaterThan(l, r) => {
+            NodeType::GreaterThan(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1695,7 +2730,7 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::LessThanOrEqualTo(l, r) => {
+            NodeType::LessThanOrEqualTo(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1711,7 +2746,7 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::GreaterThanOrEqualTo(l, r) => {
+            NodeType::GreaterThanOrEqualTo(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1727,7 +2762,7 @@ impl CodeGen {
                 self.push_expression_instruction(&settings, instruction!(EndIf));
                 value.value_type = ValueType::Primitive(PrimitiveType::Bool);
             }
-            Node::Quotient(l, r) => {
+            NodeType::Quotient(l, r) => {
                 let register = self.generate_expression_allocate_register(&settings, register_group);
                 let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
                 value.ident = register;
@@ -1740,6 +2775,35 @@ impl CodeGen {
                     _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
                 }
             }
+            NodeType::Modulo(l, r) | NodeType::FloorQuotient(l, r) | NodeType::Exponent(l, r)
+            | NodeType::BitAnd(l, r) | NodeType::BitOr(l, r) | NodeType::BitXor(l, r) | NodeType::ShiftLeft(l, r) | NodeType::ShiftRight(l, r) => {
+                let register = self.generate_expression_allocate_register(&settings, register_group);
+                let (l, r) = (self.generate_expression_inside(context, l, settings.pass(), register_group)?.value.clone(), self.generate_expression_inside(context, r, settings.pass(), register_group)?.value.clone());
+                value.ident = register;
+                if !matches!((l.value_type, r.value_type), (ValueType::Primitive(PrimitiveType::Number), ValueType::Primitive(PrimitiveType::Number))) {
+                    return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion);
+                }
+                let operation = match &node.node_type {
+                    NodeType::Modulo(..) => instruction!(
+                        Var::Mod, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ] ),
+                    NodeType::FloorQuotient(..) => instruction!(
+                        Var::Div, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { DivisionMode: FloorResult } ),
+                    NodeType::Exponent(..) => instruction!(
+                        Var::Exponent, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ] ),
+                    NodeType::BitAnd(..) => instruction!(
+                        Var::Bitwise, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { Operator: AND } ),
+                    NodeType::BitOr(..) => instruction!(
+                        Var::Bitwise, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { Operator: OR } ),
+                    NodeType::BitXor(..) => instruction!(
+                        Var::Bitwise, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { Operator: XOR } ),
+                    NodeType::ShiftLeft(..) => instruction!(
+                        Var::Bitwise, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { Operator: LeftShift } ),
+                    _ => instruction!(
+                        Var::Bitwise, [ (Ident, register), (Ident, l.ident), (Ident, r.ident) ], { Operator: RightShift } ),
+                };
+                self.push_expression_instruction(&settings, operation);
+                value.value_type = ValueType::Primitive(PrimitiveType::Number);
+            }
             _ => { return CodegenError::err(node.clone(), ErrorRepr::UnexpectedExpressionToken); }
         }
         let result = CodegenExpressionResult {
@@ -1751,8 +2815,14 @@ impl CodeGen {
     }
 
     fn declare_runtime_variable(&mut self, context: usize, decl_type: &Rc<Node>, decl_ident: &Rc<Node>) -> Result<(&RuntimeVariable, String), CodegenError> {
-        let decl_ident_str = Self::get_primary_as_ident(decl_ident, ErrorRepr::ExpectedVariableIdentifier)?;
+        Self::get_primary_as_ident(decl_ident, ErrorRepr::ExpectedVariableIdentifier)?;
         let decl_type_str = self.get_type(decl_type, context)?;
+        self.declare_runtime_variable_typed(context, decl_type_str, decl_ident)
+    }
+
+    /// Same as `declare_runtime_variable` but with an already known type, like the variable of a for loop.
+    fn declare_runtime_variable_typed(&mut self, context: usize, decl_type_str: ValueType, decl_ident: &Rc<Node>) -> Result<(&RuntimeVariable, String), CodegenError> {
+        let decl_ident_str = Self::get_primary_as_ident(decl_ident, ErrorRepr::ExpectedVariableIdentifier)?;
         let var_name = Self::make_var_name(decl_ident_str, "rvl");
         let var_ident = self.buffer.use_variable(var_name.as_ref(), DP::Var::Scope::Line);
         let runtime_str = decl_ident_str.to_owned();
@@ -1770,89 +2840,1128 @@ impl CodeGen {
         Ok((self.runtime_vars[context].get(&runtime_str).unwrap(), runtime_str))
     }
 
-    fn call_function(&mut self, context: usize, function_ident: &Rc<Node>, function_params: &Rc<Node>, return_ident: u32) -> Result<ValueType, CodegenError> {
-        // let func_context = self.find_function_by_node(function_ident, context)?;
-        let call_func_reg_group = self.buffer.allocate_line_register_group();
-        let function_ident_evaluation = self.generate_expression(context, function_ident, GenerateExpressionSettings::comptime().prefer_category(IdentifierCategory::Function))?;
-        
-        let (func_context, struct_func_ident, allocated_self_register) = match function_ident_evaluation.value.value_type {
-            ValueType::Comptime(ComptimeType::Function(func_context)) => (func_context, None, None),
-            ValueType::Comptime(ComptimeType::SelfFunction(func_context, ..)) => {
-                let self_reg = self.buffer.allocate_grouped_line_register(call_func_reg_group);
-                let Node::Access(function_ident_inside, ..) = function_ident.as_ref() else {
-                    return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedAccessableIdentifier);
-                };
-                let function_ident_evaluation_codeblocked = self.generate_expression(context, function_ident_inside, GenerateExpressionSettings::ident(self_reg).prefer_category(IdentifierCategory::Field))?;
-                (func_context, Some(self_reg), Some(function_ident_evaluation_codeblocked))
-            },
-            _ => { return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedFunctionIdentifier); }
-        };
-        let func_name = self.get_context_full_name(func_context).clone();
-        let func_id = self.buffer.use_function(func_name.as_str());
-        let mut call_instruction = instruction!(Call, [
-            (Ident, func_id)
-        ]);
-        let ContextType::Function(ret_type_field) = self.context_borrow(func_context)?.context_type.clone() else {
-            return CodegenError::err_headless(ErrorRepr::Generic);
-        };
-        let ret_type_field = match ret_type_field {
-            FunctionType::Func(value_type) => value_type,
-            FunctionType::Event(..) => ValueType::Primitive(PrimitiveType::None),
-            FunctionType::EventListener(..) => {
-                return CodegenError::err(function_ident.clone(), ErrorRepr::CannotCallEventListener)
-            },
-        };
-        if let Some(struct_func_ident) = struct_func_ident {
-            call_instruction.params.push(Parameter::from_ident(struct_func_ident));
+    /// Why a value without a trace can't be assigned to.
+    fn untraced_assignment_error(&self, context: usize, node: &Rc<Node>) -> ErrorRepr {
+        match self.find_definition_by_node(node, context) {
+            Ok(definition) if self.extract_definition_constant(&definition).is_ok() => ErrorRepr::AssignToConstant,
+            _ => ErrorRepr::NoTraceCantAssign,
         }
-        if !matches!(ret_type_field, ValueType::Primitive(PrimitiveType::None)) { // function has a return value
-            call_instruction.params.push(Parameter::from_ident(return_ident))
-        }
-        let params = Self::extract_parameter_vec(function_params)?;
-        let func_fields = self.context_borrow(func_context)?.fields.clone();
-        if params.len() > func_fields.len() {
-            return CodegenError::err(function_params.clone(), ErrorRepr::UnexpectedFunctionParameter)
+    }
+
+    fn evaluate_all_constants(&mut self) -> Result<(), CodegenError> {
+        for context_id in 0..self.contexts.len() {
+            if self.is_generic_template(context_id)? {
+                continue;
+            }
+            let constant_count = self.context_borrow(context_id)?.constants.len();
+            for constant_id in 0..constant_count {
+                self.comptime_steps = 0;
+                self.constant_value(context_id, constant_id)?;
+            }
         }
-        if params.len() < func_fields.len() {
-            return CodegenError::err(function_params.clone(), ErrorRepr::ExpectedFunctionParameter)
+        Ok(())
+    }
+
+    /// Gives back the value of a constant, working it out (along with the constants it uses) if it hasn't been yet.
+    fn constant_value(&mut self, owner: usize, constant_id: usize) -> Result<CodegenConstant, CodegenError> {
+        let constant = self.context_borrow(owner)?.constants[constant_id].clone();
+        if let Some(value) = constant.value {
+            return Ok(value);
         }
-        for (param, param_field) in params.into_iter().zip(func_fields) {
-            let param_expression = self.generate_expression(context, &param, GenerateExpressionSettings::parameter(call_func_reg_group).expect_type(&param_field.field_type))?;
-            call_instruction.params.push(Parameter::from_ident(param_expression.value.ident));
+        if self.constants_evaluating.contains(&(owner, constant_id)) {
+            return CodegenError::err(constant.value_node.clone(), ErrorRepr::RecursiveConstant);
         }
-        self.buffer.code_buffer.push_instruction(call_instruction);
-        if let Some(allocated_self_register) = allocated_self_register {
-            if let Some(allocated_self_register_trace) = allocated_self_register.trace {
-                self.set_trace_to_value(context, allocated_self_register_trace, allocated_self_register.value)?;
+        self.constants_evaluating.push((owner, constant_id));
+        self.comptime_locals.push(Vec::new()); // A constant can't see the variables of a `const func` that uses it
+        let value = self.evaluate_constant(owner, &constant.value_node);
+        self.comptime_locals.pop();
+        self.constants_evaluating.pop();
+        let mut value = value?;
+        if Self::is_inferred_type(&constant.constant_type) {
+            if !Self::is_type_inferable(&value.value_type()) {
+                return CodegenError::err(constant.value_node.clone(), ErrorRepr::CantInferType);
             }
+        } else {
+            let constant_type = self.get_type(&constant.constant_type, owner)?;
+            value = value.convert(&constant_type).ok_or(CodegenError::new(constant.value_node.clone(), ErrorRepr::CantImplicitlyCast))?;
         }
-        self.buffer.free_line_register_group(call_func_reg_group);
-        Ok(ret_type_field)
+        self.context_borrow_mut(owner)?.constants[constant_id].value = Some(value.clone());
+        Ok(value)
     }
 
-
-    fn generate_function_code(&mut self, context: usize, body: Rc<Vec<Rc<Node>>>, fields: Vec<Field>, return_type: ValueType) -> Result<(), CodegenError> {
-        // self.return_runtimes[context] = 
-        let parent = self.parents[context];
-        let parent_context = self.context_borrow(parent)?.context_type.clone();
-        let context_type = self.context_borrow(context)?.context_type.clone();
-        
-        let func_name = self.get_context_full_name(context).clone();
-        let func_id = self.buffer.use_function(func_name.as_str());
-        self.buffer.code_buffer.push_instruction(instruction!(
-            Func, [
-                (Ident, func_id)
-            ]
-        ));
-        //println!("Generating function {}, return type: {:#?}", context, return_type);
-        if matches!(parent_context, ContextType::Struct) {
-            let self_struct_param_ident = self.buffer.use_return_param("self");
-            self.buffer.code_buffer.push_parameter(Parameter::from_ident(self_struct_param_ident.0));
-        }
-        if matches!(context_type, ContextType::Function(FunctionType::EventListener(..))) {
-            let event_param_ident = self.buffer.use_return_param("event");
-            self.buffer.code_buffer.push_parameter(Parameter::from_ident(event_param_ident.0));  
-        }
+    fn evaluate_constant(&mut self, context: usize, node: &Rc<Node>) -> Result<CodegenConstant, CodegenError> {
+        Ok(match &node.node_type {
+            NodeType::Primary(token) => match &token.token_type {
+                TokenType::Number(number) => CodegenConstant::Number(*number),
+                TokenType::String(string) => CodegenConstant::String(string.clone()),
+                TokenType::Keyword(Keyword::Value(ValuedKeyword::True)) => CodegenConstant::Bool(true),
+                TokenType::Keyword(Keyword::Value(ValuedKeyword::False)) => CodegenConstant::Bool(false),
+                // The type inside is worked out once it's converted into the optional it's going into
+                TokenType::Keyword(Keyword::Value(ValuedKeyword::None)) => CodegenConstant::Optional(ValueType::Primitive(PrimitiveType::None), None),
+                TokenType::Ident(ident) => {
+                    if let Some(local) = self.comptime_local(ident) {
+                        return Ok(local.clone());
+                    }
+                    if self.find_variable_by_name_full(context, ident, node).is_ok() {
+                        return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression);
+                    }
+                    return self.evaluate_constant_access(context, node);
+                }
+                _ => { return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression); }
+            },
+            NodeType::Access(accessed, access_field) => {
+                if let Some((enum_id, variant_id)) = self.get_enum_variant(context, node)? {
+                    if !self.context_borrow(enum_id)?.variants[variant_id].fields.is_empty() {
+                        return CodegenError::err(node.clone(), ErrorRepr::ExpectedEnumPayload);
+                    }
+                    return Ok(CodegenConstant::Enum(enum_id, variant_id, Vec::new()));
+                }
+                if !self.is_comptime_local(accessed) {
+                    if self.is_runtime_path(context, accessed) {
+                        return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression);
+                    }
+                    return self.evaluate_constant_access(context, node);
+                }
+                let CodegenConstant::Struct(struct_id, fields) = self.evaluate_constant(context, accessed)? else {
+                    return CodegenError::err(accessed.clone(), ErrorRepr::ExpectedStruct);
+                };
+                let (field_id, _) = self.comptime_struct_field(struct_id, access_field)?;
+                fields[field_id].clone()
+            }
+            NodeType::ListCall(list, index) => {
+                let CodegenConstant::List(_, elements) = self.evaluate_constant(context, list)? else {
+                    return CodegenError::err(list.clone(), ErrorRepr::InvalidListAccess);
+                };
+                let index = self.comptime_list_index(context, index, elements.len())?;
+                elements[index].clone()
+            }
+            NodeType::Equal(l, r) | NodeType::NotEqual(l, r) => {
+                let (l, r) = (self.evaluate_constant(context, l)?, self.evaluate_constant(context, r)?);
+                if l.value_type() != r.value_type() {
+                    return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion);
+                }
+                CodegenConstant::Bool((l == r) == matches!(node.node_type, NodeType::Equal(..)))
+            }
+            NodeType::LessThan(l, r) | NodeType::GreaterThan(l, r) | NodeType::LessThanOrEqualTo(l, r) | NodeType::GreaterThanOrEqualTo(l, r) => {
+                let (l, r) = (self.evaluate_constant_number(context, l)?, self.evaluate_constant_number(context, r)?);
+                CodegenConstant::Bool(match node.node_type {
+                    NodeType::LessThan(..) => l < r,
+                    NodeType::GreaterThan(..) => l > r,
+                    NodeType::LessThanOrEqualTo(..) => l <= r,
+                    _ => l >= r,
+                })
+            }
+            NodeType::And(l, r) => CodegenConstant::Bool(self.evaluate_constant_bool(context, l)? && self.evaluate_constant_bool(context, r)?),
+            NodeType::Or(l, r) => CodegenConstant::Bool(self.evaluate_constant_bool(context, l)? || self.evaluate_constant_bool(context, r)?),
+            NodeType::Not(n) => CodegenConstant::Bool(!self.evaluate_constant_bool(context, n)?),
+            NodeType::Cast(n, cast_type_node) => {
+                let cast_type = self.get_type(cast_type_node, context)?;
+                let n = self.evaluate_constant(context, n)?;
+                let Some(cast) = CodegenCast::find(&n.value_type(), &cast_type) else {
+                    return CodegenError::err(node.clone(), ErrorRepr::CantCast);
+                };
+                n.cast(&cast, &cast_type).ok_or(CodegenError::new(node.clone(), ErrorRepr::CantCast))?
+            }
+            NodeType::Template(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match self.evaluate_constant(context, part)? {
+                        CodegenConstant::String(string) => text.push_str(&string),
+                        CodegenConstant::Number(number) => text.push_str(&number.to_string()),
+                        // Only strings and numbers, to match the templates built at runtime
+                        _ => { return CodegenError::err(part.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
+                    }
+                }
+                CodegenConstant::String(text)
+            }
+            NodeType::Construct(construct_ident, construct_body_node) => {
+                let ValueType::Struct(struct_id) = self.get_type(construct_ident, context)? else {
+                    return CodegenError::err(construct_ident.clone(), ErrorRepr::ExpectedStructIdentifier);
+                };
+                let NodeType::Block(construct_body) = &construct_body_node.node_type else {
+                    return CodegenError::err(construct_body_node.clone(), ErrorRepr::ExpectedBlock);
+                };
+                let mut fields = vec![None; self.context_borrow(struct_id)?.fields.len()];
+                for construct_statement in construct_body {
+                    let NodeType::Assignment(assigned_node, assigned_value) = &construct_statement.node_type else {
+                        return CodegenError::err(construct_body_node.clone(), ErrorRepr::ExpectedFieldAssignment);
+                    };
+                    let (field_id, field_type) = self.comptime_struct_field(struct_id, assigned_node)?;
+                    let field_value = self.evaluate_constant(context, assigned_value)?;
+                    fields[field_id] = Some(field_value.convert(&field_type).ok_or(CodegenError::new(assigned_value.clone(), ErrorRepr::UnexpectedStructFieldType))?);
+                }
+                let struct_fields = self.context_borrow(struct_id)?.fields.clone();
+                for (field, struct_field) in fields.iter_mut().zip(struct_fields) {
+                    if let (None, Some(default_value)) = (&field, &struct_field.default_value) {
+                        let field_value = self.evaluate_constant(struct_id, default_value)?;
+                        *field = Some(field_value.convert(&struct_field.field_type).ok_or(CodegenError::new(default_value.clone(), ErrorRepr::UnexpectedStructFieldType))?);
+                    } else if let (None, ValueType::Primitive(PrimitiveType::Optional(inside_type))) = (&field, &struct_field.field_type) {
+                        *field = Some(CodegenConstant::Optional(inside_type.as_ref().clone(), None));
+                    }
+                }
+                let fields = fields.into_iter().collect::<Option<Vec<_>>>()
+                    .ok_or(CodegenError::new(construct_body_node.clone(), ErrorRepr::ConstructFieldsMissing))?;
+                CodegenConstant::Struct(struct_id, fields)
+            }
+            NodeType::FunctionCall(func_ident, func_params) => {
+                if let Some((enum_id, variant_id)) = self.get_enum_variant(context, func_ident)? {
+                    let payload_types = self.enum_payload_types(enum_id, variant_id)?;
+                    let params = Self::extract_parameter_vec(func_params)?;
+                    if payload_types.is_empty() {
+                        return CodegenError::err(func_params.clone(), ErrorRepr::UnexpectedEnumPayload);
+                    }
+                    if params.len() > payload_types.len() {
+                        return CodegenError::err(func_params.clone(), ErrorRepr::UnexpectedFunctionParameter);
+                    }
+                    if params.len() < payload_types.len() {
+                        return CodegenError::err(func_params.clone(), ErrorRepr::ExpectedFunctionParameter);
+                    }
+                    let mut payload = Vec::new();
+                    for (param, payload_type) in params.iter().zip(payload_types) {
+                        let param_value = self.evaluate_constant(context, param)?;
+                        payload.push(param_value.convert(&payload_type).ok_or(CodegenError::new(param.clone(), ErrorRepr::CantImplicitlyCast))?);
+                    }
+                    return Ok(CodegenConstant::Enum(enum_id, variant_id, payload));
+                }
+                let Some(returned) = self.evaluate_const_function_call(context, func_ident, func_params, node)? else {
+                    return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression);
+                };
+                returned
+            }
+            NodeType::Coalesce(optional_node, fallback_node) => match self.evaluate_constant(context, optional_node)? {
+                CodegenConstant::Optional(_, Some(value)) => *value,
+                CodegenConstant::Optional(..) => self.evaluate_constant(context, fallback_node)?,
+                _ => { return CodegenError::err(optional_node.clone(), ErrorRepr::ExpectedOptionalType); }
+            },
+            NodeType::Negative(n) => match self.evaluate_constant(context, n)? {
+                CodegenConstant::Number(n) => CodegenConstant::Number(-n),
+                CodegenConstant::Vector(x, y, z) => CodegenConstant::Vector(-x, -y, -z),
+                _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
+            },
+            NodeType::Sum(l, r) | NodeType::Difference(l, r) | NodeType::Product(l, r) | NodeType::Quotient(l, r)
+            | NodeType::Modulo(l, r) | NodeType::FloorQuotient(l, r) | NodeType::Exponent(l, r) => {
+                let (l, r) = (self.evaluate_constant(context, l)?, self.evaluate_constant(context, r)?);
+                match (&node.node_type, l, r) {
+                    (NodeType::Sum(..), CodegenConstant::String(l), CodegenConstant::String(r)) => CodegenConstant::String(l + &r),
+                    (NodeType::Sum(..), CodegenConstant::String(l), CodegenConstant::Number(r)) => CodegenConstant::String(format!("{}{}", l, r)),
+                    (NodeType::Sum(..), CodegenConstant::Number(l), CodegenConstant::String(r)) => CodegenConstant::String(format!("{}{}", l, r)),
+                    (NodeType::Sum(..), CodegenConstant::Vector(lx, ly, lz), CodegenConstant::Vector(rx, ry, rz)) => CodegenConstant::Vector(lx + rx, ly + ry, lz + rz),
+                    (NodeType::Difference(..), CodegenConstant::Vector(lx, ly, lz), CodegenConstant::Vector(rx, ry, rz)) => CodegenConstant::Vector(lx - rx, ly - ry, lz - rz),
+                    (NodeType::Sum(..), CodegenConstant::Location(x, y, z, pitch, yaw), CodegenConstant::Vector(vx, vy, vz)) => CodegenConstant::Location(x + vx, y + vy, z + vz, pitch, yaw),
+                    (NodeType::Difference(..), CodegenConstant::Location(x, y, z, pitch, yaw), CodegenConstant::Vector(vx, vy, vz)) => CodegenConstant::Location(x - vx, y - vy, z - vz, pitch, yaw),
+                    // The vector that goes from the right location to the left one
+                    (NodeType::Difference(..), CodegenConstant::Location(lx, ly, lz, ..), CodegenConstant::Location(rx, ry, rz, ..)) => CodegenConstant::Vector(lx - rx, ly - ry, lz - rz),
+                    (NodeType::Product(..), CodegenConstant::Vector(x, y, z), CodegenConstant::Number(n))
+                    | (NodeType::Product(..), CodegenConstant::Number(n), CodegenConstant::Vector(x, y, z)) => CodegenConstant::Vector(x * n, y * n, z * n),
+                    (NodeType::Product(..), CodegenConstant::String(string), CodegenConstant::Number(n)) => CodegenConstant::String(string.repeat(n.max(0.0) as usize)),
+                    (NodeType::Sum(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l + r),
+                    (NodeType::Difference(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l - r),
+                    (NodeType::Product(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l * r),
+                    (NodeType::Quotient(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l / r),
+                    (NodeType::Modulo(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l % r),
+                    (NodeType::FloorQuotient(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number((l / r).floor()),
+                    (NodeType::Exponent(..), CodegenConstant::Number(l), CodegenConstant::Number(r)) => CodegenConstant::Number(l.powf(r)),
+                    _ => { return CodegenError::err(node.clone(), ErrorRepr::InvalidExpressionTypeConversion); }
+                }
+            }
+            NodeType::Vector(xn, yn, zn) => CodegenConstant::Vector(
+                self.evaluate_constant_number(context, xn)?,
+                self.evaluate_constant_number(context, yn)?,
+                self.evaluate_constant_number(context, zn)?,
+            ),
+            NodeType::Location(xn, yn, zn, pitchn, yawn) => CodegenConstant::Location(
+                self.evaluate_constant_number(context, xn)?,
+                self.evaluate_constant_number(context, yn)?,
+                self.evaluate_constant_number(context, zn)?,
+                self.evaluate_constant_number(context, pitchn)?,
+                self.evaluate_constant_number(context, yawn)?,
+            ),
+            NodeType::List(element_nodes) => {
+                let mut elements = Vec::new();
+                for element_node in element_nodes {
+                    elements.push(self.evaluate_constant(context, element_node)?);
+                }
+                let element_type = elements.first().map(|element| element.value_type()).unwrap_or(ValueType::Primitive(PrimitiveType::None));
+                let mut converted = Vec::new();
+                for (element, element_node) in elements.into_iter().zip(element_nodes) {
+                    converted.push(element.convert(&element_type).ok_or(CodegenError::new(element_node.clone(), ErrorRepr::CantImplicitlyCast))?);
+                }
+                CodegenConstant::List(element_type, converted)
+            }
+            _ => { return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression); }
+        })
+    }
+
+    fn evaluate_constant_number(&mut self, context: usize, node: &Rc<Node>) -> Result<f64, CodegenError> {
+        match self.evaluate_constant(context, node)? {
+            CodegenConstant::Number(number) => Ok(number),
+            _ => CodegenError::err(node.clone(), ErrorRepr::CantImplicitlyCast),
+        }
+    }
+
+    fn evaluate_constant_bool(&mut self, context: usize, node: &Rc<Node>) -> Result<bool, CodegenError> {
+        match self.evaluate_constant(context, node)? {
+            CodegenConstant::Bool(bool) => Ok(bool),
+            _ => CodegenError::err(node.clone(), ErrorRepr::CantImplicitlyCast),
+        }
+    }
+
+    /// Runs a call to a `const func` at compile time if every argument is a constant, giving back nothing when it has to be called at runtime.
+    fn try_const_function_call(&mut self, context: usize, func_ident: &Rc<Node>, func_params: &Rc<Node>, node: &Rc<Node>) -> Result<Option<CodegenConstant>, CodegenError> {
+        let Ok(mut func_context) = self.find_function_by_node(func_ident, context) else {
+            return Ok(None);
+        };
+        if !self.context_borrow(func_context)?.comptime {
+            return Ok(None);
+        }
+        let mut arguments = Vec::new();
+        for param in Self::extract_parameter_vec(func_params)? {
+            match self.evaluate_constant(context, &param) {
+                Ok(argument) => arguments.push(argument),
+                // Arguments only known at runtime call the function at runtime instead
+                Err(error) if matches!(error.source, ErrorRepr::ExpectedConstantExpression | ErrorRepr::NonConstOperation) => { return Ok(None); }
+                Err(error) => { return Err(error); }
+            }
+        }
+        if self.is_generic(func_context)? {
+            let argument_types: Vec<ValueType> = arguments.iter().map(|argument| argument.value_type()).collect();
+            func_context = self.infer_generic_function(func_context, &argument_types, func_params)?;
+        }
+        if self.context_borrow(func_context)?.context_type == ContextType::Function(FunctionType::Func(ValueType::Primitive(PrimitiveType::None))) {
+            return Ok(None);
+        }
+        self.comptime_steps = 0;
+        self.run_const_function(func_context, arguments, node)
+    }
+
+    fn evaluate_const_function_call(&mut self, context: usize, func_ident: &Rc<Node>, func_params: &Rc<Node>, node: &Rc<Node>) -> Result<Option<CodegenConstant>, CodegenError> {
+        let mut func_context = self.find_function_by_node(func_ident, context)?;
+        if !self.context_borrow(func_context)?.comptime {
+            return CodegenError::err(func_ident.clone(), ErrorRepr::NonConstOperation);
+        }
+        let mut arguments = Vec::new();
+        for param in Self::extract_parameter_vec(func_params)? {
+            arguments.push(self.evaluate_constant(context, &param)?);
+        }
+        if self.is_generic(func_context)? {
+            let argument_types: Vec<ValueType> = arguments.iter().map(|argument| argument.value_type()).collect();
+            func_context = self.infer_generic_function(func_context, &argument_types, func_params)?;
+        }
+        self.run_const_function(func_context, arguments, node)
+    }
+
+    /// Runs a `const func` at compile time, giving back what it returned.
+    fn run_const_function(&mut self, func_context: usize, arguments: Vec<CodegenConstant>, node: &Rc<Node>) -> Result<Option<CodegenConstant>, CodegenError> {
+        let (body, context_type, fields, func_node) = {
+            let context_borrow = self.context_borrow(func_context)?;
+            (context_borrow.body.clone(), context_borrow.context_type.clone(), context_borrow.fields.clone(), context_borrow.node.clone())
+        };
+        let ContextType::Function(FunctionType::Func(return_type)) = context_type else {
+            return CodegenError::err(node.clone(), ErrorRepr::NonConstOperation);
+        };
+        if arguments.len() > fields.len() {
+            return CodegenError::err(node.clone(), ErrorRepr::UnexpectedFunctionParameter);
+        }
+        if arguments.len() < fields.len() {
+            return CodegenError::err(node.clone(), ErrorRepr::ExpectedFunctionParameter);
+        }
+        if self.comptime_locals.len() >= CONST_FUNC_CALL_DEPTH {
+            return CodegenError::err(node.clone(), ErrorRepr::ConstCallDepthReached);
+        }
+        let mut scope = HashMap::new();
+        for ((argument, field), field_name) in arguments.into_iter().zip(fields).zip(self.field_names[func_context].clone()) {
+            scope.insert(field_name, argument.convert(&field.field_type).ok_or(CodegenError::new(node.clone(), ErrorRepr::CantImplicitlyCast))?);
+        }
+        self.comptime_locals.push(Vec::new());
+        let flow = self.run_comptime_block(func_context, &body, scope, &return_type);
+        self.comptime_locals.pop();
+        match flow? {
+            CodegenComptimeFlow::Return(returned) => Ok(returned),
+            CodegenComptimeFlow::Normal if return_type == ValueType::Primitive(PrimitiveType::None) => Ok(None),
+            CodegenComptimeFlow::Normal => CodegenError::err(func_node, ErrorRepr::ExpectedFunctionReturnValue),
+            CodegenComptimeFlow::Break | CodegenComptimeFlow::Continue => CodegenError::err(func_node, ErrorRepr::LoopControlOutsideLoop),
+        }
+    }
+
+    fn run_comptime_block(&mut self, context: usize, statements: &[Rc<Node>], scope: HashMap<String, CodegenConstant>, return_type: &ValueType) -> Result<CodegenComptimeFlow, CodegenError> {
+        self.comptime_locals.last_mut().expect("A const func should be running.").push(scope);
+        let mut flow = Ok(CodegenComptimeFlow::Normal);
+        for statement in statements {
+            flow = self.run_comptime_statement(context, statement, return_type);
+            if !matches!(flow, Ok(CodegenComptimeFlow::Normal)) {
+                break;
+            }
+        }
+        self.comptime_locals.last_mut().expect("A const func should be running.").pop();
+        flow
+    }
+
+    fn run_comptime_block_node(&mut self, context: usize, block: &Rc<Node>, scope: HashMap<String, CodegenConstant>, return_type: &ValueType) -> Result<CodegenComptimeFlow, CodegenError> {
+        let NodeType::Block(statements) = &block.node_type else {
+            return CodegenError::err(block.clone(), ErrorRepr::ExpectedBlock);
+        };
+        self.run_comptime_block(context, statements, scope, return_type)
+    }
+
+    fn comptime_step(&mut self, node: &Rc<Node>) -> Result<(), CodegenError> {
+        self.comptime_steps += 1;
+        if self.comptime_steps > CONST_FUNC_STEP_LIMIT {
+            return CodegenError::err(node.clone(), ErrorRepr::ConstStepLimitReached);
+        }
+        Ok(())
+    }
+
+    fn run_comptime_statement(&mut self, context: usize, statement: &Rc<Node>, return_type: &ValueType) -> Result<CodegenComptimeFlow, CodegenError> {
+        self.comptime_step(statement)?;
+        match &statement.node_type {
+            NodeType::Declaration(decl_type, decl_ident) => {
+                if Self::is_inferred_type(decl_type) {
+                    return CodegenError::err(statement.clone(), ErrorRepr::CantInferType);
+                }
+                let decl_type = self.get_type(decl_type, context)?;
+                let Some(value) = Self::default_constant(&decl_type) else {
+                    return CodegenError::err(statement.clone(), ErrorRepr::NonConstOperation);
+                };
+                self.declare_comptime_local(decl_ident, value)?;
+            }
+            NodeType::Assignment(assigned, value_node) => {
+                let value = self.evaluate_constant(context, value_node)?;
+                if let NodeType::Declaration(decl_type, decl_ident) = &assigned.node_type {
+                    let value = if Self::is_inferred_type(decl_type) {
+                        if !Self::is_type_inferable(&value.value_type()) {
+                            return CodegenError::err(value_node.clone(), ErrorRepr::CantInferType);
+                        }
+                        value
+                    } else {
+                        let decl_type = self.get_type(decl_type, context)?;
+                        value.convert(&decl_type).ok_or(CodegenError::new(value_node.clone(), ErrorRepr::CantImplicitlyCast))?
+                    };
+                    self.declare_comptime_local(decl_ident, value)?;
+                } else {
+                    self.comptime_assign(context, assigned, value, value_node)?;
+                }
+            }
+            NodeType::CompoundAssignment(assigned, value_node) => {
+                let value = self.evaluate_constant(context, value_node)?;
+                self.comptime_assign(context, assigned, value, value_node)?;
+            }
+            NodeType::If(..) | NodeType::IfLet(..) | NodeType::Else(..) => {
+                return Ok(self.run_comptime_if(context, statement, return_type)?.unwrap_or(CodegenComptimeFlow::Normal));
+            }
+            NodeType::While(condition, block) => {
+                while self.evaluate_constant_bool(context, condition)? {
+                    self.comptime_step(statement)?;
+                    match self.run_comptime_block_node(context, block, HashMap::new(), return_type)? {
+                        CodegenComptimeFlow::Break => break,
+                        CodegenComptimeFlow::Return(returned) => { return Ok(CodegenComptimeFlow::Return(returned)); }
+                        _ => {}
+                    }
+                }
+            }
+            NodeType::For(for_ident, iterated, block) => {
+                let for_ident = Self::get_primary_as_ident(for_ident, ErrorRepr::ExpectedVariableIdentifier)?.clone();
+                let elements = match &iterated.node_type {
+                    NodeType::Range(range_start, range_end, range_step) => {
+                        let start = self.evaluate_constant_number(context, range_start)?;
+                        let end = self.evaluate_constant_number(context, range_end)?;
+                        let step = if matches!(range_step.node_type, NodeType::None) { 1.0 } else { self.evaluate_constant_number(context, range_step)? };
+                        let count = ((end - start) / step).floor() + 1.0;
+                        if !count.is_finite() || count > CONST_FUNC_STEP_LIMIT as f64 {
+                            return CodegenError::err(iterated.clone(), ErrorRepr::ConstStepLimitReached);
+                        }
+                        (0..count.max(0.0) as usize).map(|index| CodegenConstant::Number(start + step * index as f64)).collect()
+                    }
+                    _ => match self.evaluate_constant(context, iterated)? {
+                        CodegenConstant::List(_, elements) => elements,
+                        _ => { return CodegenError::err(iterated.clone(), ErrorRepr::ExpectedListType); }
+                    }
+                };
+                for element in elements {
+                    self.comptime_step(statement)?;
+                    match self.run_comptime_block_node(context, block, HashMap::from([(for_ident.clone(), element)]), return_type)? {
+                        CodegenComptimeFlow::Break => break,
+                        CodegenComptimeFlow::Return(returned) => { return Ok(CodegenComptimeFlow::Return(returned)); }
+                        _ => {}
+                    }
+                }
+            }
+            NodeType::Break(label) | NodeType::Continue(label) => {
+                if !matches!(label.node_type, NodeType::None) {
+                    return CodegenError::err(statement.clone(), ErrorRepr::NonConstOperation);
+                }
+                return Ok(match statement.node_type {
+                    NodeType::Break(..) => CodegenComptimeFlow::Break,
+                    _ => CodegenComptimeFlow::Continue,
+                });
+            }
+            NodeType::Return(return_value) => {
+                if matches!(return_value.node_type, NodeType::None) {
+                    return Ok(CodegenComptimeFlow::Return(None));
+                }
+                if *return_type == ValueType::Primitive(PrimitiveType::None) {
+                    return CodegenError::err(return_value.clone(), ErrorRepr::UnexpectedReturnValue);
+                }
+                let returned = self.evaluate_constant(context, return_value)?;
+                let returned = returned.convert(return_type).ok_or(CodegenError::new(return_value.clone(), ErrorRepr::CantImplicitlyCast))?;
+                return Ok(CodegenComptimeFlow::Return(Some(returned)));
+            }
+            NodeType::FunctionCall(func_ident, func_params) => {
+                self.evaluate_const_function_call(context, func_ident, func_params, statement)?;
+            }
+            NodeType::Const(..) => {
+                return CodegenError::err(statement.clone(), ErrorRepr::UnexpectedConst);
+            }
+            _ => {
+                return CodegenError::err(statement.clone(), ErrorRepr::NonConstOperation);
+            }
+        }
+        Ok(CodegenComptimeFlow::Normal)
+    }
+
+    /// Runs the branch of an if/else chain whose condition holds, giving back nothing if none did.
+    fn run_comptime_if(&mut self, context: usize, node: &Rc<Node>, return_type: &ValueType) -> Result<Option<CodegenComptimeFlow>, CodegenError> {
+        match &node.node_type {
+            NodeType::If(condition, block) => {
+                if !self.evaluate_constant_bool(context, condition)? {
+                    return Ok(None);
+                }
+                Ok(Some(self.run_comptime_block_node(context, block, HashMap::new(), return_type)?))
+            }
+            NodeType::IfLet(unwrapped_ident, optional_node, block) => {
+                let CodegenConstant::Optional(_, value) = self.evaluate_constant(context, optional_node)? else {
+                    return CodegenError::err(optional_node.clone(), ErrorRepr::ExpectedOptionalType);
+                };
+                let Some(value) = value else {
+                    return Ok(None);
+                };
+                let unwrapped_name = Self::get_primary_as_ident(unwrapped_ident, ErrorRepr::ExpectedVariableIdentifier)?.clone();
+                Ok(Some(self.run_comptime_block_node(context, block, HashMap::from([(unwrapped_name, *value)]), return_type)?))
+            }
+            NodeType::Else(if_node, else_node) => {
+                if let Some(flow) = self.run_comptime_if(context, if_node, return_type)? {
+                    return Ok(Some(flow));
+                }
+                match else_node.node_type {
+                    NodeType::Block(..) => Ok(Some(self.run_comptime_block_node(context, else_node, HashMap::new(), return_type)?)),
+                    _ => self.run_comptime_if(context, else_node, return_type),
+                }
+            }
+            _ => CodegenError::err(node.clone(), ErrorRepr::ExpectedBlock)
+        }
+    }
+
+    /// The value a variable declared without one starts with in a `const func`.
+    fn default_constant(value_type: &ValueType) -> Option<CodegenConstant> {
+        let ValueType::Primitive(primitive) = value_type else {
+            return None;
+        };
+        Some(match primitive {
+            PrimitiveType::Number => CodegenConstant::Number(0.0),
+            PrimitiveType::String => CodegenConstant::String(String::new()),
+            PrimitiveType::Bool => CodegenConstant::Bool(false),
+            PrimitiveType::Vector => CodegenConstant::Vector(0.0, 0.0, 0.0),
+            PrimitiveType::Location => CodegenConstant::Location(0.0, 0.0, 0.0, 0.0, 0.0),
+            PrimitiveType::List(element_type) => CodegenConstant::List(element_type.as_ref().clone(), Vec::new()),
+            PrimitiveType::Optional(inside_type) => CodegenConstant::Optional(inside_type.as_ref().clone(), None),
+            _ => { return None; }
+        })
+    }
+
+    /// Finds a variable of the `const func` being run at compile time.
+    fn comptime_local(&self, name: &str) -> Option<&CodegenConstant> {
+        self.comptime_locals.last()?.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Whether an identifier, or the start of an access chain, is a variable of the `const func` being run.
+    fn is_comptime_local(&self, node: &Rc<Node>) -> bool {
+        match &node.node_type {
+            NodeType::Access(accessed, _) | NodeType::ListCall(accessed, _) => self.is_comptime_local(accessed),
+            NodeType::Primary(token) => matches!(&token.token_type, TokenType::Ident(ident) if self.comptime_local(ident).is_some()),
+            _ => false,
+        }
+    }
+
+    /// Whether a node like `p.position.x` starts from something that only has a value at runtime, like a variable, `self` or a call.
+    fn is_runtime_path(&mut self, context: usize, node: &Rc<Node>) -> bool {
+        match &node.node_type {
+            NodeType::Access(accessed, _) | NodeType::ListCall(accessed, _) => self.is_runtime_path(context, accessed),
+            NodeType::Primary(token) => match &token.token_type {
+                TokenType::Ident(ident) => self.find_variable_by_name_full(context, ident, node).is_ok(),
+                _ => true,
+            },
+            _ => true,
+        }
+    }
+
+    fn declare_comptime_local(&mut self, decl_ident: &Rc<Node>, value: CodegenConstant) -> Result<(), CodegenError> {
+        let decl_ident = Self::get_primary_as_ident(decl_ident, ErrorRepr::ExpectedVariableIdentifier)?.clone();
+        self.comptime_locals.last_mut().and_then(|scopes| scopes.last_mut()).expect("A const func should be running.").insert(decl_ident, value);
+        Ok(())
+    }
+
+    /// Sets a variable of the `const func` being run, or an element or field inside of one.
+    fn comptime_assign(&mut self, context: usize, assigned: &Rc<Node>, value: CodegenConstant, value_node: &Rc<Node>) -> Result<(), CodegenError> {
+        let value = match &assigned.node_type {
+            NodeType::Primary(..) => value,
+            NodeType::ListCall(list, index) => {
+                let CodegenConstant::List(element_type, mut elements) = self.evaluate_constant(context, list)? else {
+                    return CodegenError::err(list.clone(), ErrorRepr::InvalidListAccess);
+                };
+                let index = self.comptime_list_index(context, index, elements.len())?;
+                elements[index] = value.convert(&element_type).ok_or(CodegenError::new(value_node.clone(), ErrorRepr::CantImplicitlyCast))?;
+                return self.comptime_assign(context, list, CodegenConstant::List(element_type, elements), value_node);
+            }
+            NodeType::Access(accessed, access_field) if self.is_comptime_local(accessed) => {
+                let CodegenConstant::Struct(struct_id, mut fields) = self.evaluate_constant(context, accessed)? else {
+                    return CodegenError::err(accessed.clone(), ErrorRepr::ExpectedStruct);
+                };
+                let (field_id, field_type) = self.comptime_struct_field(struct_id, access_field)?;
+                fields[field_id] = value.convert(&field_type).ok_or(CodegenError::new(value_node.clone(), ErrorRepr::UnexpectedStructFieldType))?;
+                return self.comptime_assign(context, accessed, CodegenConstant::Struct(struct_id, fields), value_node);
+            }
+            _ => { return CodegenError::err(assigned.clone(), ErrorRepr::NonConstOperation); }
+        };
+        let name = Self::get_primary_as_ident(assigned, ErrorRepr::ExpectedVariableIdentifier)?;
+        if self.comptime_local(name).is_none() {
+            return CodegenError::err(assigned.clone(), match self.untraced_assignment_error(context, assigned) {
+                ErrorRepr::AssignToConstant => ErrorRepr::AssignToConstant,
+                _ => ErrorRepr::NonConstOperation,
+            });
+        }
+        let local = self.comptime_locals.last_mut().and_then(|scopes| scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)))
+            .expect("The variable was just found.");
+        *local = value.convert(&local.value_type()).ok_or(CodegenError::new(value_node.clone(), ErrorRepr::CantImplicitlyCast))?;
+        Ok(())
+    }
+
+    fn comptime_list_index(&mut self, context: usize, index_node: &Rc<Node>, length: usize) -> Result<usize, CodegenError> {
+        let index = self.evaluate_constant_number(context, index_node)?;
+        if index < 0.0 || index.fract() != 0.0 || index as usize >= length {
+            return CodegenError::err(index_node.clone(), ErrorRepr::InvalidListAccess);
+        }
+        Ok(index as usize)
+    }
+
+    fn comptime_struct_field(&self, struct_id: usize, field_node: &Rc<Node>) -> Result<(usize, ValueType), CodegenError> {
+        let field_name = Self::get_primary_as_ident(field_node, ErrorRepr::ExpectedFieldAssignment)?;
+        let struct_context = self.context_borrow(struct_id)?;
+        let definition = struct_context.definition_lookup.get(field_name)
+            .ok_or(CodegenError::new(field_node.clone(), ErrorRepr::InvalidStructDefinition))?;
+        let field_id = self.extract_definition_field(definition).map_err(|e| e.or_node(field_node))?;
+        Ok((field_id, struct_context.fields[field_id].field_type.clone()))
+    }
+
+    fn evaluate_constant_access(&mut self, context: usize, node: &Rc<Node>) -> Result<CodegenConstant, CodegenError> {
+        let (owner, definition) = self.find_definition_owner_by_node(node, context)?;
+        let Ok(constant_id) = self.extract_definition_constant(&definition) else {
+            return CodegenError::err(node.clone(), ErrorRepr::ExpectedConstantExpression);
+        };
+        self.constant_value(owner, constant_id).map_err(|e| e.or_node(node))
+    }
+
+    /// Puts a constant's value straight into the code, numbers, strings and locations become constant parameters
+    /// while everything else is built where it's used.
+    fn use_constant(&mut self, constant: &CodegenConstant, settings: &GenerateExpressionSettings, register_group: u64) -> CodegenValue {
+        let ident = match constant {
+            CodegenConstant::Number(number) => self.buffer.use_number(ParameterValue::Float(*number)),
+            CodegenConstant::String(string) => self.buffer.use_string(string),
+            CodegenConstant::Bool(bool) => self.buffer.use_number(ParameterValue::Float(if *bool { 1.0 } else { 0.0 })),
+            CodegenConstant::Location(x, y, z, pitch, yaw) => self.buffer.use_location(
+                ParameterValue::Float(*x), ParameterValue::Float(*y), ParameterValue::Float(*z), ParameterValue::Float(*pitch), ParameterValue::Float(*yaw)
+            ),
+            CodegenConstant::Vector(x, y, z) => {
+                let register = self.generate_expression_allocate_register(settings, register_group);
+                let (x, y, z) = (self.buffer.use_number(ParameterValue::Float(*x)), self.buffer.use_number(ParameterValue::Float(*y)), self.buffer.use_number(ParameterValue::Float(*z)));
+                self.push_expression_instruction(settings, instruction!(
+                    Var::Vector, [ (Ident, register), (Ident, x), (Ident, y), (Ident, z) ]
+                ));
+                register
+            }
+            CodegenConstant::FromName(primitive, name) => {
+                let register = self.generate_expression_allocate_register(settings, register_group);
+                let name = self.buffer.use_string(name);
+                self.push_expression_instruction(settings, match primitive {
+                    PrimitiveType::Sound => instruction!( Var::SetSoundType, [ (Ident, register), (Ident, name) ]),
+                    PrimitiveType::Particle => instruction!( Var::SetParticleType, [ (Ident, register), (Ident, name) ]),
+                    PrimitiveType::Potion => instruction!( Var::SetPotionType, [ (Ident, register), (Ident, name) ]),
+                    _ => instruction!( Var::SetItemType, [ (Ident, register), (Ident, name) ]),
+                });
+                register
+            }
+            CodegenConstant::Optional(_, Some(value)) => self.use_constant(value, settings, register_group).ident,
+            CodegenConstant::Optional(_, None) => self.buffer.use_string(NONE_SENTINEL),
+            // Enums with payloads are lists of their tag and then the payload, the others are only their tag
+            CodegenConstant::Enum(enum_id, variant_id, _) if !self.enum_has_payloads(*enum_id) => self.buffer.use_number(ParameterValue::Float(*variant_id as f64)),
+            CodegenConstant::Enum(_, variant_id, payload) => {
+                let elements = std::iter::once(CodegenConstant::Number(*variant_id as f64)).chain(payload.iter().cloned()).collect();
+                self.use_constant(&CodegenConstant::List(ValueType::Primitive(PrimitiveType::None), elements), settings, register_group).ident
+            }
+            // Structs are lists of their fields, in order
+            CodegenConstant::List(_, elements) | CodegenConstant::Struct(_, elements) => {
+                let register = self.generate_expression_allocate_register(settings, register_group);
+                let mut element_idents = Vec::new();
+                for element in elements {
+                    element_idents.push(self.use_constant(element, &settings.pass(), register_group).ident);
+                }
+                self.push_expression_instruction(settings, instruction!( Var::CreateList, [ (Ident, register) ]));
+                for (index, element_ident) in element_idents.into_iter().enumerate() {
+                    if index % 26 == 0 && index > 0 {
+                        self.push_expression_instruction(settings, instruction!( Var::AppendList, [ (Ident, register) ]));
+                    }
+                    self.push_expression_parameter(settings, Parameter::from_ident(element_ident));
+                }
+                register
+            }
+        };
+        CodegenValue::new(ident, constant.value_type())
+    }
+
+    fn is_inferred_type(decl_type: &Rc<Node>) -> bool {
+        matches!(&decl_type.node_type, NodeType::Primary(token) if token.token_type == TokenType::Keyword(Keyword::Let))
+    }
+
+    /// Whether a variable can be declared with the type of a value, which rules out nothing, comptime values, and lists of those (like an empty `[]`).
+    fn is_type_inferable(value_type: &ValueType) -> bool {
+        match value_type {
+            ValueType::Primitive(PrimitiveType::None) => false,
+            ValueType::Primitive(PrimitiveType::List(element_type)) => Self::is_type_inferable(element_type),
+            ValueType::Primitive(PrimitiveType::Map(entry_type, key_type)) => Self::is_type_inferable(entry_type) && Self::is_type_inferable(key_type),
+            ValueType::Primitive(PrimitiveType::Optional(inside_type)) => Self::is_type_inferable(inside_type),
+            ValueType::Primitive(..) | ValueType::Struct(..) | ValueType::Enum(..) => true,
+            ValueType::Comptime(..) | ValueType::Ident(..) => false,
+        }
+    }
+
+    fn is_match_wildcard(pattern: &Rc<Node>) -> bool {
+        matches!(&pattern.node_type, NodeType::Primary(token) if token.token_type == TokenType::Ident("_".to_owned()))
+    }
+
+    /// Whether a statement can return, break or continue, which a function split out of a match can't do for the code around it.
+    fn has_control_flow(node: &Rc<Node>) -> bool {
+        match &node.node_type {
+            NodeType::Return(..) | NodeType::Break(..) | NodeType::Continue(..) => true,
+            NodeType::Block(statements) => statements.iter().any(Self::has_control_flow),
+            NodeType::If(_, block) | NodeType::IfLet(_, _, block) | NodeType::While(_, block) | NodeType::For(_, _, block) | NodeType::Labeled(_, block) | NodeType::MatchArm(_, block) => Self::has_control_flow(block),
+            NodeType::Else(if_node, else_block) => Self::has_control_flow(if_node) || Self::has_control_flow(else_block),
+            NodeType::Match(_, arms) => arms.iter().any(Self::has_control_flow),
+            _ => false,
+        }
+    }
+
+    /// Looks the arm up in a dictionary of patterns to the names of functions split out for each arm, then calls it by name.
+    ///
+    /// The arm functions take no parameters, they see the variables around the match since DF shares line variables with the functions a line calls.
+    fn generate_match_dispatch(&mut self, context: usize, value_ident: u32, arms: Vec<(Vec<u32>, Rc<Vec<Rc<Node>>>)>, wildcard_block: Option<Rc<Vec<Rc<Node>>>>, register_group: u64) -> Result<(), CodegenError> {
+        let match_name = format!("{}.match{}", self.get_context_full_name(context), self.match_count);
+        self.match_count += 1;
+        let keys_register = self.buffer.allocate_grouped_line_register(register_group);
+        let names_register = self.buffer.allocate_grouped_line_register(register_group);
+        let dictionary_register = self.buffer.allocate_grouped_line_register(register_group);
+        let mut keys_instruction = instruction!(Var::CreateList, [ (Ident, keys_register) ]);
+        let mut names_instruction = instruction!(Var::CreateList, [ (Ident, names_register) ]);
+        let mut arm_functions = Vec::new();
+        for (arm_index, (pattern_idents, arm_block)) in arms.into_iter().enumerate() {
+            let arm_name = format!("{}#{}", match_name, arm_index);
+            let arm_name_ident = self.buffer.use_string(&arm_name);
+            for pattern_ident in pattern_idents {
+                keys_instruction.params.push(Parameter::from_ident(pattern_ident));
+                names_instruction.params.push(Parameter::from_ident(arm_name_ident));
+            }
+            arm_functions.push((arm_name, arm_block));
+        }
+        let fallback_name = format!("{}#_", match_name);
+        let fallback_name_ident = self.buffer.use_string(&fallback_name);
+        arm_functions.push((fallback_name, wildcard_block.unwrap_or_default()));
+
+        let target_name = Self::make_var_name(&match_name, "mat");
+        let target_ident = self.buffer.use_variable(&target_name, DP::Var::Scope::Line);
+        self.buffer.code_buffer.push_instruction(keys_instruction);
+        self.buffer.code_buffer.push_instruction(names_instruction);
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Var::CreateDict, [ (Ident, dictionary_register), (Ident, keys_register), (Ident, names_register) ]
+        ));
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Var::Set, [ (Ident, target_ident), (Ident, fallback_name_ident) ]
+        ));
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Varif::DictHasKey, [ (Ident, dictionary_register), (Ident, value_ident) ]
+        ));
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Var::GetDictValue, [ (Ident, target_ident), (Ident, dictionary_register), (Ident, value_ident) ]
+        ));
+        self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+        let dynamic_function_id = self.buffer.use_function(&format!("%var({})", target_name));
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Call, [ (Ident, dynamic_function_id) ]
+        ));
+
+        for (arm_name, arm_block) in arm_functions {
+            let function_code = std::mem::replace(&mut self.buffer.code_buffer, DFBin::new());
+            let arm_function_id = self.buffer.use_function(&arm_name);
+            self.buffer.code_buffer.push_instruction(instruction!(
+                Func, [ (Ident, arm_function_id) ]
+            ));
+            self.generate_body_code(context, arm_block, &ValueType::Primitive(PrimitiveType::None), None)?;
+            let mut arm_code = std::mem::replace(&mut self.buffer.code_buffer, function_code);
+            self.match_arm_code.append_bin_mut(&mut arm_code);
+        }
+        Ok(())
+    }
+
+    fn get_loop_label(label: &Rc<Node>) -> Result<&String, CodegenError> {
+        let NodeType::Primary(label_token) = &label.node_type else {
+            return CodegenError::err(label.clone(), ErrorRepr::ExpectedLoopLabel);
+        };
+        let TokenType::Label(label_string) = &label_token.token_type else {
+            return CodegenError::err(label.clone(), ErrorRepr::ExpectedLoopLabel);
+        };
+        Ok(label_string)
+    }
+
+    /// Clears the variable nested loops use to break out of or continue the labeled loop about to start.
+    fn start_labeled_loop(&mut self, label: &str) -> u32 {
+        let var_name = Self::make_var_name(label, "lbl");
+        let var_ident = self.buffer.use_variable(var_name.as_ref(), DP::Var::Scope::Line);
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Var::Set, [ (Ident, var_ident), (Int, 0) ]
+        ));
+        var_ident
+    }
+
+    /// Right after a loop ends, carries on the `break 'label` or `continue 'label` that ended it towards the labeled loop it was for.
+    ///
+    /// The loop variable is 1 to break the labeled loop and 2 to continue it.
+    fn exit_nested_loop(&mut self, exits: Vec<(String, u32)>, loop_stack: &mut Vec<(usize, Option<String>, Option<u32>, Vec<(String, u32)>)>) {
+        let Some(parent_loop) = loop_stack.last_mut() else {
+            return;
+        };
+        for (exit_label, loop_variable) in exits {
+            if parent_loop.1.as_ref() == Some(&exit_label) {
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Varif::Eq, [ (Ident, loop_variable), (Int, 1) ]
+                ));
+                self.buffer.code_buffer.push_instruction(instruction!(Ctrl::StopRepeat));
+                self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Varif::Eq, [ (Ident, loop_variable), (Int, 2) ]
+                ));
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::Set, [ (Ident, loop_variable), (Int, 0) ]
+                ));
+                self.buffer.code_buffer.push_instruction(instruction!(Ctrl::Skip));
+                self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+            } else {
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Varif::Greater, [ (Ident, loop_variable), (Int, 0) ]
+                ));
+                self.buffer.code_buffer.push_instruction(instruction!(Ctrl::StopRepeat));
+                self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+                if !parent_loop.3.iter().any(|exit| exit.0 == exit_label) {
+                    parent_loop.3.push((exit_label, loop_variable));
+                }
+            }
+        }
+    }
+
+    fn call_function(&mut self, context: usize, function_ident: &Rc<Node>, function_params: &Rc<Node>, return_ident: u32) -> Result<ValueType, CodegenError> {
+        // let func_context = self.find_function_by_node(function_ident, context)?;
+        let call_func_reg_group = self.buffer.allocate_line_register_group();
+        let function_ident_evaluation = self.generate_expression(context, function_ident, GenerateExpressionSettings::comptime().prefer_category(IdentifierCategory::Function))?;
+        
+        let mut constructed_type = None;
+        let (func_context, struct_func_ident, allocated_self_register) = match function_ident_evaluation.value.value_type {
+            ValueType::Comptime(ComptimeType::Function(func_context)) => (func_context, None, None),
+            ValueType::Comptime(ComptimeType::Type(RealtimeValueType::Struct(struct_type))) => {
+                // Calling a struct creates a new instance in the returned value, which its constructor is then called on as `self`
+                let Some(constructor) = self.context_borrow(struct_type)?.constructor else {
+                    return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedConstructor);
+                };
+                self.create_default_struct_instance(function_ident, struct_type, return_ident)?;
+                constructed_type = Some(ValueType::Struct(struct_type));
+                (constructor, Some(return_ident), None)
+            },
+            ValueType::Comptime(ComptimeType::SelfFunction(func_context, ..)) => {
+                let self_reg = self.buffer.allocate_grouped_line_register(call_func_reg_group);
+                let NodeType::Access(function_ident_inside, ..) = &function_ident.node_type else {
+                    return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedAccessableIdentifier);
+                };
+                let function_ident_evaluation_codeblocked = self.generate_expression(context, function_ident_inside, GenerateExpressionSettings::ident(self_reg).prefer_category(IdentifierCategory::Field))?;
+                (func_context, Some(self_reg), Some(function_ident_evaluation_codeblocked))
+            },
+            ValueType::Comptime(ComptimeType::MapFunction(map_function)) => {
+                self.buffer.free_line_register_group(call_func_reg_group);
+                return self.call_map_function(context, function_ident, function_params, map_function, return_ident);
+            },
+            ValueType::Comptime(ComptimeType::EnumVariant(enum_id, variant_id)) => {
+                self.buffer.free_line_register_group(call_func_reg_group);
+                return self.construct_enum_variant(context, function_params, enum_id, variant_id, return_ident);
+            },
+            _ => { return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedFunctionIdentifier); }
+        };
+        let params = Self::extract_parameter_vec(function_params)?;
+        // A generic func called without type arguments gets them from the types of its parameters, so those are generated first.
+        let mut generated_params = Vec::new();
+        let func_context = if self.is_generic(func_context)? {
+            for param in &params {
+                generated_params.push(self.generate_expression(context, param, GenerateExpressionSettings::parameter(call_func_reg_group))?);
+            }
+            let param_types: Vec<ValueType> = generated_params.iter().map(|param_expression| param_expression.value.value_type.clone()).collect();
+            self.infer_generic_function(func_context, &param_types, function_params)?
+        } else {
+            func_context
+        };
+        if self.context_borrow(func_context)?.comptime {
+            self.runtime_const_functions.insert(func_context);
+        }
+        let func_name = self.get_context_full_name(func_context).clone();
+        let func_id = self.buffer.use_function(func_name.as_str());
+        let mut call_instruction = instruction!(Call, [
+            (Ident, func_id)
+        ]);
+        let ContextType::Function(ret_type_field) = self.context_borrow(func_context)?.context_type.clone() else {
+            return CodegenError::err(function_ident.clone(), ErrorRepr::Generic);
+        };
+        let ret_type_field = match ret_type_field {
+            FunctionType::Func(value_type) => value_type,
+            FunctionType::Event(..) => ValueType::Primitive(PrimitiveType::None),
+            FunctionType::EventListener(..) => {
+                return CodegenError::err(function_ident.clone(), ErrorRepr::CannotCallEventListener)
+            },
+        };
+        if let Some(struct_func_ident) = struct_func_ident {
+            call_instruction.params.push(Parameter::from_ident(struct_func_ident));
+        }
+        if !matches!(ret_type_field, ValueType::Primitive(PrimitiveType::None)) { // function has a return value
+            call_instruction.params.push(Parameter::from_ident(return_ident))
+        }
+        let func_fields = self.context_borrow(func_context)?.fields.clone();
+        if params.len() > func_fields.len() {
+            return CodegenError::err(function_params.clone(), ErrorRepr::UnexpectedFunctionParameter)
+        }
+        if params.len() < func_fields.len() {
+            return CodegenError::err(function_params.clone(), ErrorRepr::ExpectedFunctionParameter)
+        }
+        let mut generated_params = generated_params.into_iter();
+        for (param, param_field) in params.into_iter().zip(func_fields) {
+            let param_settings = GenerateExpressionSettings::parameter(call_func_reg_group).expect_type(&param_field.field_type);
+            let param_expression = match generated_params.next() {
+                Some(generated_param) => self.implicitly_cast(context, &param, generated_param, &param_settings, call_func_reg_group)?,
+                None => self.generate_expression(context, &param, param_settings)?,
+            };
+            call_instruction.params.push(Parameter::from_ident(param_expression.value.ident));
+        }
+        self.buffer.code_buffer.push_instruction(call_instruction);
+        if let Some(allocated_self_register) = allocated_self_register {
+            if let Some(allocated_self_register_trace) = allocated_self_register.trace {
+                self.set_trace_to_value(context, allocated_self_register_trace, allocated_self_register.value)?;
+            }
+        }
+        self.buffer.free_line_register_group(call_func_reg_group);
+        Ok(constructed_type.unwrap_or(ret_type_field))
+    }
+
+
+    /// Builds the value of an enum variant with a payload, which is a list of its tag and then the payload.
+    fn construct_enum_variant(&mut self, context: usize, function_params: &Rc<Node>, enum_id: usize, variant_id: usize, return_ident: u32) -> Result<ValueType, CodegenError> {
+        let payload_types = self.enum_payload_types(enum_id, variant_id)?;
+        let params = Self::extract_parameter_vec(function_params)?;
+        if params.len() > payload_types.len() {
+            return CodegenError::err(function_params.clone(), ErrorRepr::UnexpectedFunctionParameter);
+        }
+        if params.len() < payload_types.len() {
+            return CodegenError::err(function_params.clone(), ErrorRepr::ExpectedFunctionParameter);
+        }
+        let register_group = self.buffer.allocate_line_register_group();
+        let mut payload_idents = Vec::new();
+        for (param, payload_type) in params.iter().zip(payload_types) {
+            let param_settings = GenerateExpressionSettings::parameter(register_group).expect_type(&payload_type);
+            payload_idents.push(self.generate_expression(context, param, param_settings)?.value.ident);
+        }
+        self.buffer.code_buffer.push_instruction(instruction!(Var::CreateList, [ (Ident, return_ident), (Int, variant_id) ]));
+        for (index, payload_ident) in payload_idents.into_iter().enumerate() {
+            let values = index + 2; // The return ident and the variant id come first
+            if values % 26 == 0 && values > 0 {
+                self.buffer.code_buffer.push_instruction(instruction!(Var::AppendList, [ (Ident, return_ident) ]));
+            }
+            self.buffer.code_buffer.push_parameter(Parameter::from_ident(payload_ident));
+        }
+        self.buffer.free_line_register_group(register_group);
+        Ok(ValueType::Enum(enum_id))
+    }
+
+    /// Whether any variant of an enum has a payload, which makes its values lists instead of only their tag.
+    fn enum_has_payloads(&self, enum_id: usize) -> bool {
+        self.context_borrow(enum_id).is_ok_and(|enum_context| enum_context.variants.iter().any(|variant| !variant.fields.is_empty()))
+    }
+
+    fn enum_payload_types(&self, enum_id: usize, variant_id: usize) -> Result<Vec<ValueType>, CodegenError> {
+        let enum_context = self.context_borrow(enum_id)?;
+        Ok(enum_context.variants[variant_id].fields.iter().map(|field_id| enum_context.fields[*field_id].field_type.clone()).collect())
+    }
+
+    fn find_enum_variant(&self, enum_id: usize, variant_ident: &Rc<Node>) -> Result<usize, CodegenError> {
+        let variant_name = Self::get_primary_as_ident(variant_ident, ErrorRepr::ExpectedEnumVariantIdentifier)?;
+        self.context_borrow(enum_id)?.variants.iter()
+            .position(|variant| &variant.name == variant_name)
+            .ok_or(CodegenError::new(variant_ident.clone(), ErrorRepr::UnknownEnumVariant))
+    }
+
+    /// The enum and variant a node like `State.Playing` names, or nothing if it isn't an access on an enum.
+    fn get_enum_variant(&mut self, context: usize, node: &Rc<Node>) -> Result<Option<(usize, usize)>, CodegenError> {
+        let NodeType::Access(accessed, variant_ident) = &node.node_type else {
+            return Ok(None);
+        };
+        // Only names can be types, anything else would have to be generated to find that out
+        if !Self::is_name_path(accessed) {
+            return Ok(None);
+        }
+        let Ok(ValueType::Enum(enum_id)) = self.get_type(accessed, context) else {
+            return Ok(None);
+        };
+        Ok(Some((enum_id, self.find_enum_variant(enum_id, variant_ident)?)))
+    }
+
+    fn is_name_path(node: &Rc<Node>) -> bool {
+        match &node.node_type {
+            NodeType::Primary(token) => matches!(token.token_type, TokenType::Ident(..)),
+            NodeType::Access(accessed, _) => Self::is_name_path(accessed),
+            _ => false,
+        }
+    }
+
+    /// Gets the tag out of an enum value with payloads.
+    fn get_enum_tag(&mut self, enum_value: CodegenValue, settings: &GenerateExpressionSettings, register_group: u64) -> CodegenValue {
+        let register = self.buffer.allocate_grouped_line_register(register_group);
+        self.push_expression_instruction(settings, instruction!(
+            Var::GetListValue, [ (Ident, register), (Ident, enum_value.ident), (Int, 1) ]
+        ));
+        CodegenValue::new(register, enum_value.value_type)
+    }
+
+    /// Reads a match pattern like `State.Playing(round)` for the enum being matched, giving back its variant and the names its payload is bound to.
+    fn get_enum_pattern(&mut self, context: usize, enum_id: usize, pattern: &Rc<Node>) -> Result<(usize, Vec<Option<Rc<Node>>>), CodegenError> {
+        let (variant_node, bindings) = match &pattern.node_type {
+            NodeType::FunctionCall(variant_node, bindings) => (variant_node, Some(bindings)),
+            _ => (pattern, None),
+        };
+        let Some((pattern_enum_id, variant_id)) = self.get_enum_variant(context, variant_node)? else {
+            return CodegenError::err(pattern.clone(), ErrorRepr::InvalidEnumPattern);
+        };
+        if pattern_enum_id != enum_id {
+            return CodegenError::err(pattern.clone(), ErrorRepr::InvalidEnumPattern);
+        }
+        let Some(bindings) = bindings else {
+            return Ok((variant_id, Vec::new()));
+        };
+        let payload_len = self.context_borrow(enum_id)?.variants[variant_id].fields.len();
+        let bindings = Self::extract_parameter_vec(bindings)?;
+        if payload_len == 0 {
+            return CodegenError::err(pattern.clone(), ErrorRepr::UnexpectedEnumPayload);
+        }
+        if bindings.len() > payload_len {
+            return CodegenError::err(pattern.clone(), ErrorRepr::UnexpectedFunctionParameter);
+        }
+        if bindings.len() < payload_len {
+            return CodegenError::err(pattern.clone(), ErrorRepr::ExpectedFunctionParameter);
+        }
+        // `_` leaves that part of the payload out
+        Ok((variant_id, bindings.into_iter().map(|binding| (!Self::is_match_wildcard(&binding)).then_some(binding)).collect()))
+    }
+
+    /// Calls one of the built-in functions of a map, which are each a dictionary action on the map.
+    fn call_map_function(&mut self, context: usize, function_ident: &Rc<Node>, function_params: &Rc<Node>, map_function: MapFunction, return_ident: u32) -> Result<ValueType, CodegenError> {
+        let NodeType::Access(map_node, ..) = &function_ident.node_type else {
+            return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedAccessableIdentifier);
+        };
+        let register_group = self.buffer.allocate_line_register_group();
+        let map_expression = self.generate_expression(context, map_node, GenerateExpressionSettings::parameter(register_group))?;
+        let ValueType::Primitive(PrimitiveType::Map(entry_type, key_type)) = map_expression.value.value_type.clone() else {
+            return CodegenError::err(map_node.clone(), ErrorRepr::ExpectedMapType);
+        };
+        let map_ident = map_expression.value.ident;
+        let params = Self::extract_parameter_vec(function_params)?;
+        let param_count = match map_function {
+            MapFunction::Keys | MapFunction::Values => 0,
+            MapFunction::Has | MapFunction::Remove => 1,
+        };
+        if params.len() > param_count {
+            return CodegenError::err(function_params.clone(), ErrorRepr::UnexpectedFunctionParameter);
+        }
+        if params.len() < param_count {
+            return CodegenError::err(function_params.clone(), ErrorRepr::ExpectedFunctionParameter);
+        }
+        let key_ident = match params.first() {
+            Some(param) => {
+                let settings = GenerateExpressionSettings::parameter(register_group).expect_type(&key_type);
+                let key = self.generate_expression(context, param, settings.clone())?.value;
+                Some(self.generate_map_key(key, &settings, register_group))
+            },
+            None => None,
+        };
+        let return_type = match (map_function, key_ident) {
+            (MapFunction::Keys, _) => {
+                if *key_type != ValueType::Primitive(PrimitiveType::String) {
+                    return CodegenError::err(function_ident.clone(), ErrorRepr::ExpectedStringMapKeys);
+                }
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::GetDictKeys, [ (Ident, return_ident), (Ident, map_ident) ]
+                ));
+                ValueType::Primitive(PrimitiveType::List(key_type))
+            },
+            (MapFunction::Values, _) => {
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::GetDictValues, [ (Ident, return_ident), (Ident, map_ident) ]
+                ));
+                ValueType::Primitive(PrimitiveType::List(entry_type))
+            },
+            (MapFunction::Has, Some(key_ident)) => {
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::Set, [ (Ident, return_ident), (Int, 0) ] ));
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Varif::DictHasKey, [ (Ident, map_ident), (Ident, key_ident) ] ));
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::Set, [ (Ident, return_ident), (Int, 1) ] ));
+                self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+                ValueType::Primitive(PrimitiveType::Bool)
+            },
+            (MapFunction::Remove, Some(key_ident)) => {
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::RemoveDictEntry, [ (Ident, map_ident), (Ident, key_ident) ]
+                ));
+                // The map might be a copy, like a struct field, so it's written back to where it came from
+                if let Some(map_trace) = map_expression.trace {
+                    self.set_trace_to_value(context, map_trace, map_expression.value)?;
+                }
+                ValueType::Primitive(PrimitiveType::None)
+            },
+            _ => { return CodegenError::err(function_params.clone(), ErrorRepr::ExpectedFunctionParameter); }
+        };
+        self.buffer.free_line_register_group(register_group);
+        Ok(return_type)
+    }
+
+    fn generate_function_code(&mut self, context: usize, body: Rc<Vec<Rc<Node>>>, fields: Vec<Field>, return_type: ValueType) -> Result<(), CodegenError> {
+        // self.return_runtimes[context] = 
+        let parent = self.parents[context];
+        let parent_context = self.context_borrow(parent)?.context_type.clone();
+        let context_type = self.context_borrow(context)?.context_type.clone();
+        
+        let func_name = self.get_context_full_name(context).clone();
+        let func_id = self.buffer.use_function(func_name.as_str());
+        self.buffer.code_buffer.push_instruction(instruction!(
+            Func, [
+                (Ident, func_id)
+            ]
+        ));
+        //println!("Generating function {}, return type: {:#?}", context, return_type);
+        if matches!(parent_context, ContextType::Struct) {
+            let self_struct_param_ident = self.buffer.use_return_param("self");
+            self.buffer.code_buffer.push_parameter(Parameter::from_ident(self_struct_param_ident.0));
+        }
+        if matches!(context_type, ContextType::Function(FunctionType::EventListener(..))) {
+            let event_param_ident = self.buffer.use_return_param("event");
+            self.buffer.code_buffer.push_parameter(Parameter::from_ident(event_param_ident.0));  
+        }
 
         let return_type_ident = if !matches!(return_type, ValueType::Primitive(PrimitiveType::None)) {
             let return_type_param_ident = self.buffer.use_return_param("fr");
@@ -1861,7 +3970,6 @@ impl CodeGen {
         } else {
             None
         };
-        let mut returned_value = false;
         let mut field_id = 0;
         let mut param_and_var_idents = Vec::new();
         for field in fields {
@@ -1879,16 +3987,68 @@ impl CodeGen {
             self.buffer.code_buffer.push_parameter(Parameter::from_ident(param_and_var_ident.0));
             field_id += 1;
         }
+        let returned_value = self.generate_body_code(context, body, &return_type, return_type_ident)?;
+        if !returned_value && return_type_ident.is_some() { // This means the function needs to a return a value, but hasn't in the core branch.
+            return CodegenError::err(self.context_borrow(context)?.node.clone(), ErrorRepr::ExpectedFunctionReturnValue);
+        }
+        if let ContextType::Function(FunctionType::Event(EventStructType::Struct(event_struct_id, autobuilt_struct))) = context_type {
+            let send_id = if autobuilt_struct {
+                let reg = self.buffer.allocate_line_register();
+                self.buffer.code_buffer.push_instruction(instruction!(
+                    Var::CreateList, [(Ident, reg)]
+                ));
+                for add_param_autobuild in param_and_var_idents {
+                    self.buffer.code_buffer.push_parameter(Parameter::from_ident(add_param_autobuild.1))
+                }
+                self.buffer.free_line_register(reg)?;
+                reg
+            } else {
+                param_and_var_idents.get(0).expect("If event has no autobuilt struct, it should have 1 and only param (the struct itself)").1
+            };
+            if let Some(listeners) = self.context_listeners.get(&context) {
+                for listener in listeners {
+                    let listener_name = self.get_context_full_name(*listener).clone();
+                    let listener_id = self.buffer.use_function(listener_name.as_str());
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Call, [
+                            (Ident, listener_id), (Ident, send_id)
+                        ]
+                    ))
+                }
+            }
+        }
+        // The functions split out of dispatched matches start with their own header, so they go after everything of this function
+        let mut match_arm_code = std::mem::replace(&mut self.match_arm_code, DFBin::new());
+        self.buffer.code_buffer.append_bin_mut(&mut match_arm_code);
+        Ok(())
+    }
+
+    /// Generates the statements of a function body, returning whether its core branch returned a value.
+    fn generate_body_code(&mut self, context: usize, body: Rc<Vec<Rc<Node>>>, return_type: &ValueType, return_type_ident: Option<u32>) -> Result<bool, CodegenError> {
+        let mut returned_value = false;
         let mut body_stack: VecDeque<(usize, Rc<Vec<Rc<Node>>>, Vec<String>, Option<Instruction>, CodegenBodyStackMode)> = VecDeque::new();
         body_stack.push_back((0, body, Vec::new(), None, CodegenBodyStackMode::None));
+        // The loops the current statement is in: the body stack length their body was pushed at, their label, the variable
+        // used to break or continue them from nested loops, and the labeled loops further out that their body exits to.
+        let mut loop_stack: Vec<(usize, Option<String>, Option<u32>, Vec<(String, u32)>)> = Vec::new();
+        // The payload bindings of enum match arms, declared once their arm block starts: the enum value, and each binding's name, type and list index.
+        let mut arm_bindings: HashMap<*const Vec<Rc<Node>>, (u32, Vec<(Rc<Node>, ValueType, usize)>)> = HashMap::new();
         
         'total: loop {
             'verify: loop {
                 if body_stack[0].0 >= body_stack[0].1.len() {
+                    let ended_loop = if loop_stack.last().is_some_and(|ended_loop| ended_loop.0 == body_stack.len()) {
+                        loop_stack.pop()
+                    } else {
+                        None
+                    };
                     let remove = body_stack.pop_front().expect("Body stack should pop a front");
                     if let Some(instruction_trail) = remove.3 {
                         self.buffer.code_buffer.push_instruction(instruction_trail);
                     }
+                    if let Some(ended_loop) = ended_loop {
+                        self.exit_nested_loop(ended_loop.3, &mut loop_stack);
+                    }
                     for remove_variable in remove.2 {
                         self.runtime_vars[context].remove(&remove_variable);
                     }
@@ -1902,24 +4062,44 @@ impl CodeGen {
             if body_stack.len() == 0 {
                 break 'total;   
             }
+            if body_stack[0].0 == 0 {
+                if let Some((enum_ident, bindings)) = arm_bindings.remove(&Rc::as_ptr(&body_stack[0].1)) {
+                    for (binding, binding_type, payload_index) in bindings {
+                        let binding_variable = self.declare_runtime_variable_typed(context, binding_type, &binding)?.0.variable.ident;
+                        self.buffer.code_buffer.push_instruction(instruction!(
+                            Var::GetListValue, [ (Ident, binding_variable), (Ident, enum_ident), (Int, payload_index) ] ));
+                    }
+                    body_stack[0].2.extend(self.block_runtime_vars_add.clone());
+                    self.block_runtime_vars_add.clear();
+                }
+            }
             body_stack[0].0 += 1;
             let body_get = &body_stack[0];
             let body_stack_mode = body_get.4.clone();
             let statement = (&body_get.1[body_get.0 - 1]).clone();
-            match statement.as_ref() {
-                Node::Declaration(..) | Node::Assignment(..) | Node::DFASM(..) => {
+            let (statement, loop_label) = match &statement.node_type {
+                NodeType::Labeled(label, labeled) => {
+                    if !matches!(labeled.node_type, NodeType::While(..) | NodeType::For(..)) {
+                        return CodegenError::err(label.clone(), ErrorRepr::UnexpectedLoopLabel);
+                    }
+                    (labeled.clone(), Some(Self::get_loop_label(label)?.clone()))
+                },
+                _ => (statement, None),
+            };
+            match &statement.node_type {
+                NodeType::Declaration(..) | NodeType::Assignment(..) | NodeType::CompoundAssignment(..) | NodeType::DFASM(..) => {
                     let void_register = self.buffer.constant_void();
                     self.generate_expression(context, &statement.clone(), GenerateExpressionSettings::void(void_register))?;
                 },
-                Node::Else(if_node, else_block) => {
-                    let Node::Block(else_block) = else_block.as_ref() else {
+                NodeType::Else(if_node, else_block) => {
+                    let NodeType::Block(else_block) = &else_block.node_type else {
                         return CodegenError::err(else_block.clone(), ErrorRepr::ExpectedBlock);
                     };
                     body_stack.push_front((0, Rc::new(else_block.clone()), Vec::new(), Some(instruction!(EndIf)), body_stack_mode));
                     // Do the if stuff
                     body_stack.push_front((0, Rc::new(vec![if_node.clone()]), Vec::new(), None, CodegenBodyStackMode::Else));
                 },
-                Node::If(if_condition, if_block) => {
+                NodeType::If(if_condition, if_block) => {
                     let if_allocation = self.buffer.allocate_line_register_group();
                     let expr_id = self.generate_expression(context, if_condition, GenerateExpressionSettings::parameter(if_allocation).expect_type(&ValueType::Primitive(PrimitiveType::Bool)))?;
                     self.buffer.code_buffer.push_instruction(instruction!(
@@ -1928,7 +4108,7 @@ impl CodeGen {
                             (Int, 1)
                         ]
                     ));
-                    let Node::Block(if_block) = if_block.as_ref() else {
+                    let NodeType::Block(if_block) = &if_block.node_type else {
                         return CodegenError::err(if_block.clone(), ErrorRepr::ExpectedBlock);
                     };
                     body_stack.push_front((0, Rc::new(if_block.clone()), Vec::new(), Some(match body_stack_mode {
@@ -1937,8 +4117,39 @@ impl CodeGen {
                     }), body_stack_mode));
                     self.buffer.free_line_register_group(if_allocation);
                 },
-                Node::Return(return_value) => {
-                    if !matches!(return_value.as_ref(), Node::None) { //You're returning a value
+                NodeType::IfLet(unwrapped_ident, optional_node, if_let_block) => {
+                    let if_let_allocation = self.buffer.allocate_line_register_group();
+                    let optional = self.generate_expression(context, optional_node, GenerateExpressionSettings::parameter(if_let_allocation))?.value;
+                    let ValueType::Primitive(PrimitiveType::Optional(inside_type)) = optional.value_type else {
+                        return CodegenError::err(optional_node.clone(), ErrorRepr::ExpectedOptionalType);
+                    };
+                    // Worked out like a `!=`, so an `else` can follow it just like after an if
+                    let some_register = self.buffer.allocate_grouped_line_register(if_let_allocation);
+                    let none = self.buffer.use_string(NONE_SENTINEL);
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Var::Set, [ (Ident, some_register), (Int, 1) ] ));
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Varif::Eq, [ (Ident, optional.ident), (Ident, none) ] ));
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Var::Set, [ (Ident, some_register), (Int, 0) ] ));
+                    self.buffer.code_buffer.push_instruction(instruction!(EndIf));
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Varif::Eq, [ (Ident, some_register), (Int, 1) ] ));
+                    let NodeType::Block(if_let_block) = &if_let_block.node_type else {
+                        return CodegenError::err(if_let_block.clone(), ErrorRepr::ExpectedBlock);
+                    };
+                    let unwrapped_variable = self.declare_runtime_variable_typed(context, (*inside_type).clone(), unwrapped_ident)?.0.variable.ident;
+                    self.buffer.code_buffer.push_instruction(instruction!(
+                        Var::Set, [ (Ident, unwrapped_variable), (Ident, optional.ident) ] ));
+                    // The unwrapped variable gets added to the body below, so it goes out of scope along with it.
+                    body_stack.push_front((0, Rc::new(if_let_block.clone()), Vec::new(), Some(match body_stack_mode {
+                        CodegenBodyStackMode::None => instruction!(EndIf),
+                        CodegenBodyStackMode::Else => instruction!(Else),
+                    }), body_stack_mode));
+                    self.buffer.free_line_register_group(if_let_allocation);
+                },
+                NodeType::Return(return_value) => {
+                    if !matches!(return_value.node_type, NodeType::None) { //You're returning a value
                         let Some(return_type_ident_some) = return_type_ident else {
                             return CodegenError::err(return_value.clone(), ErrorRepr::UnexpectedReturnValue)
                         };
@@ -1949,11 +4160,12 @@ impl CodeGen {
                         returned_value = true;
                     }
                 },
-                Node::FunctionCall(function_ident, function_parameters) => {
+                NodeType::FunctionCall(function_ident, function_parameters) => {
                     let void = self.buffer.constant_void();
                     self.call_function(context, function_ident, function_parameters, void)?;
                 },
-                Node::While(while_cond, while_block) => {
+                NodeType::While(while_cond, while_block) => {
+                    let loop_variable = loop_label.as_ref().map(|label| self.start_labeled_loop(label));
                     self.buffer.code_buffer.push_instruction(instruction!(Rep::Forever));
                     let while_allocation = self.buffer.allocate_line_register_group();
                     let expr_id = self.generate_expression(context, while_cond, GenerateExpressionSettings::parameter(while_allocation).expect_type(&ValueType::Primitive(PrimitiveType::Bool)))?;
@@ -1965,58 +4177,256 @@ impl CodeGen {
                     ));
                     self.buffer.code_buffer.push_instruction(instruction!(Ctrl::StopRepeat));
                     self.buffer.code_buffer.push_instruction(instruction!(EndIf));
-                    let Node::Block(if_block) = while_block.as_ref() else {
+                    let NodeType::Block(if_block) = &while_block.node_type else {
                         return CodegenError::err(while_block.clone(), ErrorRepr::ExpectedBlock);
                     };
                     body_stack.push_front((0, Rc::new(if_block.clone()), Vec::new(), Some(instruction!(EndRep)), body_stack_mode));
+                    loop_stack.push((body_stack.len(), loop_label, loop_variable, Vec::new()));
                     self.buffer.free_line_register_group(while_allocation);
                 }
+                NodeType::For(for_ident, for_iterated, for_block) => {
+                    let for_allocation = self.buffer.allocate_line_register_group();
+                    let repeat_instruction = match &for_iterated.node_type {
+                        NodeType::Range(range_start, range_end, range_step) => {
+                            let number_type = ValueType::Primitive(PrimitiveType::Number);
+                            let start_id = self.generate_expression(context, range_start, GenerateExpressionSettings::parameter(for_allocation).expect_type(&number_type))?.value.ident;
+                            let end_id = self.generate_expression(context, range_end, GenerateExpressionSettings::parameter(for_allocation).expect_type(&number_type))?.value.ident;
+                            let step_id = if matches!(range_step.node_type, NodeType::None) {
+                                None
+                            } else {
+                                Some(self.generate_expression(context, range_step, GenerateExpressionSettings::parameter(for_allocation).expect_type(&number_type))?.value.ident)
+                            };
+                            let var_ident = self.declare_runtime_variable_typed(context, number_type, for_ident)?.0.variable.ident;
+                            let mut range_instruction = instruction!(
+                                Rep::Range, [
+                                    (Ident, var_ident),
+                                    (Ident, start_id),
+                                    (Ident, end_id)
+                                ]
+                            );
+                            if let Some(step_id) = step_id {
+                                range_instruction.params.push(Parameter::from_ident(step_id));
+                            }
+                            range_instruction
+                        },
+                        _ => {
+                            let list = self.generate_expression(context, for_iterated, GenerateExpressionSettings::parameter(for_allocation))?.value;
+                            let ValueType::Primitive(PrimitiveType::List(inside_type)) = list.value_type else {
+                                return CodegenError::err(for_iterated.clone(), ErrorRepr::ExpectedListType);
+                            };
+                            let var_ident = self.declare_runtime_variable_typed(context, (*inside_type).clone(), for_ident)?.0.variable.ident;
+                            instruction!(
+                                Rep::ForEach, [
+                                    (Ident, var_ident),
+                                    (Ident, list.ident)
+                                ]
+                            )
+                        },
+                    };
+                    let loop_variable = loop_label.as_ref().map(|label| self.start_labeled_loop(label));
+                    self.buffer.code_buffer.push_instruction(repeat_instruction);
+                    let NodeType::Block(for_block) = &for_block.node_type else {
+                        return CodegenError::err(for_block.clone(), ErrorRepr::ExpectedBlock);
+                    };
+                    // The loop variable gets added to the loop body below, so it goes out of scope along with it.
+                    body_stack.push_front((0, Rc::new(for_block.clone()), Vec::new(), Some(instruction!(EndRep)), body_stack_mode));
+                    loop_stack.push((body_stack.len(), loop_label, loop_variable, Vec::new()));
+                    self.buffer.free_line_register_group(for_allocation);
+                }
+                NodeType::Match(match_value, match_arms) => {
+                    let match_allocation = self.buffer.allocate_line_register_group();
+                    let value = self.generate_expression(context, match_value, GenerateExpressionSettings::parameter(match_allocation))?.value;
+                    // Enums are matched by their tag, and every variant has to be covered
+                    let mut enum_variants_left = None;
+                    let mut match_ident = value.ident;
+                    if let ValueType::Enum(enum_id) = value.value_type {
+                        enum_variants_left = Some((enum_id, (0..self.context_borrow(enum_id)?.variants.len()).collect::<Vec<usize>>()));
+                        if self.enum_has_payloads(enum_id) {
+                            match_ident = self.get_enum_tag(value.clone(), &GenerateExpressionSettings::parameter(match_allocation), match_allocation).ident;
+                        }
+                    }
+                    let mut arms = Vec::new();
+                    let mut wildcard_block = None;
+                    for match_arm in match_arms {
+                        let NodeType::MatchArm(patterns, arm_block) = &match_arm.node_type else {
+                            return CodegenError::err(match_arm.clone(), ErrorRepr::ExpectedMatchArm);
+                        };
+                        if wildcard_block.is_some() {
+                            return CodegenError::err(match_arm.clone(), ErrorRepr::UnreachableMatchArm);
+                        }
+                        let NodeType::Block(arm_block) = &arm_block.node_type else {
+                            return CodegenError::err(arm_block.clone(), ErrorRepr::ExpectedBlock);
+                        };
+                        if patterns.iter().any(Self::is_match_wildcard) {
+                            wildcard_block = Some(Rc::new(arm_block.clone()));
+                            continue;
+                        }
+                        let mut pattern_idents = Vec::new();
+                        let arm_block = Rc::new(arm_block.clone());
+                        for pattern in patterns {
+                            let Some((enum_id, variants_left)) = &mut enum_variants_left else {
+                                let pattern_settings = GenerateExpressionSettings::parameter(match_allocation).expect_type(&value.value_type);
+                                pattern_idents.push(self.generate_expression(context, pattern, pattern_settings)?.value.ident);
+                                continue;
+                            };
+                            let enum_id = *enum_id;
+                            let (variant_id, bindings) = self.get_enum_pattern(context, enum_id, pattern)?;
+                            let Some(variant_position) = variants_left.iter().position(|variant| *variant == variant_id) else {
+                                return CodegenError::err(pattern.clone(), ErrorRepr::UnreachableMatchArm);
+                            };
+                            variants_left.remove(variant_position);
+                            if bindings.iter().any(Option::is_some) {
+                                if patterns.len() > 1 {
+                                    return CodegenError::err(pattern.clone(), ErrorRepr::UnexpectedEnumPatternBinding);
+                                }
+                                let payload_types = self.enum_payload_types(enum_id, variant_id)?;
+                                let bindings = bindings.into_iter().zip(payload_types).enumerate()
+                                    .filter_map(|(payload_index, (binding, payload_type))| Some((binding?, payload_type, payload_index + 2)))
+                                    .collect();
+                                // An empty block never starts, and its pointer could be shared
+                                if !arm_block.is_empty() {
+                                    arm_bindings.insert(Rc::as_ptr(&arm_block), (value.ident, bindings));
+                                }
+                            }
+                            pattern_idents.push(self.buffer.use_number(ParameterValue::Float(variant_id as f64)));
+                        }
+                        arms.push((pattern_idents, arm_block));
+                    }
+
+                    if let (Some((enum_id, variants_left)), None) = (&enum_variants_left, &wildcard_block) {
+                        if !variants_left.is_empty() {
+                            let enum_context = self.context_borrow(*enum_id)?;
+                            let missing = variants_left.iter().map(|variant| enum_context.variants[*variant].name.clone()).collect::<Vec<String>>();
+                            return CodegenError::err(statement.clone(), ErrorRepr::NonExhaustiveMatch(missing.join(", ")));
+                        }
+                    }
+                    if arms.len() >= MATCH_DISPATCH_ARMS
+                        && matches!(value.value_type, ValueType::Primitive(PrimitiveType::String))
+                        && !match_arms.iter().any(Self::has_control_flow) {
+                        self.generate_match_dispatch(context, value.ident, arms, wildcard_block, match_allocation)?;
+                    } else if arms.is_empty() {
+                        if let Some(wildcard_block) = wildcard_block {
+                            body_stack.push_front((0, wildcard_block, Vec::new(), None, body_stack_mode));
+                        }
+                    } else {
+                        // Every arm is checked in the else branch of the one before it, so only one check ever has to run per arm.
+                        // The checks after the first are the trailing instructions of empty blocks placed between the arms.
+                        let arm_count = arms.len();
+                        let mut arm_entries = Vec::new();
+                        for (arm_index, (pattern_idents, arm_block)) in arms.into_iter().enumerate() {
+                            let mut check = instruction!(Varif::Eq, [ (Ident, match_ident) ]);
+                            for pattern_ident in pattern_idents {
+                                check.params.push(Parameter::from_ident(pattern_ident));
+                            }
+                            if arm_index == 0 {
+                                self.buffer.code_buffer.push_instruction(check);
+                            } else {
+                                arm_entries.push((0, Rc::new(Vec::new()), Vec::new(), Some(check), body_stack_mode.clone()));
+                            }
+                            let trail = if arm_index + 1 == arm_count && wildcard_block.is_none() {
+                                instruction!(EndIf)
+                            } else {
+                                instruction!(Else)
+                            };
+                            arm_entries.push((0, arm_block, Vec::new(), Some(trail), body_stack_mode.clone()));
+                        }
+                        if let Some(wildcard_block) = wildcard_block {
+                            arm_entries.push((0, wildcard_block, Vec::new(), Some(instruction!(EndIf)), body_stack_mode.clone()));
+                        }
+                        for _ in 1..arm_count {
+                            arm_entries.push((0, Rc::new(Vec::new()), Vec::new(), Some(instruction!(EndIf)), body_stack_mode.clone()));
+                        }
+                        for arm_entry in arm_entries.into_iter().rev() {
+                            body_stack.push_front(arm_entry);
+                        }
+                    }
+                    self.buffer.free_line_register_group(match_allocation);
+                }
+                NodeType::Break(label) | NodeType::Continue(label) => {
+                    let is_break = matches!(statement.node_type, NodeType::Break(..));
+                    if loop_stack.is_empty() {
+                        return CodegenError::err(statement.clone(), ErrorRepr::LoopControlOutsideLoop);
+                    }
+                    let innermost_loop = loop_stack.len() - 1;
+                    let target_loop = if matches!(label.node_type, NodeType::None) {
+                        innermost_loop
+                    } else {
+                        let label_string = Self::get_loop_label(label)?;
+                        loop_stack.iter()
+                            .rposition(|enclosing_loop| enclosing_loop.1.as_ref() == Some(label_string))
+                            .ok_or(CodegenError::new(label.clone(), ErrorRepr::UnknownLoopLabel))?
+                    };
+                    if target_loop == innermost_loop {
+                        self.buffer.code_buffer.push_instruction(match is_break {
+                            true => instruction!(Ctrl::StopRepeat),
+                            false => instruction!(Ctrl::Skip),
+                        });
+                    } else {
+                        // DF can only stop the innermost repeat, so leave a note for the loops on the way out.
+                        let target_label = loop_stack[target_loop].1.clone().expect("Targeted outer loops should be labeled.");
+                        let loop_variable = loop_stack[target_loop].2.expect("Labeled loops should have a loop variable.");
+                        self.buffer.code_buffer.push_instruction(instruction!(
+                            Var::Set, [ (Ident, loop_variable), (Int, if is_break { 1 } else { 2 }) ]
+                        ));
+                        self.buffer.code_buffer.push_instruction(instruction!(Ctrl::StopRepeat));
+                        let exits = &mut loop_stack[innermost_loop].3;
+                        if !exits.iter().any(|exit| exit.0 == target_label) {
+                            exits.push((target_label, loop_variable));
+                        }
+                    }
+                }
+                NodeType::Const(..) => {
+                    return CodegenError::err(statement.clone(), ErrorRepr::UnexpectedConst);
+                }
                 _ => {
-                    println!("Unrecognized Statement: {:#?}", statement.clone());
+                    return CodegenError::err(statement.clone(), ErrorRepr::UnexpectedStatement);
                 }
             }
             body_stack[0].2.extend(self.block_runtime_vars_add.clone());
             self.block_runtime_vars_add.clear();
         }
-        if !returned_value && return_type_ident.is_some() { // This means the function needs to a return a value, but hasn't in the core branch.
-            return CodegenError::err_headless(ErrorRepr::ExpectedFunctionReturnValue);
-        }
-        if let ContextType::Function(FunctionType::Event(EventStructType::Struct(event_struct_id, autobuilt_struct))) = context_type {
-            let send_id = if autobuilt_struct {
-                let reg = self.buffer.allocate_line_register();
-                self.buffer.code_buffer.push_instruction(instruction!(
-                    Var::CreateList, [(Ident, reg)]
-                ));
-                for add_param_autobuild in param_and_var_idents {
-                    self.buffer.code_buffer.push_parameter(Parameter::from_ident(add_param_autobuild.1))
-                }
-                self.buffer.free_line_register(reg)?;
-                reg
-            } else {
-                param_and_var_idents.get(0).expect("If event has no autobuilt struct, it should have 1 and only param (the struct itself)").1
-            };
-            if let Some(listeners) = self.context_listeners.get(&context) {
-                for listener in listeners {
-                    let listener_name = self.get_context_full_name(*listener).clone();
-                    let listener_id = self.buffer.use_function(listener_name.as_str());
-                    self.buffer.code_buffer.push_instruction(instruction!(
-                        Call, [
-                            (Ident, listener_id), (Ident, send_id)
-                        ]
-                    ))
-                }
-            }
-        }
-        Ok(())
+        Ok(returned_value)
     }
 
     pub fn codegen_from_node(&mut self, node: Rc<Node>) -> Result<(), CodegenError> {
+        self.outline_from_node(node)?;
+        //##println!("\n\n\n\n{:#?}\n\n\n\n", self.context_names);
+        self.evaluate_all_constants()?;
+        self.generate_all_code()?;
+        Ok(())
+    }
+
+    /// Scans the contexts out of `node` and resolves their field types, without generating any code.
+    pub fn outline_from_node(&mut self, node: Rc<Node>) -> Result<(), CodegenError> {
         self.buffer.clear();
         let _root_context = self.scan_block_outline(node, ContextType::Domain, 0, 0, CodeScope::Public, Vec::new(), "main".to_owned())?;
+        if let Some(e) = self.resolve_all_imports().into_iter().next() {
+            return Err(e);
+        }
         self.fill_all_field_types()?;
         self.root_context = 0;
-        //##println!("\n\n\n\n{:#?}\n\n\n\n", self.context_names);
-        self.generate_all_code()?;
+        Ok(())
+    }
+
+    /// Same as [Self::codegen_from_node], but for every module of a project, with each error pointing at the file it's in.
+    pub fn codegen_from_modules(&mut self, modules: &ModuleGraph) -> Result<(), Vec<ModuleError>> {
+        self.outline_from_modules(modules)?;
+        self.evaluate_all_constants()
+            .and_then(|_| self.generate_all_code())
+            .map_err(|e| vec![modules.error(e)])
+    }
+
+    /// Same as [Self::outline_from_node], but for every module of a project, reporting every import that couldn't be found.
+    pub fn outline_from_modules(&mut self, modules: &ModuleGraph) -> Result<(), Vec<ModuleError>> {
+        self.buffer.clear();
+        self.scan_block_outline(modules.merged(), ContextType::Domain, 0, 0, CodeScope::Public, Vec::new(), "main".to_owned())
+            .map_err(|e| vec![modules.error(e)])?;
+        let import_errors = self.resolve_all_imports();
+        if !import_errors.is_empty() {
+            return Err(import_errors.into_iter().map(|e| modules.error(e)).collect());
+        }
+        self.fill_all_field_types().map_err(|e| vec![modules.error(e)])?;
+        self.root_context = 0;
         Ok(())
     }
 }
@@ -2027,10 +4437,260 @@ mod tests {
     use core::str;
     use std::fs;
 
+    use dfbin::Constants::Actions;
     use esh_parser::parser::*;
     use lexer::{Lexer, types::Token};
     use super::*;
 
+    fn generate(source: &str) -> Result<CodeGen, CodegenError> {
+        let tokens: Vec<Rc<Token>> = Lexer::new(source).map(|token| Rc::new(token.expect("Lexer token should unwrap"))).collect();
+        let node = Rc::new(Parser::new(&tokens).parse().expect("Parser statement block should unwrap"));
+        let mut codegen = CodeGen::new();
+        codegen.codegen_from_node(node)?;
+        Ok(codegen)
+    }
+
+    fn generate_error(source: &str) -> ErrorRepr {
+        generate(source).err().expect("Codegen should fail").source
+    }
+
+    #[test]
+    pub fn match_dispatch_control_flow_test() {
+        let arms = "\"a\" => {} \"b\" => {} \"c\" => {} \"d\" => {} \"e\" => {} \"f\" => {} \"g\" => {}";
+        let dispatched = generate(&format!("func f(string s, num? o) {{ match s {{ {} \"h\" => {{ num a = 1; }} }} }}", arms))
+            .expect("Codegen should generate");
+        assert_eq!(dispatched.match_count, 1);
+
+        // A return inside an if let has to keep the match in the function it returns from
+        let chained = generate(&format!("func f(string s, num? o) {{ match s {{ {} \"h\" => {{ if let v = o {{ return; }} }} }} }}", arms))
+            .expect("Codegen should generate");
+        assert_eq!(chained.match_count, 0);
+    }
+
+    #[test]
+    pub fn map_type_inference_test() {
+        for map_type in ["map<K, V>", "V[K]"] {
+            let get = format!("func get<K, V>({} m, K key) -> V {{ return m[key]; }}", map_type);
+            generate(&format!("{}\nfunc main() {{ map<num, string> names = {{ 1: \"a\" }}; string name = get(names, 1); }}", get))
+                .expect("Codegen should generate");
+            let error = generate_error(&format!("{}\nfunc main() {{ map<num, string> names = {{ 1: \"a\" }}; num name = get(names, 1); }}", get));
+            assert_eq!(error, ErrorRepr::ExpectedExplicitCast);
+        }
+    }
+
+    #[test]
+    pub fn struct_field_default_test() {
+        let source = "struct P { num x = 5; num y; }\nfunc main() { P p = P { y = 2; }; }";
+        let mut codegen = generate(source).expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        let instance = code.iter().find(|instruction| instruction.action == Actions::Var::CreateList).expect("Instance should be created");
+        assert_eq!(instance.params[1].value, ParameterValue::Ident(codegen.buffer.use_number(ParameterValue::Float(5.0))));
+        assert_eq!(instance.params[2].value, ParameterValue::Ident(codegen.buffer.use_number(ParameterValue::Float(2.0))));
+        // Only fields with an initializer can be left out
+        assert_eq!(generate_error("struct P { num x = 5; num y; }\nfunc main() { P p = P { x = 2; }; }"), ErrorRepr::ConstructFieldsMissing);
+    }
+
+    #[test]
+    pub fn struct_constructor_test() {
+        let source = "struct Board {\n    string[] players;\n    num currentPlayer = 3;\n    num round;\n    func self(string[] players) {\n        self.players = players;\n    }\n}\nfunc main() {\n    string[] players = [\"a\"];\n    Board board = Board(players);\n}";
+        let mut codegen = generate(source).expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        let call = code.iter().rposition(|instruction| instruction.action == Actions::Call).expect("Constructor should be called");
+        let instance = code[..call].iter().rposition(|instruction| instruction.action == Actions::Var::CreateList).expect("Instance should be created");
+        assert_eq!(code[instance].params[2].value, ParameterValue::Ident(codegen.buffer.use_number(ParameterValue::Float(3.0))));
+        // A field without an initializer starts out as the default value of its type
+        let round_default = code[..instance].iter().rfind(|instruction| instruction.action == Actions::Var::Set && instruction.params[0].value == code[instance].params[3].value);
+        assert_eq!(round_default.expect("Field should get a default").params[1].value, ParameterValue::Int(0));
+        // The freshly built instance is what the constructor gets as `self`
+        assert_eq!(code[call].params[1].value, code[instance].params[0].value);
+    }
+
+    #[test]
+    pub fn struct_constructor_error_test() {
+        assert_eq!(generate_error("domain d { func self() { } }"), ErrorRepr::ConstructorOutsideStruct);
+        assert_eq!(generate_error("struct P { func self() { } func self() { } }"), ErrorRepr::ConstructorAlreadyDefined);
+        assert_eq!(generate_error("struct P { func self() -> num { return 1; } }"), ErrorRepr::UnexpectedConstructorReturnType);
+        assert_eq!(generate_error("struct P { num x; }\nfunc main() { P p = P(); }"), ErrorRepr::ExpectedConstructor);
+    }
+
+    #[test]
+    pub fn vector_arithmetic_type_test() {
+        let source = "func main() {\n    loc a = <1, 2, 3, 0, 0>;\n    loc b = <4, 5, 6, 0, 0>;\n    vec between = a - b;\n    vec scaled = 2 * between;\n    vec scaled_back = scaled * 2;\n    vec negated = -scaled;\n}";
+        let codegen = generate(source).expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        let locations: Vec<_> = code.iter().filter(|instruction| instruction.action == Actions::Var::SetAllCoords).collect();
+        let between = code.iter().find(|instruction| instruction.action == Actions::Var::VectorBetween).expect("Vector between should be generated");
+        // `a - b` is the vector that goes from b to a
+        assert_eq!(between.params[1].value, locations[1].params[0].value);
+        assert_eq!(between.params[2].value, locations[0].params[0].value);
+
+        // Shifting a location by a vector stays a location
+        assert_eq!(generate_error("func f(loc l, vec v) { vec c = l - v; }"), ErrorRepr::ExpectedExplicitCast);
+        assert_eq!(generate_error("func f(num n, vec v) { num c = n * v; }"), ErrorRepr::CantImplicitlyCast);
+    }
+
+    #[test]
+    pub fn cast_find_test() {
+        let number = ValueType::Primitive(PrimitiveType::Number);
+        let string = ValueType::Primitive(PrimitiveType::String);
+        let list = |element: &ValueType| ValueType::Primitive(PrimitiveType::List(Rc::new(element.clone())));
+        let optional = |inside: &ValueType| ValueType::Primitive(PrimitiveType::Optional(Rc::new(inside.clone())));
+
+        assert_eq!(CodegenCast::find(&number, &number), Some(CodegenCast::Retype));
+        assert_eq!(CodegenCast::find(&ValueType::Primitive(PrimitiveType::Bool), &number), Some(CodegenCast::Retype));
+        assert_eq!(CodegenCast::find(&number, &string), Some(CodegenCast::NumberToString));
+        assert_eq!(CodegenCast::find(&string, &number), Some(CodegenCast::StringToNumber));
+        assert_eq!(CodegenCast::find(&number, &ValueType::Primitive(PrimitiveType::Item)), None);
+        assert_eq!(CodegenCast::find(&number, &optional(&number)), Some(CodegenCast::Retype));
+        assert_eq!(CodegenCast::find(&number, &optional(&string)), None);
+        assert_eq!(CodegenCast::find(&optional(&number), &number), None);
+        assert_eq!(CodegenCast::find(&list(&number), &list(&number)), Some(CodegenCast::Retype));
+        assert_eq!(CodegenCast::find(&list(&number), &list(&string)), Some(CodegenCast::List(Rc::new(CodegenCast::NumberToString))));
+        assert_eq!(CodegenCast::find(&list(&number), &list(&ValueType::Primitive(PrimitiveType::Item))), None);
+
+        assert!(CodegenCast::Retype.is_implicit());
+        assert!(CodegenCast::NumberToString.is_implicit());
+        assert!(CodegenCast::VectorToLocation.is_implicit());
+        assert!(!CodegenCast::StringToNumber.is_implicit());
+        assert!(!CodegenCast::LocationToVector.is_implicit());
+        assert!(CodegenCast::List(Rc::new(CodegenCast::NumberToString)).is_implicit());
+        assert!(!CodegenCast::List(Rc::new(CodegenCast::StringToNumber)).is_implicit());
+    }
+
+    #[test]
+    pub fn cast_test() {
+        assert_eq!(generate_error("func f(string s) { num n = s; }"), ErrorRepr::ExpectedExplicitCast);
+        assert_eq!(generate_error("func f(num n) { item i = n as item; }"), ErrorRepr::CantCast);
+        assert_eq!(generate_error("func f(string[] s) { num[] n = s; }"), ErrorRepr::ExpectedExplicitCast);
+
+        // Every element of the list is cast inside a loop
+        let codegen = generate("func f(string[] s) { num[] n = s as num[]; }").expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        let actions: Vec<_> = code.iter().map(|instruction| &instruction.action).skip_while(|action| **action != Actions::Var::CreateList).collect();
+        assert_eq!(actions[..4], [&Actions::Var::CreateList, &Actions::Rep::ForEach, &Actions::Var::ParseNumber, &Actions::Var::AppendValue]);
+    }
+
+    #[test]
+    pub fn enum_payload_chunk_test() {
+        let fields: Vec<String> = (0..25).map(|field| format!("num f{}", field)).collect();
+        let values: Vec<String> = (0..25).map(|value| value.to_string()).collect();
+        let source = format!("enum E {{ A, B({}) }}\nfunc main() {{ E e = E.B({}); }}", fields.join(", "), values.join(", "));
+        let mut codegen = generate(&source).expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        let create = code.iter().position(|instruction| instruction.action == Actions::Var::CreateList).expect("Enum value should be created");
+        // The return ident and the variant id leave room for 24 payload values
+        assert_eq!(code[create].params.len(), 26);
+        assert_eq!(code[create].params[1].value, ParameterValue::Int(1));
+        assert_eq!(code[create + 1].action, Actions::Var::AppendList);
+        assert_eq!(code[create + 1].params.len(), 2);
+        assert_eq!(code[create + 1].params[1].value, ParameterValue::Ident(codegen.buffer.use_number(ParameterValue::Float(24.0))));
+    }
+
+    #[test]
+    pub fn compound_assignment_target_test() {
+        let source = "func index() -> num { return 1; }\nfunc main() { num[] list = [1, 2]; list[index()] += 1; }";
+        let codegen = generate(source).expect("Codegen should generate");
+        let code = codegen.buffer.code_buffer.instructions();
+        // The target is only generated once, for both reading and writing it
+        assert_eq!(code.iter().filter(|instruction| instruction.action == Actions::Call).count(), 1);
+        let read = code.iter().find(|instruction| instruction.action == Actions::Var::GetListValue).expect("Element should be read");
+        let write = code.iter().find(|instruction| instruction.action == Actions::Var::SetListValue).expect("Element should be written");
+        assert_eq!(read.params[2].value, write.params[1].value);
+    }
+
+    #[test]
+    pub fn const_function_runtime_argument_test() {
+        let sources = [
+            "num x = 1; num y = double(x);",
+            "num x = 1; num y = double(x + 1);",
+            "num[] l = [1]; num y = double(l[0]);",
+            "P p = P { x = 1; }; num y = double(p.x);",
+            "num y = double(runtime());",
+            "string s = \"1\"; num y = double(s as num);",
+        ];
+        for source in sources {
+            let source = format!("struct P {{ num x; func get() -> num {{ return double(x) + double(self.x); }} }}\nconst func double(num n) -> num {{ return n * 2; }}\nfunc runtime() -> num {{ return 1; }}\nfunc main() {{ {} }}", source);
+            let codegen = generate(&source).expect("Codegen should generate");
+            assert_eq!(codegen.runtime_const_functions.len(), 1, "{}", source);
+        }
+        // Anything else that's wrong with a constant argument is still an error
+        let out_of_bounds = "const num[] l = [1];\nconst func double(num n) -> num { return n * 2; }\nfunc main() { num y = double(l[5]); }";
+        assert_eq!(generate_error(out_of_bounds), ErrorRepr::InvalidListAccess);
+    }
+
+    #[test]
+    pub fn const_arithmetic_test() {
+        let codegen = generate(concat!(
+            "const vec v = 2 * <1, 2, 3>;\n",
+            "const loc origin = <1, 2, 3, 10, 20>;\n",
+            "const loc shifted = origin + <1, 1, 1>;\n",
+            "const loc back = origin - <1, 1, 1>;\n",
+            "const loc target = <4, 4, 4, 0, 0>;\n",
+            "const vec between = target - origin;\n",
+            "const string s = \"ab\" * 3;\n",
+        )).expect("Codegen should generate");
+        let values: Vec<_> = codegen.context_borrow(0).expect("Context should exist").constants.iter().map(|constant| constant.value.clone()).collect();
+        assert_eq!(values, vec![
+            Some(CodegenConstant::Vector(2.0, 4.0, 6.0)),
+            Some(CodegenConstant::Location(1.0, 2.0, 3.0, 10.0, 20.0)),
+            Some(CodegenConstant::Location(2.0, 3.0, 4.0, 10.0, 20.0)),
+            Some(CodegenConstant::Location(0.0, 1.0, 2.0, 10.0, 20.0)),
+            Some(CodegenConstant::Location(4.0, 4.0, 4.0, 0.0, 0.0)),
+            Some(CodegenConstant::Vector(3.0, 2.0, 1.0)),
+            Some(CodegenConstant::String("ababab".to_string())),
+        ]);
+
+        // Templates only take strings and numbers, at compile time just like at runtime
+        assert_eq!(generate_error("const string s = `{true}`;"), ErrorRepr::InvalidExpressionTypeConversion);
+        assert_eq!(generate_error("func f(bool b) { string s = `{b}`; }"), ErrorRepr::InvalidExpressionTypeConversion);
+    }
+
+    #[test]
+    pub fn event_dispatched_match_test() {
+        let mut codegen = generate("domain game { event scored(string name); }\nevent game.scored { }").expect("Codegen should generate");
+        let event = (0..codegen.contexts.len())
+            .find(|context| matches!(codegen.context_borrow(*context).expect("Context should exist").context_type, ContextType::Function(FunctionType::Event(..))))
+            .expect("Event should be scanned");
+        // Event bodies are always empty in the source, so a body with a dispatched match is generated for it directly
+        let arms = "\"a\" => {} \"b\" => {} \"c\" => {} \"d\" => {} \"e\" => {} \"f\" => {} \"g\" => {} \"h\" => { num a = 1; }";
+        let tokens: Vec<Rc<Token>> = Lexer::new(&format!("match name {{ {} }}", arms)).map(|token| Rc::new(token.expect("Lexer token should unwrap"))).collect();
+        let NodeType::Block(body) = Parser::new(&tokens).parse().expect("Parser statement block should unwrap").node_type else {
+            panic!("Parser should return a block");
+        };
+        codegen.buffer.code_buffer.clear();
+        let fields = codegen.context_borrow(event).expect("Event should exist").fields.clone();
+        codegen.generate_function_code(event, Rc::new(body), fields, ValueType::Primitive(PrimitiveType::None)).expect("Event should generate");
+        assert_eq!(codegen.match_count, 1);
+
+        let code = codegen.buffer.code_buffer.instructions();
+        let listener_call = code.iter().rposition(|instruction| instruction.action == Actions::Call).expect("Listener should be called");
+        let arm_function = code.iter().skip(1).position(|instruction| instruction.action == Actions::Func).expect("Arms should be split out") + 1;
+        assert!(listener_call < arm_function);
+    }
+
+    #[test]
+    pub fn unexpected_statement_test() {
+        assert_eq!(generate_error("func f(num a, num b) { a + b; }"), ErrorRepr::UnexpectedStatement);
+        assert_eq!(generate_error("func f(num a) { while a > 0 { a; } }"), ErrorRepr::UnexpectedStatement);
+        assert!(generate("func f(num a) { 'outer: while a > 0 { break 'outer; } }").is_ok());
+
+        // The parser only puts labels on loops, so the labeled `if` is put together by hand
+        let tokens: Vec<Rc<Token>> = Lexer::new("func f(bool a) { if (a) { } }").map(|token| Rc::new(token.expect("Lexer token should unwrap"))).collect();
+        let node = Parser::new(&tokens).parse().expect("Parser statement block should unwrap");
+        let NodeType::Block(statements) = &node.node_type else { panic!("Expected a block") };
+        let NodeType::Func(name, parameters, return_type, body, doc) = &statements[0].node_type else { panic!("Expected a function") };
+        let NodeType::Block(body_statements) = &body.node_type else { panic!("Expected a block") };
+        let if_node = body_statements[0].clone();
+        let label = Token { token_type: TokenType::Label("outer".to_string()), range: if_node.range.clone() };
+        let label = Rc::new(Node::new(NodeType::Primary(Rc::new(label)), if_node.range.clone()));
+        let labeled = Rc::new(Node::new(NodeType::Labeled(label, if_node.clone()), if_node.range.clone()));
+        let body = Rc::new(Node::new(NodeType::Block(vec![labeled]), body.range.clone()));
+        let func = Rc::new(Node::new(NodeType::Func(name.clone(), parameters.clone(), return_type.clone(), body, doc.clone()), statements[0].range.clone()));
+        let node = Rc::new(Node::new(NodeType::Block(vec![func]), node.range.clone()));
+        let error = CodeGen::new().codegen_from_node(node).expect_err("Codegen should fail");
+        assert_eq!(error.source, ErrorRepr::UnexpectedLoopLabel);
+    }
+
     #[test]
     pub fn decompile_from_file_test() {
         let name = "hello";
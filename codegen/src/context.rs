@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 use std::rc::Rc;
-use crate::types::{Field, ValueType};
+use crate::types::{Constant, EnumVariant, Field, ValueType};
 use crate::Node;
 
 #[derive(Clone, Debug)]
 pub enum CodeDefinition {
     Context(usize),
     Field(usize),
+    Constant(usize),
     Multiple(Vec<CodeDefinition>),
 }
 
@@ -15,6 +16,7 @@ pub enum ContextType {
     Struct,
     Function(FunctionType),
     Domain,
+    Enum,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -43,14 +45,29 @@ pub struct Context {
     pub id: usize,
     pub depth: u32,
     pub fields: Vec<Field>,
+    pub constants: Vec<Constant>,
     pub definition_lookup: HashMap<String, CodeDefinition>,
     pub body: Rc<Vec<Rc<Node>>>,
     pub scope: CodeScope,
     pub children: Vec<usize>,
+    /// The block node this context was scanned from
+    pub node: Rc<Node>,
+    /// The `///` doc comment written above the declaration of this context
+    pub doc: Option<String>,
+    /// Whether this is a `const func`, which is run at compile time when it's called with constants
+    pub comptime: bool,
+    /// The names of the type parameters of a generic struct or func, which only gets code generated for its instances
+    pub type_parameters: Vec<String>,
+    /// The generic struct or func this is an instance of, along with the type given for each of its type parameters
+    pub instance_of: Option<(usize, Vec<ValueType>)>,
+    /// The `func self` of a struct, called on a new instance when the struct is called like a function
+    pub constructor: Option<usize>,
+    /// The variants of an enum, numbered by their position for the tag its values are stored with
+    pub variants: Vec<EnumVariant>,
 }
 
 impl Context {
-    pub fn new_empty(context_type: ContextType, parent_id: usize, id: usize, depth: u32, body: Rc<Vec<Rc<Node>>>, scope: CodeScope) -> Context {
+    pub fn new_empty(context_type: ContextType, parent_id: usize, id: usize, depth: u32, body: Rc<Vec<Rc<Node>>>, scope: CodeScope, node: Rc<Node>) -> Context {
         Self {
             context_type,
             parent_id,
@@ -58,9 +75,17 @@ impl Context {
             id,
             depth,
             fields: Vec::new(),
+            constants: Vec::new(),
             definition_lookup: HashMap::new(),
             body,
             scope,
+            node,
+            doc: None,
+            comptime: false,
+            type_parameters: Vec::new(),
+            instance_of: None,
+            constructor: None,
+            variants: Vec::new(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use esh_parser::parser::{Node, NodeType};
+use lexer::types::TokenType;
+
+use crate::codegen::CodeGen;
+use crate::context::{CodeDefinition, Context, ContextType, EventStructType, FunctionType};
+use crate::errors::{CodegenError, ErrorRepr};
+use crate::types::{ComptimeType, PrimitiveType, RealtimeValueType, ValueType};
+
+/// The format `esh doc` writes its API reference in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DocItemKind {
+    Domain,
+    Struct,
+    Enum,
+    Function,
+    Event,
+    Field,
+}
+
+impl DocItemKind {
+    fn label(&self) -> &'static str {
+        match self {
+            DocItemKind::Domain => "domain",
+            DocItemKind::Struct => "struct",
+            DocItemKind::Enum => "enum",
+            DocItemKind::Function => "func",
+            DocItemKind::Event => "event",
+            DocItemKind::Field => "field",
+        }
+    }
+}
+
+/// A documented declaration, read back from the contexts of a [CodeGen] once its outline has been scanned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocItem {
+    pub kind: DocItemKind,
+    pub name: String,
+    pub signature: String,
+    pub doc: Option<String>,
+    pub fields: Vec<DocItem>,
+    pub children: Vec<DocItem>,
+}
+
+impl DocItem {
+    /// Builds the documentation tree starting at the root context.
+    ///
+    /// The field types must already be filled in, see [CodeGen::outline_from_node].
+    pub fn from_codegen(codegen: &CodeGen) -> Result<DocItem, CodegenError> {
+        let root = Self::from_context(codegen, codegen.root_context, "main")?;
+        Ok(root.expect("The root context should be a domain."))
+    }
+
+    fn from_context(codegen: &CodeGen, context_id: usize, name: &str) -> Result<Option<DocItem>, CodegenError> {
+        let context = Self::borrow(codegen, context_id)?;
+        let (field_names, context_names) = Self::definition_names(&context);
+        let parameters = || -> Result<String, CodegenError> {
+            let mut parameters = Vec::new();
+            for (field_id, field) in context.fields.iter().enumerate() {
+                let type_name = Self::type_name(codegen, &field.field_type)?;
+                parameters.push(match field_names.get(&field_id) {
+                    Some(field_name) => format!("{} {}", type_name, field_name),
+                    None => type_name,
+                });
+            }
+            Ok(parameters.join(", "))
+        };
+        let type_parameters = match context.type_parameters.is_empty() {
+            true => String::new(),
+            false => format!("<{}>", context.type_parameters.join(", ")),
+        };
+        let (kind, signature) = match &context.context_type {
+            ContextType::Domain => (DocItemKind::Domain, format!("domain {}", name)),
+            ContextType::Struct => (DocItemKind::Struct, format!("struct {}{}", name, type_parameters)),
+            ContextType::Enum => {
+                let mut variants = Vec::new();
+                for variant in &context.variants {
+                    if variant.fields.is_empty() {
+                        variants.push(variant.name.clone());
+                        continue;
+                    }
+                    let mut payload = Vec::new();
+                    for field_id in &variant.fields {
+                        payload.push(format!("{} {}", Self::type_name(codegen, &context.fields[*field_id].field_type)?, codegen.get_field_name(context_id, *field_id)));
+                    }
+                    variants.push(format!("{}({})", variant.name, payload.join(", ")));
+                }
+                (DocItemKind::Enum, format!("enum {} {{ {} }}", name, variants.join(", ")))
+            },
+            ContextType::Function(FunctionType::Func(return_type)) => {
+                let mut signature = format!("{}func {}{}({})", if context.comptime { "const " } else { "" }, name, type_parameters, parameters()?);
+                let no_return_type = match return_type {
+                    ValueType::Ident(return_type_node) => matches!(return_type_node.node_type, NodeType::None),
+                    _ => matches!(return_type, ValueType::Primitive(PrimitiveType::None)),
+                };
+                if !no_return_type {
+                    let _ = write!(signature, " -> {}", Self::type_name(codegen, return_type)?);
+                }
+                (DocItemKind::Function, signature)
+            },
+            ContextType::Function(FunctionType::Event(..)) => (DocItemKind::Event, format!("event {}({})", name, parameters()?)),
+            ContextType::Function(FunctionType::EventListener(..)) => return Ok(None),
+        };
+
+        let mut fields = Vec::new();
+        if matches!(context.context_type, ContextType::Domain | ContextType::Struct) {
+            for (field_id, field) in context.fields.iter().enumerate() {
+                let Some(field_name) = field_names.get(&field_id) else {
+                    continue;
+                };
+                if codegen.is_definition_private(context_id, &CodeDefinition::Field(field_id))? {
+                    continue;
+                }
+                fields.push(DocItem {
+                    kind: DocItemKind::Field,
+                    name: field_name.to_string(),
+                    signature: format!("{} {}", Self::type_name(codegen, &field.field_type)?, field_name),
+                    doc: None,
+                    fields: Vec::new(),
+                    children: Vec::new(),
+                });
+            }
+        }
+
+        // Events declared with named parameters get a struct built for them, which is already shown on the event.
+        let mut autobuilt_structs = Vec::new();
+        for child_id in &context.children {
+            if let ContextType::Function(FunctionType::Event(EventStructType::Struct(struct_id, true))) = Self::borrow(codegen, *child_id)?.context_type {
+                autobuilt_structs.push(struct_id);
+            }
+        }
+        let mut children = Vec::new();
+        for child_id in &context.children {
+            // Private definitions can't be used from outside, so they aren't part of the reference.
+            if autobuilt_structs.contains(child_id) || codegen.is_definition_private(context_id, &CodeDefinition::Context(*child_id))? {
+                continue;
+            }
+            let child_name = match context_names.get(child_id) {
+                Some(child_name) => child_name.as_str(),
+                None if context.constructor == Some(*child_id) => "self",
+                None => continue,
+            };
+            if let Some(child) = Self::from_context(codegen, *child_id, child_name)? {
+                children.push(child);
+            }
+        }
+
+        Ok(Some(DocItem {
+            kind,
+            name: name.to_owned(),
+            signature,
+            doc: context.doc.clone(),
+            fields,
+            children,
+        }))
+    }
+
+    fn borrow(codegen: &CodeGen, context_id: usize) -> Result<std::cell::Ref<'_, Context>, CodegenError> {
+        CodegenError::map_headless(codegen.contexts[context_id].try_borrow(), ErrorRepr::BadBorrow)
+    }
+
+    /// Maps the field and child context ids of a context back to the names they were defined with.
+    fn definition_names(context: &Context) -> (HashMap<usize, &String>, HashMap<usize, &String>) {
+        let mut field_names = HashMap::new();
+        let mut context_names = HashMap::new();
+        for (name, definition) in context.definition_lookup.iter() {
+            let definitions = match definition {
+                CodeDefinition::Multiple(definitions) => definitions.iter().collect(),
+                definition => vec![definition],
+            };
+            for definition in definitions {
+                match definition {
+                    CodeDefinition::Field(field_id) => { field_names.insert(*field_id, name); },
+                    CodeDefinition::Context(context_id) => { context_names.insert(*context_id, name); },
+                    CodeDefinition::Constant(..) | CodeDefinition::Multiple(..) => {},
+                }
+            }
+        }
+        (field_names, context_names)
+    }
+
+    /// Finds the name a context was declared with in its parent, or the name it was scanned with if it wasn't declared, like a generic instance.
+    fn context_name(codegen: &CodeGen, context_id: usize) -> Result<String, CodegenError> {
+        let parent_id = Self::borrow(codegen, context_id)?.parent_id;
+        let parent = Self::borrow(codegen, parent_id)?;
+        let (_, context_names) = Self::definition_names(&parent);
+        Ok(context_names.get(&context_id).map(|name| name.to_string()).unwrap_or_else(|| codegen.get_context_name(context_id).clone()))
+    }
+
+    /// Writes a type the way it would be written in Esh source.
+    pub(crate) fn type_name(codegen: &CodeGen, value_type: &ValueType) -> Result<String, CodegenError> {
+        Ok(match value_type {
+            ValueType::Primitive(primitive) => match primitive {
+                PrimitiveType::None => "none".to_owned(),
+                PrimitiveType::Number => "num".to_owned(),
+                PrimitiveType::String => "string".to_owned(),
+                PrimitiveType::Bool => "bool".to_owned(),
+                PrimitiveType::Vector => "vec".to_owned(),
+                PrimitiveType::Location => "loc".to_owned(),
+                PrimitiveType::Sound => "sound".to_owned(),
+                PrimitiveType::Particle => "particle".to_owned(),
+                PrimitiveType::Potion => "potion".to_owned(),
+                PrimitiveType::Item => "item".to_owned(),
+                PrimitiveType::List(inside_type) => format!("{}[]", Self::type_name(codegen, inside_type)?),
+                PrimitiveType::Map(mapped_type, mapping_type) => format!("map<{}, {}>", Self::type_name(codegen, mapping_type)?, Self::type_name(codegen, mapped_type)?),
+                PrimitiveType::Optional(inside_type) => format!("{}?", Self::type_name(codegen, inside_type)?),
+            },
+            ValueType::Struct(context_id) | ValueType::Enum(context_id) => Self::context_name(codegen, *context_id)?,
+            ValueType::Comptime(ComptimeType::Type(RealtimeValueType::Struct(context_id) | RealtimeValueType::Enum(context_id))) => Self::context_name(codegen, *context_id)?,
+            ValueType::Comptime(ComptimeType::Type(RealtimeValueType::Primitive(primitive))) => Self::type_name(codegen, &ValueType::Primitive(primitive.clone()))?,
+            ValueType::Comptime(ComptimeType::Domain(context_id) | ComptimeType::Function(context_id) | ComptimeType::SelfFunction(context_id, _)) => Self::context_name(codegen, *context_id)?,
+            ValueType::Comptime(ComptimeType::MapFunction(map_function)) => map_function.name().to_owned(),
+            ValueType::Comptime(ComptimeType::EnumVariant(enum_id, variant_id)) => format!("{}.{}", Self::context_name(codegen, *enum_id)?, Self::borrow(codegen, *enum_id)?.variants[*variant_id].name),
+            ValueType::Ident(type_node) => Self::type_node_name(type_node),
+        })
+    }
+
+    /// Writes back a type that hasn't been worked out, which is the case inside generic structs and funcs.
+    fn type_node_name(type_node: &Node) -> String {
+        match &type_node.node_type {
+            NodeType::Primary(token) => match &token.token_type {
+                TokenType::Ident(ident) => ident.clone(),
+                _ => "?".to_owned(),
+            },
+            NodeType::Access(parent, child) => format!("{}.{}", Self::type_node_name(parent), Self::type_node_name(child)),
+            NodeType::ListCall(inside, index) if matches!(index.node_type, NodeType::None) => format!("{}[]", Self::type_node_name(inside)),
+            NodeType::ListCall(mapped, mapping) => format!("{}[{}]", Self::type_node_name(mapped), Self::type_node_name(mapping)),
+            NodeType::Generic(ident, type_arguments) => {
+                let type_arguments: Vec<String> = type_arguments.iter().map(|type_argument| Self::type_node_name(type_argument)).collect();
+                format!("{}<{}>", Self::type_node_name(ident), type_arguments.join(", "))
+            },
+            NodeType::Optional(inside) => format!("{}?", Self::type_node_name(inside)),
+            NodeType::None => "none".to_owned(),
+            _ => "?".to_owned(),
+        }
+    }
+
+    pub fn render(&self, format: DocFormat) -> String {
+        let mut output = String::new();
+        match format {
+            DocFormat::Markdown => self.write_markdown(&mut output, 1),
+            DocFormat::Html => {
+                output.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+                let _ = writeln!(output, "<title>{}</title>", escape_html(&self.name));
+                output.push_str("</head>\n<body>\n");
+                self.write_html(&mut output, 1);
+                output.push_str("</body>\n</html>\n");
+            },
+        }
+        output
+    }
+
+    fn write_markdown(&self, output: &mut String, level: usize) {
+        let _ = writeln!(output, "{} {} `{}`\n", "#".repeat(level.min(6)), self.kind.label(), self.name);
+        let _ = writeln!(output, "```esh\n{}\n```\n", self.signature);
+        if let Some(doc) = &self.doc {
+            let _ = writeln!(output, "{}\n", doc);
+        }
+        if !self.fields.is_empty() {
+            for field in &self.fields {
+                let _ = writeln!(output, "- `{}`", field.signature);
+            }
+            output.push('\n');
+        }
+        for child in &self.children {
+            child.write_markdown(output, level + 1);
+        }
+    }
+
+    fn write_html(&self, output: &mut String, level: usize) {
+        let level = level.min(6);
+        output.push_str("<section>\n");
+        let _ = writeln!(output, "<h{}>{} <code>{}</code></h{}>", level, self.kind.label(), escape_html(&self.name), level);
+        let _ = writeln!(output, "<pre><code>{}</code></pre>", escape_html(&self.signature));
+        if let Some(doc) = &self.doc {
+            for paragraph in doc.split("\n\n") {
+                let _ = writeln!(output, "<p>{}</p>", escape_html(paragraph));
+            }
+        }
+        if !self.fields.is_empty() {
+            output.push_str("<ul>\n");
+            for field in &self.fields {
+                let _ = writeln!(output, "<li><code>{}</code></li>", escape_html(&field.signature));
+            }
+            output.push_str("</ul>\n");
+        }
+        for child in &self.children {
+            child.write_html(output, level + 1);
+        }
+        output.push_str("</section>\n");
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use esh_parser::parser::Parser;
+    use lexer::{Lexer, types::Token};
+    use super::*;
+
+    #[test]
+    pub fn markdown_test() {
+        let source = "/// Keeps score.\ndomain game {\n    num rounds;\n    /// Someone scored.\n    event scored(string name, num points);\n    /// Adds two numbers.\n    func add(num a, num b) -> num {\n        return a + b;\n    }\n    private num secret;\n    /// Only used inside.\n    private func helper() {\n    }\n}";
+        let tokens: Vec<Rc<Token>> = Lexer::new(source).map(|token| Rc::new(token.expect("Lexer token should unwrap"))).collect();
+        let node = Rc::new(Parser::new(&tokens).parse().expect("Parser statement block should unwrap"));
+        let mut codegen = CodeGen::new();
+        codegen.outline_from_node(node).expect("Outline should scan");
+
+        let docs = DocItem::from_codegen(&codegen).expect("Docs should build");
+        assert_eq!(
+            docs.render(DocFormat::Markdown),
+            "# domain `main`\n\n```esh\ndomain main\n```\n\n\
+            ## domain `game`\n\n```esh\ndomain game\n```\n\nKeeps score.\n\n- `num rounds`\n\n\
+            ### event `scored`\n\n```esh\nevent scored(string name, num points)\n```\n\nSomeone scored.\n\n\
+            ### func `add`\n\n```esh\nfunc add(num a, num b) -> num\n```\n\nAdds two numbers.\n\n"
+        );
+    }
+}
@@ -1,34 +1,31 @@
 use std::{fmt, rc::Rc};
 
 use esh_parser::parser::Node;
+use lexer::types::Range;
 
 #[derive(thiserror::Error, Debug, PartialEq)]
-#[error("Compiler error.")]
+#[error("Compiler error{}: {source}", .token.as_ref().map(|token| format!(" at {}", token)).unwrap_or_default())]
 pub struct CodegenError {
-    token: Option<ErrorToken>,
+    pub token: Option<ErrorToken>,
     pub source: ErrorRepr,
 }
 
+/// The node an error was raised on, together with the exact source [Range] it points at
 #[derive(Debug, PartialEq)]
 pub struct ErrorToken {
     pub token: Rc<Node>,
-    pub position: usize
+    pub range: Range
 }
 impl fmt::Display for ErrorToken {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}; {:?})", self.position, self.token)
+        write!(f, "{}-{}", self.range.start, self.range.end)
     }
 }
 
 impl CodegenError {
     pub fn new(node: Rc<Node>, source: ErrorRepr) -> CodegenError {
-        Self {
-            token: Some(ErrorToken {
-                token: node,
-                position: 0
-            }),
-            source
-        }
+        let range = node.range.clone();
+        Self::new_range(node, range, source)
     }
     pub fn err<T>(node: Rc<Node>, source: ErrorRepr) -> Result<T, CodegenError> {
         Err(Self::new(node, source))
@@ -42,11 +39,12 @@ impl CodegenError {
     pub fn map_headless<T, U>(err: Result<T, U>, source: ErrorRepr) -> Result<T, CodegenError> {
         err.map_err(|_| Self::new_headless(source))
     }
-    pub fn new_position(node: Rc<Node>, position: usize, source: ErrorRepr) -> CodegenError {
+    /// Creates an error pointing at a specific range, which may be narrower than the range of the node itself.
+    pub fn new_range(node: Rc<Node>, range: Range, source: ErrorRepr) -> CodegenError {
         Self {
             token: Some(ErrorToken {
                 token: node,
-                position
+                range
             }),
             source
         }
@@ -57,6 +55,16 @@ impl CodegenError {
             source
         }
     }
+    /// Attaches a node to a headless error, leaving errors that already point somewhere untouched.
+    pub fn or_node(self, node: &Rc<Node>) -> CodegenError {
+        match self.token {
+            Some(_) => self,
+            None => Self::new(node.clone(), self.source)
+        }
+    }
+    pub fn range(&self) -> Option<&Range> {
+        self.token.as_ref().map(|token| &token.range)
+    }
 }
 
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -131,6 +139,8 @@ pub enum ErrorRepr {
     FunctionNestedInFunction,
     #[error("Structs cannot nest inside functions.")]
     StructNestedInFunction,
+    #[error("Enums cannot nest inside functions.")]
+    EnumNestedInFunction,
     #[error("Domains cannot nest inside functions.")]
     DomainNestedInFunction,
     #[error("Expected a domain identifier string.")]
@@ -161,6 +171,14 @@ pub enum ErrorRepr {
     UnexpectedStructAccessIdent,
     #[error("Incomplete struct construct, field(s) are missing.")]
     ConstructFieldsMissing,
+    #[error("This struct has no constructor, construct it with `Type {{ ... }}` instead.")]
+    ExpectedConstructor,
+    #[error("Constructors can only be declared inside structs.")]
+    ConstructorOutsideStruct,
+    #[error("This struct already has a constructor.")]
+    ConstructorAlreadyDefined,
+    #[error("Constructors can't have a return type, they return the new instance.")]
+    UnexpectedConstructorReturnType,
     #[error("Expected a field assignment.")]
     ExpectedFieldAssignment,
     #[error("Invalid struct field.")]
@@ -187,6 +205,28 @@ pub enum ErrorRepr {
     InvalidStructDefinition,
     #[error("Can't implicitly cast this expression to the expected type.")]
     CantImplicitlyCast,
+    #[error("This expression can only be cast to the expected type explicitly, with `as`.")]
+    ExpectedExplicitCast,
+    #[error("Can't cast this expression to this type.")]
+    CantCast,
+    #[error("Can't infer the type of this variable from its value, give it a type instead of `let`.")]
+    CantInferType,
+    #[error("Expected an expression that can be worked out at compile time (literals, arithmetic, and other constants).")]
+    ExpectedConstantExpression,
+    #[error("This constant depends on its own value.")]
+    RecursiveConstant,
+    #[error("Can't assign to a constant.")]
+    AssignToConstant,
+    #[error("Constants can only be declared at the top level of a domain, struct or function.")]
+    UnexpectedConst,
+    #[error("This can't be used as a statement here, only declarations, assignments, calls and control flow can.")]
+    UnexpectedStatement,
+    #[error("This can't be run at compile time, a `const func` can only work with constants and call other `const func`s.")]
+    NonConstOperation,
+    #[error("This `const func` ran for too long at compile time.")]
+    ConstStepLimitReached,
+    #[error("This `const func` calls itself too deeply at compile time.")]
+    ConstCallDepthReached,
     #[error("Expected a list type.")]
     ExpectedListType,
     #[error("Invalid location coordinate access.")]
@@ -205,4 +245,76 @@ pub enum ErrorRepr {
     UnexpectedEventParameter,
     #[error("Event keyword used in non-event-listener code.")]
     EventInNonListenerCode,
+    #[error("Visibility can only be given to declarations inside structs and domains.")]
+    UnexpectedVisibility,
+    #[error("This definition is private to the struct or domain it was declared in.")]
+    PrivateDefinitionAccess,
+    #[error("Break and continue can only be used inside loops.")]
+    LoopControlOutsideLoop,
+    #[error("Expected a loop label.")]
+    ExpectedLoopLabel,
+    #[error("No loop around this statement has this label.")]
+    UnknownLoopLabel,
+    #[error("Only `while` and `for` loops can be labeled.")]
+    UnexpectedLoopLabel,
+    #[error("Expected a match arm.")]
+    ExpectedMatchArm,
+    #[error("This match arm can never be reached, it comes after the `_` arm.")]
+    UnreachableMatchArm,
+    #[error("Imports and package declarations can only be at the top level of a file or domain.")]
+    UnexpectedImport,
+    #[error("Couldn't find anything with this name to import.")]
+    UnresolvedImport,
+    #[error("Only functions, structs, enums, domains and events can be imported, not variables or constants.")]
+    UnimportableDefinition,
+    #[error("These packages import each other in a cycle: {0}.")]
+    ImportCycle(String),
+    #[error("This file is in package `{0}`, but declares a different package.")]
+    PackageMismatch(String),
+    #[error("Expected a type parameter name.")]
+    ExpectedTypeParameterIdentifier,
+    #[error("This type parameter is already declared.")]
+    TypeParameterAlreadyDefined,
+    #[error("This is generic, expected more type arguments.")]
+    ExpectedTypeArguments,
+    #[error("Unexpected type argument, this has fewer type parameters.")]
+    UnexpectedTypeArguments,
+    #[error("Can't infer the type arguments of this generic function from its parameters, give them like `func<num>(...)`.")]
+    CantInferTypeArguments,
+    #[error("Generic types are nested inside their own instances too deeply.")]
+    GenericDepthReached,
+    #[error("`none` needs an optional type to be, like `num? x = none;`.")]
+    CantInferNone,
+    #[error("Optional values have to be unwrapped with `if let` or `??` before they can be used.")]
+    UnwrappedOptional,
+    #[error("Expected an optional type.")]
+    ExpectedOptionalType,
+    #[error("Expected an enum identifier string.")]
+    ExpectedEnumIdentifier,
+    #[error("Expected an enum variant identifier.")]
+    ExpectedEnumVariantIdentifier,
+    #[error("This enum already has a variant with this name.")]
+    EnumVariantAlreadyDefined,
+    #[error("This enum has no variant with this name.")]
+    UnknownEnumVariant,
+    #[error("This variant has a payload, give it like `Variant(...)`.")]
+    ExpectedEnumPayload,
+    #[error("This variant has no payload.")]
+    UnexpectedEnumPayload,
+    #[error("Expected a variant of the enum being matched, like `State.Playing(round)`.")]
+    InvalidEnumPattern,
+    #[error("Variants with payloads can't be bound in a match arm with more than one pattern.")]
+    UnexpectedEnumPatternBinding,
+    #[error("This match doesn't cover every variant of the enum, add the missing ones or a `_` arm: {0}.")]
+    NonExhaustiveMatch(String),
+    #[error("Enum values with payloads can only be compared to variants without one, use `match` instead.")]
+    EnumPayloadComparison,
+    #[error("This enum has no default value, since its first variant has a payload.")]
+    ExpectedEnumDefault,
+    #[error("Invalid map access.")]
+    InvalidMapAccess,
+    #[error("Expected a map type.")]
+    ExpectedMapType,
+    #[error("Only maps with string keys can list their keys, since every key is stored as a string.")]
+    ExpectedStringMapKeys,
 }
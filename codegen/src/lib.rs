@@ -2,9 +2,11 @@ pub mod codegen;
 pub mod errors;
 pub mod types;
 pub mod context;
+pub mod docs;
 pub use esh_parser;
 pub mod buffer;
 pub mod constants;
+pub mod modules;
 pub use esh_parser::parser::Parser as Parser;
 pub use esh_parser::parser::Node as Node;
 pub use lexer::compiler::Compiler as Compiler;
\ No newline at end of file
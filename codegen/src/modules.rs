@@ -0,0 +1,388 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{fs, io, rc::Rc};
+
+use esh_parser::parser::{Node, NodeType, Parser, ParserError};
+use lexer::errors::LexerError;
+use lexer::types::{Range, Token, TokenType};
+use lexer::Lexer;
+
+use crate::errors::{CodegenError, ErrorRepr};
+
+/// A single parsed `.esh` file of a project
+pub struct Module {
+    /// The package the file is in, empty for the file compiling started from
+    pub package: Vec<String>,
+    pub file: PathBuf,
+    pub source: String,
+    pub node: Rc<Node>,
+}
+
+/// Every module of a project, starting with the file compiling started from, followed by the files of each package it imports
+pub struct ModuleGraph {
+    pub modules: Vec<Module>,
+}
+
+/// A problem found in one of the files of a project
+#[derive(Debug)]
+pub struct ModuleError {
+    pub file: PathBuf,
+    pub source: String,
+    pub kind: ModuleErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ModuleErrorKind {
+    Read(io::Error),
+    Lexer(Vec<LexerError>),
+    Parser(Vec<ParserError>),
+    Codegen(CodegenError),
+}
+
+/// Finds and loads the packages imported by a project.
+///
+/// A package is a folder, so `from game.ui import Menu;` loads every `.esh` file in `game/ui/`, looking in the project root first and then in each search path.
+/// Imports that don't name a folder are left for codegen to resolve, since they can point at domains declared in the project itself.
+pub struct ModuleResolver {
+    pub root: PathBuf,
+    pub search_paths: Vec<PathBuf>,
+}
+
+impl ModuleResolver {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            search_paths: Vec::new(),
+        }
+    }
+
+    pub fn with_search_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.search_paths.push(path.into());
+        self
+    }
+
+    /// Loads the file at `entry`, along with every package it imports, directly or through other packages.
+    pub fn resolve(&self, entry: &Path) -> Result<ModuleGraph, ModuleError> {
+        let mut graph = ModuleGraph {
+            modules: vec![Self::load(entry, Vec::new())?],
+        };
+        self.load_imports(&mut graph, 0..1, &mut Vec::new(), &mut HashSet::new())?;
+        Ok(graph)
+    }
+
+    /// Loads the packages imported by `modules`, depth first, so a package imported while it's still being loaded is an import cycle.
+    fn load_imports(&self, graph: &mut ModuleGraph, modules: std::ops::Range<usize>, loading: &mut Vec<Vec<String>>, loaded: &mut HashSet<Vec<String>>) -> Result<(), ModuleError> {
+        for module_id in modules {
+            let mut imports = Vec::new();
+            Self::find_imports(&graph.modules[module_id].node, &mut imports);
+            for (import_node, path) in imports {
+                let Some((package, files)) = self.find_package(&path) else {
+                    continue;
+                };
+                // Names from the module's own package are loaded along with it, that's not a cycle
+                if package == graph.modules[module_id].package {
+                    continue;
+                }
+                if let Some(position) = loading.iter().position(|loading_package| *loading_package == package) {
+                    let mut cycle: Vec<String> = loading[position..].iter().map(|loading_package| loading_package.join(".")).collect();
+                    cycle.push(package.join("."));
+                    return Err(graph.modules[module_id].error(CodegenError::new(import_node, ErrorRepr::ImportCycle(cycle.join(" -> ")))));
+                }
+                if loaded.contains(&package) {
+                    continue;
+                }
+                let first_file = graph.modules.len();
+                for file in files {
+                    graph.modules.push(Self::load(&file, package.clone())?);
+                }
+                loading.push(package.clone());
+                self.load_imports(graph, first_file..graph.modules.len(), loading, loaded)?;
+                loading.pop();
+                loaded.insert(package);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the folder of the longest package at the start of `path`, giving back the package and its `.esh` files.
+    ///
+    /// A folder without any `.esh` files isn't a package, so the search carries on past it.
+    fn find_package(&self, path: &[String]) -> Option<(Vec<String>, Vec<PathBuf>)> {
+        for length in (1..=path.len()).rev() {
+            let package = &path[..length];
+            for directory in std::iter::once(&self.root).chain(self.search_paths.iter()) {
+                let folder = package.iter().fold(directory.clone(), |folder, name| folder.join(name));
+                let Ok(entries) = fs::read_dir(&folder) else {
+                    continue;
+                };
+                let mut files: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|file| file.is_file() && file.extension().is_some_and(|extension| extension == "esh"))
+                    .collect();
+                if files.is_empty() {
+                    continue;
+                }
+                files.sort();
+                return Some((package.to_vec(), files));
+            }
+        }
+        None
+    }
+
+    /// Collects the imports at the top level of the file and its domains, along with the full path of each imported name.
+    fn find_imports(node: &Rc<Node>, imports: &mut Vec<(Rc<Node>, Vec<String>)>) {
+        match &node.node_type {
+            NodeType::Block(statements) => {
+                for statement in statements {
+                    Self::find_imports(statement, imports);
+                }
+            },
+            NodeType::Domain(_, body, _) => Self::find_imports(body, imports),
+            NodeType::Import(path, _) => imports.push((node.clone(), path_names(path))),
+            NodeType::FromImport(path, names) => {
+                for name_node in names {
+                    let NodeType::Import(name, _) = &name_node.node_type else {
+                        continue;
+                    };
+                    let mut names = path_names(path);
+                    names.extend(path_names(name));
+                    imports.push((name_node.clone(), names));
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Reads, lexes and parses a file, checking that any `package` it declares is the one it was found in.
+    fn load(file: &Path, package: Vec<String>) -> Result<Module, ModuleError> {
+        let source = fs::read_to_string(file).map_err(|e| ModuleError {
+            file: file.to_path_buf(),
+            source: String::new(),
+            kind: ModuleErrorKind::Read(e),
+        })?;
+        let module_error = |kind: ModuleErrorKind| ModuleError {
+            file: file.to_path_buf(),
+            source: source.clone(),
+            kind,
+        };
+
+        let mut tokens = Vec::new();
+        let mut lexer_errors = Vec::new();
+        for token in Lexer::new(&source) {
+            match token {
+                Ok(token) => tokens.push(Rc::new(token)),
+                Err(e) => lexer_errors.push(e),
+            }
+        }
+        if !lexer_errors.is_empty() {
+            return Err(module_error(ModuleErrorKind::Lexer(lexer_errors)));
+        }
+        let mut parser = Parser::new(tokens.as_slice());
+        let (node, parser_errors) = parser.parse_recovering();
+        if !parser_errors.is_empty() {
+            return Err(module_error(ModuleErrorKind::Parser(parser_errors)));
+        }
+        let node = Rc::new(node);
+
+        if !package.is_empty() {
+            if let NodeType::Block(statements) = &node.node_type {
+                for statement in statements {
+                    if let NodeType::Package(declared) = &statement.node_type {
+                        if path_names(declared) != package {
+                            return Err(module_error(ModuleErrorKind::Codegen(CodegenError::new(statement.clone(), ErrorRepr::PackageMismatch(package.join("."))))));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(Module {
+            package,
+            file: file.to_path_buf(),
+            source,
+            node,
+        })
+    }
+}
+
+impl Module {
+    fn error(&self, error: CodegenError) -> ModuleError {
+        ModuleError {
+            file: self.file.clone(),
+            source: self.source.clone(),
+            kind: ModuleErrorKind::Codegen(error),
+        }
+    }
+}
+
+/// The statements of a package and the packages nested inside of it
+#[derive(Default)]
+struct PackageTree {
+    statements: Vec<Rc<Node>>,
+    packages: BTreeMap<String, PackageTree>,
+}
+
+impl PackageTree {
+    /// Turns the tree into statements, with each nested package as a domain named after it.
+    fn into_statements(self) -> Vec<Rc<Node>> {
+        let mut statements = self.statements;
+        for (name, package) in self.packages {
+            let range = Range::new((0, 0), (0, 0));
+            let ident = Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident(name),
+                range: range.clone(),
+            })), range.clone()));
+            let body = Rc::new(Node::new(NodeType::Block(package.into_statements()), range.clone()));
+            statements.push(Rc::new(Node::new(NodeType::Domain(ident, body, None), range)));
+        }
+        statements
+    }
+}
+
+impl ModuleGraph {
+    /// Puts every module into a single tree for codegen, with the statements of each package in domains named after its path.
+    pub fn merged(&self) -> Rc<Node> {
+        let mut tree = PackageTree::default();
+        for module in &self.modules {
+            let package = module.package.iter().fold(&mut tree, |tree, name| tree.packages.entry(name.clone()).or_default());
+            match &module.node.node_type {
+                NodeType::Block(statements) => package.statements.extend(statements.iter().cloned()),
+                _ => package.statements.push(module.node.clone()),
+            }
+        }
+        let range = self.modules.first().map(|module| module.node.range.clone()).unwrap_or(Range::new((0, 0), (0, 0)));
+        Rc::new(Node::new(NodeType::Block(tree.into_statements()), range))
+    }
+
+    /// Attributes a codegen error to the module its node came from, or to the first module if it can't be traced.
+    pub fn error(&self, error: CodegenError) -> ModuleError {
+        let module = error.token.as_ref()
+            .and_then(|token| self.modules.iter().find(|module| contains_node(&module.node, &token.token)))
+            .or(self.modules.first())
+            .expect("Module graph should have the module compiling started from.");
+        module.error(error)
+    }
+}
+
+/// The names in an `a.b.c` path
+fn path_names(path: &Rc<Node>) -> Vec<String> {
+    match &path.node_type {
+        NodeType::Primary(token) => match &token.token_type {
+            TokenType::Ident(name) => vec![name.clone()],
+            _ => Vec::new(),
+        },
+        NodeType::Access(parent, child) => {
+            let mut names = path_names(parent);
+            names.extend(path_names(child));
+            names
+        },
+        _ => Vec::new(),
+    }
+}
+
+fn contains_node(node: &Rc<Node>, target: &Rc<Node>) -> bool {
+    Rc::ptr_eq(node, target) || node.children().into_iter().any(|child| contains_node(child, target))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    /// A project folder in the temp folder, removed again once the test is done with it.
+    struct TempProject(PathBuf);
+
+    impl TempProject {
+        fn new(name: &str) -> Self {
+            let folder = env::temp_dir().join(format!("esh_modules_{}_{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&folder);
+            fs::create_dir_all(&folder).expect("Project folder should be created");
+            Self(folder)
+        }
+
+        fn file(&self, path: &str, source: &str) -> PathBuf {
+            let file = self.0.join(path);
+            fs::create_dir_all(file.parent().expect("File should be in a folder")).expect("Folder should be created");
+            fs::write(&file, source).expect("File should write");
+            file
+        }
+    }
+
+    impl Drop for TempProject {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn codegen_error(error: ModuleError) -> ErrorRepr {
+        match error.kind {
+            ModuleErrorKind::Codegen(error) => error.source,
+            kind => panic!("Expected a codegen error, got {:?}", kind),
+        }
+    }
+
+    #[test]
+    pub fn longest_package_test() {
+        let project = TempProject::new("longest_package");
+        let entry = project.file("main.esh", "import game.ui.Menu;\nfrom game import Score;");
+        project.file("game/score.esh", "package game;\nstruct Score { num points; }");
+        project.file("game/ui/menu.esh", "package game.ui;\nstruct Menu { }");
+
+        let graph = ModuleResolver::new(&project.0).resolve(&entry).expect("Project should resolve");
+        let packages: Vec<Vec<String>> = graph.modules.iter().map(|module| module.package.clone()).collect();
+        assert_eq!(packages, vec![vec![], vec!["game".to_owned(), "ui".to_owned()], vec!["game".to_owned()]]);
+    }
+
+    #[test]
+    pub fn search_path_test() {
+        let project = TempProject::new("search_path");
+        let entry = project.file("main/main.esh", "import lib.Thing;");
+        // Folders without any .esh files are skipped, and the search paths are looked in in order
+        project.file("main/lib/notes.txt", "");
+        project.file("first/lib/thing.esh", "struct Thing { }");
+        project.file("second/lib/thing.esh", "struct Thing { }");
+
+        let graph = ModuleResolver::new(project.0.join("main"))
+            .with_search_path(project.0.join("first"))
+            .with_search_path(project.0.join("second"))
+            .resolve(&entry)
+            .expect("Project should resolve");
+        assert_eq!(graph.modules.len(), 2);
+        assert_eq!(graph.modules[1].file, project.0.join("first/lib/thing.esh"));
+    }
+
+    #[test]
+    pub fn import_cycle_test() {
+        let project = TempProject::new("import_cycle");
+        let entry = project.file("main.esh", "import a.A;");
+        project.file("a/a.esh", "import b.B;\nstruct A { }");
+        project.file("b/b.esh", "import a.A;\nstruct B { }");
+
+        let error = ModuleResolver::new(&project.0).resolve(&entry).err().expect("Cycle should fail to resolve");
+        assert_eq!(error.file, project.0.join("b/b.esh"));
+        assert_eq!(codegen_error(error), ErrorRepr::ImportCycle("a -> b -> a".to_owned()));
+    }
+
+    #[test]
+    pub fn same_package_import_test() {
+        let project = TempProject::new("same_package_import");
+        let entry = project.file("main.esh", "from lib import A;");
+        project.file("lib/a.esh", "package lib;\nfrom lib import B;\nstruct A { B b; }");
+        project.file("lib/b.esh", "package lib;\nfrom lib import A;\nstruct B { num n; }");
+
+        let graph = ModuleResolver::new(&project.0).resolve(&entry).expect("Imports inside a package should resolve");
+        assert_eq!(graph.modules.len(), 3);
+        let mut codegen = crate::codegen::CodeGen::new();
+        assert!(codegen.codegen_from_modules(&graph).is_ok());
+    }
+
+    #[test]
+    pub fn package_mismatch_test() {
+        let project = TempProject::new("package_mismatch");
+        let entry = project.file("main.esh", "import lib.Thing;");
+        project.file("lib/thing.esh", "package other;\nstruct Thing { }");
+
+        let error = ModuleResolver::new(&project.0).resolve(&entry).err().expect("Mismatched package should fail to resolve");
+        assert_eq!(codegen_error(error), ErrorRepr::PackageMismatch("lib".to_owned()));
+    }
+}
@@ -6,6 +6,112 @@ use crate::context::CodeScope;
 pub struct Field {
     pub field_type: ValueType,
     pub scope: CodeScope,
+    /// The initializer of a struct field (`num x = 0;`), used when a construct leaves the field out
+    pub default_value: Option<Rc<Node>>,
+}
+
+/// A variant of an enum, the fields of the enum its payload is kept in are listed in order.
+#[derive(Clone, PartialEq, Debug)]
+pub struct EnumVariant {
+    pub name: String,
+    pub fields: Vec<usize>,
+}
+
+/// A `const` declaration, its value is worked out the first time it's used.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Constant {
+    pub constant_type: Rc<Node>,
+    pub value_node: Rc<Node>,
+    pub scope: CodeScope,
+    pub value: Option<CodegenConstant>,
+}
+
+/// A value worked out at compile time.
+#[derive(Clone, PartialEq, Debug)]
+pub enum CodegenConstant {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Vector(f64, f64, f64),
+    Location(f64, f64, f64, f64, f64),
+    List(ValueType, Vec<CodegenConstant>), // The element type, and the elements
+    Struct(usize, Vec<CodegenConstant>),   // The struct context, and the fields in order
+    FromName(PrimitiveType, String),       // An item, sound, particle or potion made from its name with `as`
+    Optional(ValueType, Option<Box<CodegenConstant>>), // The type inside the optional, and the value if it isn't `none`
+    Enum(usize, usize, Vec<CodegenConstant>), // The enum context, the variant, and its payload
+}
+
+impl CodegenConstant {
+    pub fn value_type(&self) -> ValueType {
+        ValueType::Primitive(match self {
+            Self::Number(..) => PrimitiveType::Number,
+            Self::String(..) => PrimitiveType::String,
+            Self::Bool(..) => PrimitiveType::Bool,
+            Self::Vector(..) => PrimitiveType::Vector,
+            Self::Location(..) => PrimitiveType::Location,
+            Self::List(element_type, ..) => PrimitiveType::List(Rc::new(element_type.clone())),
+            Self::Struct(struct_id, ..) => return ValueType::Struct(*struct_id),
+            Self::Enum(enum_id, ..) => return ValueType::Enum(*enum_id),
+            Self::FromName(primitive, ..) => primitive.clone(),
+            Self::Optional(inside_type, ..) => PrimitiveType::Optional(Rc::new(inside_type.clone())),
+        })
+    }
+
+    /// The compile-time version of the implicit casts, gives back nothing if the constant can't be converted.
+    pub fn convert(self, value_type: &ValueType) -> Option<Self> {
+        if self.value_type() == *value_type {
+            return Some(self);
+        }
+        let ValueType::Primitive(primitive) = value_type else {
+            return None;
+        };
+        match (self, primitive) {
+            (Self::Number(number), PrimitiveType::String) => Some(Self::String(number.to_string())),
+            (Self::Bool(bool), PrimitiveType::Number) => Some(Self::Number(if bool { 1.0 } else { 0.0 })),
+            (Self::Vector(x, y, z), PrimitiveType::Location) => Some(Self::Location(x, y, z, 0.0, 0.0)),
+            (Self::List(_, elements), PrimitiveType::List(element_type)) => {
+                let mut converted = Vec::new();
+                for element in elements {
+                    converted.push(element.convert(element_type)?);
+                }
+                Some(Self::List(element_type.as_ref().clone(), converted))
+            },
+            (Self::Optional(_, value), PrimitiveType::Optional(inside_type)) => match value {
+                Some(value) => Some(Self::Optional(inside_type.as_ref().clone(), Some(Box::new(value.convert(inside_type)?)))),
+                None => Some(Self::Optional(inside_type.as_ref().clone(), None)),
+            },
+            (value, PrimitiveType::Optional(inside_type)) => Some(Self::Optional(inside_type.as_ref().clone(), Some(Box::new(value.convert(inside_type)?)))),
+            _ => None,
+        }
+    }
+
+    /// The compile-time version of `as`, gives back nothing if the cast needs to happen at runtime.
+    pub fn cast(self, cast: &CodegenCast, value_type: &ValueType) -> Option<Self> {
+        match (cast, self) {
+            (CodegenCast::Retype, constant) => constant.convert(value_type),
+            (CodegenCast::NumberToString, Self::Number(number)) => Some(Self::String(number.to_string())),
+            (CodegenCast::BoolToString, Self::Bool(bool)) => Some(Self::String(bool.to_string())),
+            (CodegenCast::StringToNumber, Self::String(string)) => string.trim().parse().ok().map(Self::Number),
+            (CodegenCast::NumberToBool, Self::Number(number)) => Some(Self::Bool(number != 0.0)),
+            (CodegenCast::VectorToLocation, Self::Vector(x, y, z)) => Some(Self::Location(x, y, z, 0.0, 0.0)),
+            (CodegenCast::LocationToVector, Self::Location(x, y, z, ..)) => Some(Self::Vector(x, y, z)),
+            (CodegenCast::StringToItem, Self::String(string)) => Some(Self::FromName(PrimitiveType::Item, string)),
+            (CodegenCast::StringToSound, Self::String(string)) => Some(Self::FromName(PrimitiveType::Sound, string)),
+            (CodegenCast::StringToParticle, Self::String(string)) => Some(Self::FromName(PrimitiveType::Particle, string)),
+            (CodegenCast::StringToPotion, Self::String(string)) => Some(Self::FromName(PrimitiveType::Potion, string)),
+            (CodegenCast::List(element_cast), Self::List(_, elements)) => {
+                let ValueType::Primitive(PrimitiveType::List(element_type)) = value_type else {
+                    return None;
+                };
+                let mut cast_elements = Vec::new();
+                for element in elements {
+                    cast_elements.push(element.cast(element_cast, element_type)?);
+                }
+                Some(Self::List(element_type.as_ref().clone(), cast_elements))
+            },
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -49,6 +155,7 @@ pub enum ValueType {
     Primitive(PrimitiveType),
     Comptime(ComptimeType),
     Struct(usize),
+    Enum(usize),
     Ident(Rc<Node>),
 }
 
@@ -59,7 +166,8 @@ pub enum IdentifierCategory {
     Domain,
     Function,
     Type,
-    SelfFunction
+    SelfFunction,
+    Constant,
 }
 
 impl ValueType {
@@ -67,7 +175,7 @@ impl ValueType {
         match self {
             Self::Primitive(..) => true,
             Self::Comptime(..) => false,
-            Self::Struct(..) => true,
+            Self::Struct(..) | Self::Enum(..) => true,
             Self::Ident(..) => { panic!("An Ident valuetype is only for the scanning phase and should not be checked for realtime/comptime (code generation related checks)"); }
         }
     }
@@ -75,16 +183,33 @@ impl ValueType {
         match self {
             Self::Primitive(..) => false,
             Self::Comptime(..) => true,
-            Self::Struct(..) => false,
+            Self::Struct(..) | Self::Enum(..) => false,
             Self::Ident(..) => { panic!("An Ident valuetype is only for the scanning phase and should not be checked for realtime/comptime (code generation related checks)"); }
         }
     }
+    /// The opposite of [RealtimeValueType::normalize], for types that values can have at runtime.
+    pub fn realtime_type(&self) -> Option<RealtimeValueType> {
+        match self {
+            Self::Primitive(primitive) => Some(RealtimeValueType::Primitive(primitive.clone())),
+            Self::Struct(struct_id) => Some(RealtimeValueType::Struct(*struct_id)),
+            Self::Enum(enum_id) => Some(RealtimeValueType::Enum(*enum_id)),
+            Self::Comptime(..) | Self::Ident(..) => None,
+        }
+    }
+    /// The type inside an optional type, or the type itself if it isn't optional.
+    pub fn unwrap_optional(&self) -> &ValueType {
+        match self {
+            Self::Primitive(PrimitiveType::Optional(inside_type)) => inside_type.as_ref(),
+            _ => self,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum RealtimeValueType {
     Primitive(PrimitiveType),
     Struct(usize),
+    Enum(usize),
 }
 
 impl RealtimeValueType {
@@ -92,18 +217,46 @@ impl RealtimeValueType {
         match self.clone() {
             RealtimeValueType::Primitive(primitive) => ValueType::Primitive(primitive),
             RealtimeValueType::Struct(struct_id) => ValueType::Struct(struct_id),
+            RealtimeValueType::Enum(enum_id) => ValueType::Enum(enum_id),
         }
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum PrimitiveType {
-    None, Number, String, Bool, List(Rc<ValueType>), Map(Rc<ValueType>, Rc<ValueType>), Vector, Location, Sound, Particle, Potion, Item // Realtime Types
+    None, Number, String, Bool, List(Rc<ValueType>), Map(Rc<ValueType>, Rc<ValueType>), Optional(Rc<ValueType>), Vector, Location, Sound, Particle, Potion, Item // Realtime Types
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ComptimeType {
-    Domain(usize), Function(usize), Type(RealtimeValueType), SelfFunction(usize, u32) // Comptime Types
+    Domain(usize), Function(usize), Type(RealtimeValueType), SelfFunction(usize, u32), MapFunction(MapFunction),
+    EnumVariant(usize, usize) // Comptime Types (an enum variant that still needs its payload, like `State.Playing`)
+}
+
+/// The built-in functions of maps, called like `stats.keys()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MapFunction {
+    Keys, Values, Has, Remove
+}
+
+impl MapFunction {
+    pub fn from_name(name: &str) -> Option<MapFunction> {
+        match name {
+            "keys" => Some(MapFunction::Keys),
+            "values" => Some(MapFunction::Values),
+            "has" => Some(MapFunction::Has),
+            "remove" => Some(MapFunction::Remove),
+            _ => None
+        }
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapFunction::Keys => "keys",
+            MapFunction::Values => "values",
+            MapFunction::Has => "has",
+            MapFunction::Remove => "remove",
+        }
+    }
 }
 
 
@@ -131,6 +284,81 @@ pub enum CodegenExpressionType {
     Access
 }
 
+/// How a value of one type turns into another, see [CodegenCast::find] for the types that can be converted.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CodegenCast {
+    Retype, // Nothing to convert, the value just gets looked at as another type
+    NumberToString,
+    BoolToString,
+    StringToNumber,
+    NumberToBool,
+    VectorToLocation,
+    LocationToVector,
+    StringToItem,
+    StringToSound,
+    StringToParticle,
+    StringToPotion,
+    List(Rc<CodegenCast>), // Casts every element of the list
+}
+
+impl CodegenCast {
+    pub fn find(from: &ValueType, to: &ValueType) -> Option<Self> {
+        if from == to {
+            return Some(Self::Retype);
+        }
+        // Any value is already an optional of its own type, `none` is only ever a separate value
+        if let ValueType::Primitive(PrimitiveType::Optional(to_inside)) = to {
+            let from_inside = match from {
+                ValueType::Primitive(PrimitiveType::Optional(from_inside)) => from_inside.as_ref(),
+                _ => from,
+            };
+            return match Self::find(from_inside, to_inside)? {
+                Self::Retype => Some(Self::Retype),
+                _ => None,
+            };
+        }
+        let (ValueType::Primitive(from), ValueType::Primitive(to)) = (from, to) else {
+            return None;
+        };
+        Some(match (from, to) {
+            (PrimitiveType::Bool, PrimitiveType::Number) => Self::Retype, // Bools are already stored as 0 or 1
+            (PrimitiveType::Number, PrimitiveType::String) => Self::NumberToString,
+            (PrimitiveType::Bool, PrimitiveType::String) => Self::BoolToString,
+            (PrimitiveType::String, PrimitiveType::Number) => Self::StringToNumber,
+            (PrimitiveType::Number, PrimitiveType::Bool) => Self::NumberToBool,
+            (PrimitiveType::Vector, PrimitiveType::Location) => Self::VectorToLocation,
+            (PrimitiveType::Location, PrimitiveType::Vector) => Self::LocationToVector,
+            (PrimitiveType::String, PrimitiveType::Item) => Self::StringToItem,
+            (PrimitiveType::String, PrimitiveType::Sound) => Self::StringToSound,
+            (PrimitiveType::String, PrimitiveType::Particle) => Self::StringToParticle,
+            (PrimitiveType::String, PrimitiveType::Potion) => Self::StringToPotion,
+            (PrimitiveType::List(from_element), PrimitiveType::List(to_element)) => match Self::find(from_element, to_element)? {
+                Self::Retype => Self::Retype,
+                element_cast => Self::List(Rc::new(element_cast)),
+            },
+            _ => return None,
+        })
+    }
+
+    /// Whether the cast can happen without an `as`, which is only when no information is lost and nothing can fail.
+    pub fn is_implicit(&self) -> bool {
+        match self {
+            Self::Retype | Self::NumberToString | Self::VectorToLocation => true,
+            Self::List(element_cast) => element_cast.is_implicit(),
+            _ => false,
+        }
+    }
+}
+
+/// How a statement run at compile time by a `const func` finished.
+#[derive(Debug, Clone)]
+pub enum CodegenComptimeFlow {
+    Normal,
+    Break,
+    Continue,
+    Return(Option<CodegenConstant>),
+}
+
 #[derive(Debug, Clone)]
 pub enum CodegenBodyStackMode {
     None,
@@ -257,6 +485,11 @@ pub struct GenerateExpressionSettings {
     pub expected_type: Option<ValueType>,
 
     pub preferred_category: IdentifierCategory,
+
+    /// If this is true, list indices and map keys in the trace are the identifiers they were generated into,
+    /// instead of nodes that get generated again when the trace is set.
+    /// The trace then has to be set before the register group the expression was generated in is freed.
+    pub keep_trace_indices: bool,
 }
 
 impl GenerateExpressionSettings {
@@ -269,6 +502,7 @@ impl GenerateExpressionSettings {
             expected_type: None,
             preferred_category: IdentifierCategory::RuntimeVariable,
             depth: 0,
+            keep_trace_indices: false,
         }
     }
     pub fn group(group: u64) -> Self {
@@ -280,6 +514,7 @@ impl GenerateExpressionSettings {
             expected_type: None,
             preferred_category: IdentifierCategory::RuntimeVariable,
             depth: 0,
+            keep_trace_indices: false,
         }
     }
     pub fn comptime() -> Self {
@@ -291,6 +526,7 @@ impl GenerateExpressionSettings {
             expected_type: None,
             preferred_category: IdentifierCategory::RuntimeVariable,
             depth: 0,
+            keep_trace_indices: false,
         }
     }
 
@@ -307,6 +543,7 @@ impl GenerateExpressionSettings {
             expected_type: None,
             preferred_category: IdentifierCategory::RuntimeVariable,
             depth: 0,
+            keep_trace_indices: false,
         }
     }
 
@@ -322,6 +559,7 @@ impl GenerateExpressionSettings {
             expected_type: None,
             preferred_category: IdentifierCategory::RuntimeVariable,
             depth: 0,
+            keep_trace_indices: false,
         }
     }
 
@@ -357,6 +595,13 @@ impl GenerateExpressionSettings {
         changed
     }
 
+    /// Makes the trace keep the generated list indices and map keys, see `keep_trace_indices`.
+    pub fn keep_trace_indices(&self) -> Self {
+        let mut changed = self.clone();
+        changed.keep_trace_indices = true;
+        changed
+    }
+
     /// Makes the expression prefer a comptime category type (such as a domain, function, etc.)
     pub fn prefer_category(&self, category: IdentifierCategory) -> Self {
         let mut changed = self.clone();
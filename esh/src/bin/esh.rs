@@ -1,6 +1,7 @@
 use codegen::codegen::CodeGen;
+use codegen::docs::{DocFormat, DocItem};
+use codegen::modules::{ModuleError, ModuleErrorKind, ModuleGraph, ModuleResolver};
 use codegen::Compiler;
-use codegen::Parser;
 use detemplater::Detemplater;
 use dfbin::DFBin;
 use optimizer::optimizer::Optimizer;
@@ -9,17 +10,17 @@ use templater::Templater;
 use core::str;
 use std::env;
 use std::fs;
+use std::process;
 use std::io::BufRead;
-use std::rc::Rc;
 use websocket::ClientBuilder;
 use websocket::Message;
 use std::net::TcpStream;
 use websocket::sync::Client;
 use std::{thread, time};
-use lexer::Lexer;
+use esh::diagnostics::Diagnostic;
 
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn main() {
     let matches = Command::new("esh")
@@ -54,6 +55,26 @@ fn main() {
                     .short('o')
                     .help("Optimizes the templates using the best optimizer settings.")
                     .action(ArgAction::SetTrue))
+                .arg(package_path_arg())
+        )
+        .subcommand(
+            Command::new("doc")
+                .about("Generates API documentation for an esh file from its doc comments")
+                .arg(Arg::new("input")
+                    .help("Path to input .esh file")
+                    .required(true)
+                    .value_parser(clap::value_parser!(PathBuf)))
+                .arg(Arg::new("output")
+                    .help("Optional output .md or .html path, prints to stdout if not given")
+                    .required(false)
+                    .value_parser(clap::value_parser!(PathBuf)))
+                .arg(Arg::new("format")
+                    .short('f')
+                    .help("Output format, markdown or html")
+                    .required(false)
+                    .default_value("markdown")
+                    .value_parser(["markdown", "html"]))
+                .arg(package_path_arg())
         )
         .subcommand(
             Command::new("assemble")
@@ -157,6 +178,7 @@ fn main() {
 
     match matches.subcommand() {
         Some(("compile", sub_m)) => handle_compile(sub_m),
+        Some(("doc", sub_m)) => handle_doc(sub_m),
         Some(("assemble", sub_m)) => handle_assemble(sub_m),
         Some(("template", sub_m)) => handle_template(sub_m),
         Some(("disassemble", sub_m)) => handle_disassemble(sub_m),
@@ -177,17 +199,12 @@ fn handle_compile(matches: &ArgMatches) {
     let optimize = matches.get_flag("optimize");
 
     let time_save = SystemTime::now();
-    let file_bytes = fs::read(input).expect("File should read");
-    let lexer = Lexer::new(str::from_utf8(&file_bytes).expect("Should encode to utf-8"));
-    let lexer_tokens: Vec<Rc<lexer::types::Token>> = lexer.map(|v| Rc::new(v.expect("Lexer token should unwrap"))).collect();
-    
-    // println!("LEXER TOKENS\n----------------------\n{:#?}\n----------------------", lexer_tokens);
-    let mut parser = Parser::new(lexer_tokens.as_slice());
-    let parser_tree = Rc::new(parser.parse().expect("Parser statement block should unwrap"));
-    //##println!("PARSER TREE\n----------------------\n{:#?}\n----------------------", parser_tree);
+    let modules = resolve_modules(input, matches);
     
     let mut codegen = CodeGen::new();
-    codegen.codegen_from_node(parser_tree.clone()).expect("Codegen should generate");
+    if let Err(errors) = codegen.codegen_from_modules(&modules) {
+        exit_with_module_errors(input, &errors);
+    }
 
 
     let mut code = codegen.buffer.flush();
@@ -227,6 +244,76 @@ fn handle_compile(matches: &ArgMatches) {
 
 }
 
+/// The `-p/--path` argument of the subcommands that load packages, read by [resolve_modules].
+fn package_path_arg() -> Arg {
+    Arg::new("path")
+        .short('p')
+        .long("path")
+        .help("Another folder to look for imported packages in, after the one the input file is in. Can be given more than once.")
+        .action(ArgAction::Append)
+        .value_parser(clap::value_parser!(PathBuf))
+}
+
+/// Loads the input file along with every package it imports, exiting the process with diagnostics if any of them can't be loaded.
+///
+/// Packages are looked for in the folder of the input file first, then in each `--path` folder in order.
+fn resolve_modules(input: &Path, matches: &ArgMatches) -> ModuleGraph {
+    let root = input.parent().unwrap_or(Path::new(""));
+    let mut resolver = ModuleResolver::new(root);
+    for path in matches.get_many::<PathBuf>("path").into_iter().flatten() {
+        resolver = resolver.with_search_path(path);
+    }
+    match resolver.resolve(input) {
+        Ok(modules) => modules,
+        Err(e) => exit_with_module_errors(input, &[e]),
+    }
+}
+
+fn handle_doc(matches: &ArgMatches) {
+    let input = matches.get_one::<PathBuf>("input").unwrap();
+    let output = matches.get_one::<PathBuf>("output");
+    let format = match matches.get_one::<String>("format").map(String::as_str) {
+        Some("html") => DocFormat::Html,
+        _ => DocFormat::Markdown,
+    };
+
+    let modules = resolve_modules(input, matches);
+
+    let mut codegen = CodeGen::new();
+    if let Err(errors) = codegen.outline_from_modules(&modules) {
+        exit_with_module_errors(input, &errors);
+    }
+    let docs = match DocItem::from_codegen(&codegen) {
+        Ok(docs) => docs.render(format),
+        Err(e) => exit_with_module_errors(input, &[modules.error(e)]),
+    };
+
+    match output {
+        Some(output_path) => fs::write(output_path, docs).expect("Docs should write."),
+        None => print!("{}", docs),
+    }
+}
+
+/// Prints the diagnostics of every error to stderr, each against the file it was found in, and exits the process with a nonzero code.
+fn exit_with_module_errors(input: &Path, errors: &[ModuleError]) -> ! {
+    let mut error_count = 0;
+    for error in errors {
+        let file_name = error.file.display().to_string();
+        let diagnostics = match &error.kind {
+            ModuleErrorKind::Read(e) => vec![Diagnostic::new(format!("couldn't read {}: {}", file_name, e), None)],
+            ModuleErrorKind::Lexer(errors) => errors.iter().map(Diagnostic::from).collect(),
+            ModuleErrorKind::Parser(errors) => errors.iter().map(Diagnostic::from).collect(),
+            ModuleErrorKind::Codegen(e) => vec![Diagnostic::from(e)],
+        };
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic.render(&file_name, &error.source));
+        }
+        error_count += diagnostics.len();
+    }
+    eprintln!("error: could not compile {} due to {} previous error{}", input.display(), error_count, if error_count == 1 { "" } else { "s" });
+    process::exit(1);
+}
+
 fn handle_assemble(matches: &ArgMatches) {
     let input = matches.get_one::<PathBuf>("input").unwrap();
     let output = matches.get_one::<PathBuf>("output");
@@ -0,0 +1,123 @@
+use std::fmt::Write;
+
+use codegen::errors::CodegenError;
+use esh_parser::parser::ParserError;
+use lexer::errors::LexerError;
+use lexer::types::Range;
+
+/// A problem found while compiling, pointing at the [Range] of source it was found in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub range: Option<Range>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, range: Option<Range>) -> Self {
+        Self {
+            message: message.into(),
+            range,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// Renders the diagnostic rustc-style, quoting the offending line of `source` and underlining the range with carets.
+    ///
+    /// Ranges spanning multiple lines are underlined until the end of their first line.
+    pub fn render(&self, file_name: &str, source: &str) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "error: {}", self.message);
+
+        let Some(range) = &self.range else {
+            let _ = writeln!(report, " --> {}", file_name);
+            for note in &self.notes {
+                let _ = writeln!(report, "  = note: {}", note);
+            }
+            return report;
+        };
+
+        let line_number = (range.start.line + 1).to_string();
+        let gutter = " ".repeat(line_number.len());
+        let line = source.lines().nth(range.start.line).unwrap_or("");
+        let line_length = line.chars().count();
+
+        // Keep tabs in the padding so the carets line up with the quoted line.
+        let padding: String = line.chars()
+            .take(range.start.char)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline_end = if range.end.line == range.start.line {
+            range.end.char + 1
+        } else {
+            line_length
+        };
+        let underline_length = underline_end.saturating_sub(range.start.char).max(1);
+
+        let _ = writeln!(report, "{} --> {}:{}:{}", gutter, file_name, range.start.line + 1, range.start.char + 1);
+        let _ = writeln!(report, "{} |", gutter);
+        let _ = writeln!(report, "{} | {}", line_number, line);
+        let _ = writeln!(report, "{} | {}{}", gutter, padding, "^".repeat(underline_length));
+        for note in &self.notes {
+            let _ = writeln!(report, "{} = note: {}", gutter, note);
+        }
+        report
+    }
+}
+
+impl From<&LexerError> for Diagnostic {
+    fn from(error: &LexerError) -> Self {
+        Diagnostic::new(error.source.to_string(), Some(error.range.clone()))
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(error: &ParserError) -> Self {
+        Diagnostic::new(error.to_string(), Some(error.token().range.clone()))
+    }
+}
+
+impl From<&CodegenError> for Diagnostic {
+    fn from(error: &CodegenError) -> Self {
+        let diagnostic = Diagnostic::new(error.source.to_string(), error.range().cloned());
+        if diagnostic.range.is_none() {
+            return diagnostic.with_note("this error could not be traced back to a location in the source");
+        }
+        diagnostic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_render_underline() {
+        let source = "func main() {\n    num x = y;\n}";
+        let diagnostic = Diagnostic::new("Invalid variable name.", Some(Range::new((1, 12), (1, 12))))
+            .with_note("`y` is not declared in this scope");
+
+        assert_eq!(
+            diagnostic.render("main.esh", source),
+            "error: Invalid variable name.\n  --> main.esh:2:13\n  |\n2 |     num x = y;\n  |             ^\n  = note: `y` is not declared in this scope\n"
+        );
+    }
+
+    #[test]
+    pub fn test_render_multiline_and_headless() {
+        let source = "if x {\n\ty = \"abc\n}";
+        let diagnostic = Diagnostic::new("unterminated string", Some(Range::new((1, 5), (2, 0))));
+        assert_eq!(
+            diagnostic.render("main.esh", source),
+            "error: unterminated string\n  --> main.esh:2:6\n  |\n2 | \ty = \"abc\n  | \t    ^^^^\n"
+        );
+
+        let headless = Diagnostic::new("Generic Error", None);
+        assert_eq!(headless.render("main.esh", source), "error: Generic Error\n --> main.esh\n");
+    }
+}
@@ -1,9 +1,54 @@
-use std::rc::Rc;
-use lexer::types::{Keyword, Token, TokenType, ValuedKeyword};
+use std::{borrow::Cow, collections::HashMap, fmt, rc::Rc};
+use lexer::types::{Keyword, Position, Range, TemplateSegment, Token, TokenType, ValuedKeyword};
 
-/// A syntactical node
+/// A syntactical node, spanning the [Range] of the tokens it was parsed from
 #[derive(Debug, PartialEq)]
-pub enum Node {
+pub struct Node {
+    pub node_type: NodeType,
+    pub range: Range,
+}
+
+impl Node {
+    pub fn new(node_type: NodeType, range: Range) -> Self {
+        Self {
+            node_type,
+            range,
+        }
+    }
+
+    /// The nodes directly nested inside of this one
+    pub fn children(&self) -> Vec<&Rc<Node>> {
+        match &self.node_type {
+            NodeType::None | NodeType::Primary(..) => vec![],
+            NodeType::Not(a) | NodeType::Negative(a) | NodeType::Break(a) | NodeType::Continue(a)
+            | NodeType::Return(a) | NodeType::ConstFunc(a) | NodeType::Visibility(_, a) | NodeType::Package(a)
+            | NodeType::Optional(a) => vec![a],
+            NodeType::FunctionCall(a, b) | NodeType::Access(a, b) | NodeType::Construct(a, b) | NodeType::Cast(a, b)
+            | NodeType::Product(a, b) | NodeType::Quotient(a, b) | NodeType::Modulo(a, b) | NodeType::FloorQuotient(a, b)
+            | NodeType::Exponent(a, b) | NodeType::BitAnd(a, b) | NodeType::BitOr(a, b) | NodeType::BitXor(a, b)
+            | NodeType::ShiftLeft(a, b) | NodeType::ShiftRight(a, b) | NodeType::Sum(a, b) | NodeType::Difference(a, b)
+            | NodeType::LessThan(a, b) | NodeType::GreaterThan(a, b) | NodeType::LessThanOrEqualTo(a, b)
+            | NodeType::GreaterThanOrEqualTo(a, b) | NodeType::Equal(a, b) | NodeType::NotEqual(a, b) | NodeType::And(a, b)
+            | NodeType::Or(a, b) | NodeType::ListCall(a, b) | NodeType::Declaration(a, b) | NodeType::Assignment(a, b)
+            | NodeType::Const(a, b) | NodeType::CompoundAssignment(a, b) | NodeType::If(a, b) | NodeType::Else(a, b)
+            | NodeType::While(a, b) | NodeType::Labeled(a, b) | NodeType::Struct(a, b, _) | NodeType::Domain(a, b, _)
+            | NodeType::EventDecl(a, b, _) | NodeType::Event(a, b) | NodeType::Import(a, b) | NodeType::Coalesce(a, b)
+            | NodeType::EnumVariant(a, b) => vec![a, b],
+            NodeType::Vector(a, b, c) | NodeType::For(a, b, c) | NodeType::Range(a, b, c) | NodeType::DFASM(a, b, c)
+            | NodeType::IfLet(a, b, c) => vec![a, b, c],
+            NodeType::Func(a, b, c, d, _) => vec![a, b, c, d],
+            NodeType::Location(a, b, c, d, e) => vec![a, b, c, d, e],
+            NodeType::Tuple(nodes) | NodeType::List(nodes) | NodeType::Template(nodes) | NodeType::Block(nodes) => nodes.iter().collect(),
+            NodeType::Match(a, nodes) | NodeType::FromImport(a, nodes) | NodeType::Generic(a, nodes) | NodeType::Enum(a, nodes, _) => std::iter::once(a).chain(nodes).collect(),
+            NodeType::MatchArm(nodes, a) => nodes.iter().chain(std::iter::once(a)).collect(),
+            NodeType::Map(entries) => entries.iter().flat_map(|(key, value)| [key, value]).collect(),
+        }
+    }
+}
+
+/// The kind of a [Node]
+#[derive(Debug, PartialEq)]
+pub enum NodeType {
     None,                                                       // ()
     Primary(Rc<Token>),                                         // 0
     FunctionCall(Rc<Node>, Rc<Node>),                           // ident(tuple/expr)
@@ -11,9 +56,17 @@ pub enum Node {
     Construct(Rc<Node>, Rc<Node>),                              // ident {block} 
     Not(Rc<Node>),                                              // !expr
     Negative(Rc<Node>),                                         // -expr
+    Cast(Rc<Node>, Rc<Node>),                                   // expr as type
     Product(Rc<Node>, Rc<Node>),                                // expr * expr
     Quotient(Rc<Node>, Rc<Node>),                               // expr / expr
     Modulo(Rc<Node>, Rc<Node>),                                 // expr % expr
+    FloorQuotient(Rc<Node>, Rc<Node>),                          // expr // expr
+    Exponent(Rc<Node>, Rc<Node>),                               // expr ** expr
+    BitAnd(Rc<Node>, Rc<Node>),                                 // expr & expr
+    BitOr(Rc<Node>, Rc<Node>),                                  // expr | expr
+    BitXor(Rc<Node>, Rc<Node>),                                 // expr ^ expr
+    ShiftLeft(Rc<Node>, Rc<Node>),                              // expr << expr
+    ShiftRight(Rc<Node>, Rc<Node>),                             // expr >> expr
     Sum(Rc<Node>, Rc<Node>),                                    // expr + expr
     Difference(Rc<Node>, Rc<Node>),                             // expr - expr
     LessThan(Rc<Node>, Rc<Node>),                               // expr < expr
@@ -25,24 +78,52 @@ pub enum Node {
     NotEqual(Rc<Node>, Rc<Node>),                               // expr != expr
     And(Rc<Node>, Rc<Node>),                                    // expr && expr
     Or(Rc<Node>, Rc<Node>),                                     // expr || expr
+    Coalesce(Rc<Node>, Rc<Node>),                               // expr ?? expr
     ListCall(Rc<Node>, Rc<Node>),                               // ident[expr]
     List(Vec<Rc<Node>>),                                        // [expr, expr, expr]
+    Map(Vec<(Rc<Node>, Rc<Node>)>),                             // {expr: expr, expr: expr}
+    Template(Vec<Rc<Node>>),                                    // `text {expr} text`
     Vector(Rc<Node>, Rc<Node>, Rc<Node>),                       // <expr, expr, expr>
     Location(Rc<Node>, Rc<Node>, Rc<Node>, Rc<Node>, Rc<Node>), // <expr, expr, expr, expr, expr>
-    Declaration(Rc<Node>, Rc<Node>),                            // ident ident
-    Break,                                                      // break;
+    Declaration(Rc<Node>, Rc<Node>),                            // ident ident, or let ident
+    Break(Rc<Node>),                                            // break 'label;
+    Continue(Rc<Node>),                                         // continue 'label;
     Return(Rc<Node>),                                           // return expr;
     Assignment(Rc<Node>, Rc<Node>),                             // decl/ident = expr;
+    Const(Rc<Node>, Rc<Node>),                                  // const decl = expr;
+    CompoundAssignment(Rc<Node>, Rc<Node>),                     // ident += expr; ident++; (the second node is ident + expr)
     If(Rc<Node>, Rc<Node>),                                     // if cond {block}
+    IfLet(Rc<Node>, Rc<Node>, Rc<Node>),                        // if let ident = expr {block}
     Else(Rc<Node>, Rc<Node>),                                   // stmt else {block}
     While(Rc<Node>, Rc<Node>),                                  // while cond {block}
-    Func(Rc<Node>, Rc<Node>, Rc<Node>, Rc<Node>),               // func ident (tuple/decl) -> tuple/ident {block}
-    Struct(Rc<Node>, Rc<Node>),                                 // struct ident {block}
-    Domain(Rc<Node>, Rc<Node>),                                 // domain ident {block}
+    For(Rc<Node>, Rc<Node>, Rc<Node>),                          // for ident in expr/range {block}
+    Labeled(Rc<Node>, Rc<Node>),                                // 'label: while/for
+    Match(Rc<Node>, Vec<Rc<Node>>),                             // match expr { arm, arm, arm }
+    MatchArm(Vec<Rc<Node>>, Rc<Node>),                          // expr | expr | expr => {block} (_ matches anything)
+    Range(Rc<Node>, Rc<Node>, Rc<Node>),                        // expr..expr step expr (both ends included, the step is optional)
+    Func(Rc<Node>, Rc<Node>, Rc<Node>, Rc<Node>, Option<String>), // (/// doc) func ident (tuple/decl) -> tuple/ident {block}
+    ConstFunc(Rc<Node>),                                        // const func ident (tuple/decl) -> tuple/ident {block}
+    Struct(Rc<Node>, Rc<Node>, Option<String>),                 // (/// doc) struct ident {block}
+    Enum(Rc<Node>, Vec<Rc<Node>>, Option<String>),              // (/// doc) enum ident { variant, variant, variant }
+    EnumVariant(Rc<Node>, Rc<Node>),                            // ident(tuple/decl) (the payload is () for a variant without one)
+    Domain(Rc<Node>, Rc<Node>, Option<String>),                 // (/// doc) domain ident {block}
     Block(Vec<Rc<Node>>),                                       // stmt; stmt; stmt;
     DFASM(Rc<Node>, Rc<Node>, Rc<Node>),                        // dfasm(tuple/ident) -> type ident {dfasm block}
-    EventDecl(Rc<Node>, Rc<Node>),                              // event ident(tuple/decl OR type);
-    Event(Rc<Node>, Rc<Node>)                                   // event ident { block }
+    EventDecl(Rc<Node>, Rc<Node>, Option<String>),              // (/// doc) event ident(tuple/decl OR type);
+    Event(Rc<Node>, Rc<Node>),                                  // event ident { block }
+    Visibility(Visibility, Rc<Node>),                           // pub/private stmt
+    Generic(Rc<Node>, Vec<Rc<Node>>),                           // ident<type, type> (the names of the type parameters when declaring a struct or func)
+    Optional(Rc<Node>),                                         // type?
+    Package(Rc<Node>),                                          // package ident.ident;
+    Import(Rc<Node>, Rc<Node>),                                 // import ident.ident as ident; (the alias is () if not given)
+    FromImport(Rc<Node>, Vec<Rc<Node>>),                        // from ident.ident import ident as ident, ident; (each name is an Import)
+}
+
+/// The visibility a declaration is given with `pub` or `private`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Visibility {
+    Public,
+    Private,
 }
 
 /// A parser error
@@ -56,6 +137,40 @@ pub enum ParserError {
     MissingBracket(Rc<Token>),      // Expected opening/closing bracket
     MissingBrace(Rc<Token>),        // Expected opening/closing brace
     MissingAngleBracket(Rc<Token>), // Expected opening/closing angle bracket
+    CommentAfterOperand(Rc<Token>), // A `//` meant as a comment was read as floor division
+}
+
+impl ParserError {
+    /// Gets the token the error was raised on
+    pub fn token(&self) -> &Rc<Token> {
+        match self {
+            ParserError::InvalidToken(token)
+            | ParserError::InvalidStatement(token)
+            | ParserError::MissingIdentifier(token)
+            | ParserError::MissingSemicolon(token)
+            | ParserError::MissingParenthesis(token)
+            | ParserError::MissingBracket(token)
+            | ParserError::MissingBrace(token)
+            | ParserError::MissingAngleBracket(token)
+            | ParserError::CommentAfterOperand(token) => token,
+        }
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            ParserError::InvalidToken(..) => "Unexpected token.",
+            ParserError::InvalidStatement(..) => "Unrecognized statement.",
+            ParserError::MissingIdentifier(..) => "Expected an identifier.",
+            ParserError::MissingSemicolon(..) => "Expected a semicolon.",
+            ParserError::MissingParenthesis(..) => "Expected a parenthesis.",
+            ParserError::MissingBracket(..) => "Expected a bracket.",
+            ParserError::MissingBrace(..) => "Expected a brace.",
+            ParserError::MissingAngleBracket(..) => "Expected an angle bracket.",
+            ParserError::CommentAfterOperand(..) => "`//` right after an operand is floor division, not a comment. Put the comment on its own line.",
+        })
+    }
 }
 
 /// Returns a [ParserError] if [self.curr()](Parser::curr()) does not match the input.
@@ -65,7 +180,9 @@ macro_rules! expect {
             $token => false,
             _ => true
         } {
-            return Err(if let $token = TokenType::Ident("".to_string()) {
+            return Err(if let TokenType::DoubleSlash = $self.curr().token_type {
+                ParserError::CommentAfterOperand($self.curr().clone())
+            } else if let $token = TokenType::Ident("".to_string()) {
                 ParserError::MissingIdentifier($self.curr().clone())
             } else if let $token = TokenType::Semicolon {
                 ParserError::MissingSemicolon($self.curr().clone())
@@ -85,15 +202,77 @@ macro_rules! expect {
 }
 
 pub struct Parser<'a> {
-    tokens: &'a [Rc<Token>],
+    tokens: Cow<'a, [Rc<Token>]>,
     current: usize,
+
+    /// The text of `///` doc comments, which are taken out of [Self::tokens], keyed by the index of the token they sit on top of.
+    doc_comments: HashMap<usize, String>,
+
+    /// The [TokenType::Eof] token, given out by [Self::curr()] once every token has been consumed.
+    /// If the input doesn't end with one, it is placed right after the last token.
+    eof: Rc<Token>,
+
+    /// Used to denote whether statement blocks should collect their errors into [Self::errors] and keep going.
+    recovering: bool,
+    errors: Vec<ParserError>,
+
+    /// Used to denote whether `ident {` should be left alone rather than parsed as a construct, like in a loop header.
+    no_construct: bool,
+
+    /// How many lists of type arguments are being parsed inside of each other, like in `Box<Box<num>>`.
+    type_argument_depth: usize,
+    /// Used to denote whether the first half of a `>>` token has closed the inner list of nested type arguments.
+    split_shift: bool,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a [Rc<Token>]) -> Self {
+        let mut doc_comments: HashMap<usize, String> = HashMap::new();
+        let tokens = if input.iter().any(|token| matches!(token.token_type, TokenType::Comment(..))) {
+            let mut tokens = Vec::with_capacity(input.len());
+            for token in input {
+                let TokenType::Comment(text) = &token.token_type else {
+                    tokens.push(token.clone());
+                    continue;
+                };
+                match doc_comments.get_mut(&tokens.len()) {
+                    Some(doc) => {
+                        doc.push('\n');
+                        doc.push_str(text);
+                    },
+                    None => {
+                        doc_comments.insert(tokens.len(), text.clone());
+                    },
+                }
+            }
+            Cow::Owned(tokens)
+        } else {
+            Cow::Borrowed(input)
+        };
+        let eof = match input.iter().rev().find(|token| !matches!(token.token_type, TokenType::Comment(..))) {
+            Some(token) if token.token_type == TokenType::Eof => token.clone(),
+            Some(token) => {
+                let position = Position { line: token.range.end.line, char: token.range.end.char + 1 };
+                Rc::new(Token {
+                    token_type: TokenType::Eof,
+                    range: Range { start: position.clone(), end: position },
+                })
+            },
+            None => Rc::new(Token {
+                token_type: TokenType::Eof,
+                range: Range::new((0, 0), (0, 0)),
+            }),
+        };
         Self {
-            tokens: input,
+            tokens,
             current: 0,
+            doc_comments,
+            eof,
+            recovering: false,
+            errors: Vec::new(),
+            no_construct: false,
+            type_argument_depth: 0,
+            split_shift: false,
         }
     }
 
@@ -101,9 +280,65 @@ impl<'a> Parser<'a> {
         self.statement_block()
     }
 
-    /// Gets the current token
+    /// Parses the whole input, recovering from syntax errors instead of stopping at the first one.
+    ///
+    /// After an error, the parser skips ahead to the end of the statement (a `;` or a closing `}`) and keeps going.
+    /// Returns the partial statement block along with every error found.
+    pub fn parse_recovering(&mut self) -> (Node, Vec<ParserError>) {
+        self.recovering = true;
+        let start = self.current;
+        let mut block = vec![];
+        loop {
+            if let Ok(Node { node_type: NodeType::Block(statements), .. }) = self.statement_block() {
+                block.extend(statements);
+            }
+            if self.is_at_end() {
+                break;
+            }
+            // The statement block only stops early on a closing brace with nothing to close.
+            self.errors.push(ParserError::InvalidStatement(self.curr().clone()));
+            self.advance();
+        }
+        self.recovering = false;
+        (self.node(start, NodeType::Block(block)), std::mem::take(&mut self.errors))
+    }
+
+    /// Skips to the start of the next statement after an error.
+    ///
+    /// Stops after a `;` or a whole `{ }` block, or right before a `}` closing the block the error was found in.
+    pub(crate) fn synchronize(&mut self) {
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            match self.curr().token_type {
+                TokenType::Semicolon if depth == 0 => {
+                    self.advance();
+                    return;
+                },
+                TokenType::LBrace => depth += 1,
+                TokenType::RBrace => {
+                    if depth == 0 {
+                        return;
+                    }
+                    depth -= 1;
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                },
+                _ => ()
+            }
+            self.advance();
+        }
+    }
+
+    /// Gets the current token, or the [TokenType::Eof] token if every token has been consumed
     pub(crate) fn curr(&self) -> &Rc<Token> {
-        &self.tokens[self.current]
+        self.tokens.get(self.current).unwrap_or(&self.eof)
+    }
+
+    /// Gets the doc comment written right above the token at `index`, if any
+    pub(crate) fn doc_comment(&self, index: usize) -> Option<String> {
+        self.doc_comments.get(&index).cloned()
     }
 
     /// Gets the previous token
@@ -111,26 +346,79 @@ impl<'a> Parser<'a> {
         &self.tokens[self.current - 1]
     }
 
-    /// Advances to the next token
+    /// Advances to the next token, never going past the end of the input
     pub(crate) fn advance(&mut self) {
-        self.current += 1;
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+    }
+
+    /// Runs `parse` with [Self::no_construct] set to `no_construct`, putting the outer value back afterwards
+    pub(crate) fn with_no_construct(&mut self, no_construct: bool, parse: fn(&mut Self) -> Result<Node, ParserError>) -> Result<Node, ParserError> {
+        let outer = std::mem::replace(&mut self.no_construct, no_construct);
+        let result = parse(self);
+        self.no_construct = outer;
+        result
     }
 
-    /// If the current token is out of range
+    /// If the current token is out of range or the end of the file
     pub(crate) fn is_at_end(&mut self) -> bool {
-        self.current >= self.tokens.len()
+        self.current >= self.tokens.len() || self.tokens[self.current].token_type == TokenType::Eof
+    }
+
+    /// Gets the position where the token at `index` starts.
+    ///
+    /// If `index` is out of range, the end of the last token is used instead.
+    pub(crate) fn position_at(&self, index: usize) -> Position {
+        if let Some(token) = self.tokens.get(index) {
+            return token.range.start.clone();
+        }
+        match self.tokens.last() {
+            Some(token) => token.range.end.clone(),
+            None => Position { line: 0, char: 0 },
+        }
+    }
+
+    /// Gets the range spanning from the token at `start` to the previous token.
+    ///
+    /// If no token has been consumed since `start`, the range is empty and sits where the token at `start` begins.
+    pub(crate) fn range_from(&self, start: usize) -> Range {
+        if self.current <= start || start >= self.tokens.len() {
+            let position = self.position_at(start);
+            return Range {
+                start: position.clone(),
+                end: position,
+            };
+        }
+        Range {
+            start: self.tokens[start].range.start.clone(),
+            end: self.tokens[self.current.min(self.tokens.len()) - 1].range.end.clone(),
+        }
+    }
+
+    /// Wraps a [NodeType] in a [Node] spanning every token consumed since the token at `start`
+    pub(crate) fn node(&self, start: usize, node_type: NodeType) -> Node {
+        Node::new(node_type, self.range_from(start))
     }
 
     /// Returns the current statement block
     pub(crate) fn statement_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut block = vec![];
         while !self.is_at_end() {
             if self.curr().token_type == TokenType::RBrace {
                 break;
             }
-            block.push(Rc::new(self.statement()?));
+            match self.statement() {
+                Ok(statement) => block.push(Rc::new(statement)),
+                Err(e) if self.recovering => {
+                    self.errors.push(e);
+                    self.synchronize();
+                },
+                Err(e) => return Err(e),
+            }
         }
-        return Ok(Node::Block(block));
+        return Ok(self.node(start, NodeType::Block(block)));
     }
 
     /// Returns the current statement
@@ -139,21 +427,27 @@ impl<'a> Parser<'a> {
             TokenType::Keyword(Keyword::Value(ValuedKeyword::Event)) => {
                 self.event()
             },
-            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => {
-                let expr = self.assignment();
+            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_) | Keyword::Let) => {
+                let expr = self.assignment()?;
                 expect!(self, TokenType::Semicolon);
                 self.advance();
-                expr
+                Ok(expr)
             },
             TokenType::Keyword(Keyword::Struct) => {
                 self.struct_statement()
             },
+            TokenType::Keyword(Keyword::Enum) => {
+                self.enum_statement()
+            },
             TokenType::Keyword(Keyword::Domain) => {
                 self.domain_statement()
             },
             TokenType::Keyword(Keyword::Func) => {
                 self.func()
             },
+            TokenType::Keyword(Keyword::Pub | Keyword::Private) => {
+                self.visibility_statement()
+            },
             TokenType::Keyword(Keyword::If) => {
                 self.if_else_block()
             },
@@ -163,46 +457,125 @@ impl<'a> Parser<'a> {
             TokenType::Keyword(Keyword::While) => {
                 self.while_block()
             },
-            TokenType::Keyword(Keyword::Return) => {
-                let expr = self.return_block();
+            TokenType::Keyword(Keyword::For) => {
+                self.for_block()
+            },
+            TokenType::Keyword(Keyword::Match) => {
+                self.match_block()
+            },
+            TokenType::Keyword(Keyword::Const) => {
+                let expr = self.const_statement()?;
+                if !matches!(expr.node_type, NodeType::ConstFunc(..)) {
+                    expect!(self, TokenType::Semicolon);
+                    self.advance();
+                }
+                Ok(expr)
+            },
+            TokenType::Keyword(Keyword::Package | Keyword::Import | Keyword::From) => {
+                let expr = self.import_statement()?;
                 expect!(self, TokenType::Semicolon);
                 self.advance();
-                expr
+                Ok(expr)
             },
-            TokenType::Keyword(Keyword::Break) => {
+            TokenType::Keyword(Keyword::Return) => {
+                let expr = self.return_block()?;
+                expect!(self, TokenType::Semicolon);
                 self.advance();
+                Ok(expr)
+            },
+            TokenType::Keyword(Keyword::Break | Keyword::Continue) => {
+                let expr = self.loop_control()?;
                 expect!(self, TokenType::Semicolon);
                 self.advance();
-                Ok(Node::Break)
+                Ok(expr)
+            },
+            TokenType::Label(_) => {
+                self.labeled_loop()
             },
             _ => Err(ParserError::InvalidStatement(self.curr().clone()))
         }
     }
 
+    /// Returns the current declaration statement, along with the visibility it was given
+    pub(crate) fn visibility_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let visibility = match self.curr().token_type {
+            TokenType::Keyword(Keyword::Private) => Visibility::Private,
+            _ => Visibility::Public,
+        };
+        self.advance();
+        if matches!(self.curr().token_type, TokenType::Keyword(Keyword::Pub | Keyword::Private)) {
+            return Err(ParserError::InvalidStatement(self.curr().clone()));
+        }
+        // The doc comment sits above the visibility keyword, but documents the declaration after it.
+        if let Some(doc) = self.doc_comments.remove(&start) {
+            self.doc_comments.insert(self.current, doc);
+        }
+        let statement = self.statement()?;
+        Ok(self.node(start, NodeType::Visibility(visibility, Rc::new(statement))))
+    }
+
     /// Returns the current struct declaration statement
     pub(crate) fn struct_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::Struct));
-        let expr = Node::Struct(
+        let expr = NodeType::Struct(
             {  // Struct name
                 self.advance();
                 expect!(self, TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)));
-                Rc::new(self.ident()?)
+                Rc::new(self.generic_ident()?)
             },
             {  // Struct body
                 expect!(self, TokenType::LBrace);
                 self.advance();
                 Rc::new(self.statement_block()?)
             },
+            self.doc_comment(start),
         );
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
+    }
+
+    /// Returns the current enum declaration statement
+    pub(crate) fn enum_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        expect!(self, TokenType::Keyword(Keyword::Enum));
+        self.advance();
+        expect!(self, TokenType::Ident(_));
+        let ident = self.ident()?;
+        expect!(self, TokenType::LBrace);
+        self.advance();
+        let mut variants = Vec::new();
+        while self.curr().token_type != TokenType::RBrace && !self.is_at_end() {
+            variants.push(Rc::new(self.enum_variant()?));
+            match self.curr().token_type {
+                TokenType::Comma => self.advance(),
+                _ => break,
+            }
+        }
+        expect!(self, TokenType::RBrace);
+        self.advance();
+        Ok(self.node(start, NodeType::Enum(Rc::new(ident), variants, self.doc_comment(start))))
+    }
+
+    /// Returns the current enum variant, along with the declarations of its payload
+    pub(crate) fn enum_variant(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        expect!(self, TokenType::Ident(_));
+        let ident = self.ident()?;
+        let payload = match self.curr().token_type {
+            TokenType::LParen => self.tuple()?,
+            _ => self.node(self.current, NodeType::None),
+        };
+        Ok(self.node(start, NodeType::EnumVariant(Rc::new(ident), Rc::new(payload))))
     }
 
     /// Returns the current domain declaration statement
     pub(crate) fn domain_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::Domain));
-        let expr = Node::Domain(
+        let expr = NodeType::Domain(
             {  // Domain name
                 self.advance();
                 expect!(self, TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)));
@@ -213,20 +586,22 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Rc::new(self.statement_block()?)
             },
+            self.doc_comment(start),
         );
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
     }
 
     /// Returns the current function declaration statement
     pub(crate) fn func(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::Func));
-        let expr = Node::Func(
+        let expr = NodeType::Func(
             {  // Function name
                 self.advance();
                 expect!(self, TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)));
-                Rc::new(self.ident()?)
+                Rc::new(self.generic_ident()?)
             },
             {  // Function parameters
                 expect!(self, TokenType::LParen);
@@ -237,11 +612,11 @@ impl<'a> Parser<'a> {
                     TokenType::Arrow => {
                         self.advance();
                         match self.curr().token_type {
-                            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => Rc::new(self.ident()?),
+                            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => Rc::new(self.type_expression()?),
                             _ => Rc::new(self.primary()?)
                         }
                     },
-                    _ => Rc::new(Node::None)
+                    _ => Rc::new(self.node(self.current, NodeType::None))
                 }
             },
             {  // Function body
@@ -249,10 +624,11 @@ impl<'a> Parser<'a> {
                 self.advance();
                 Rc::new(self.statement_block()?)
             },
+            self.doc_comment(start),
         );
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
     }
 
     /// Returns the current event declaration/listening statement
@@ -262,22 +638,23 @@ impl<'a> Parser<'a> {
         self.advance();
         let Ok(event_ident) = self.access() else { // Regular ol' event keyword
             self.current = current_save;
-            let expr = self.assignment();
+            let expr = self.assignment()?;
             expect!(self, TokenType::Semicolon);
             self.advance();
-            return expr;
+            return Ok(expr);
         };
         let expr = match self.curr().token_type {
             TokenType::LParen => { // Event declaration
-                let ret = Node::EventDecl( // event ident(things)
+                let ret = NodeType::EventDecl( // event ident(things)
                     Rc::new(event_ident),
                     Rc::new(self.tuple()?),
+                    self.doc_comment(current_save),
                 );
                 expect!(self, TokenType::Semicolon);
                 ret
             },
             TokenType::LBrace => { // Event listening
-                let ret = Node::Event(
+                let ret = NodeType::Event(
                     {  // Event listening ident
                         Rc::new(event_ident)
                     },
@@ -291,17 +668,18 @@ impl<'a> Parser<'a> {
             },
             _ => { // Regular ol' event keyword
                 self.current = current_save;
-                let expr = self.assignment();
+                let expr = self.assignment()?;
                 expect!(self, TokenType::Semicolon);
                 self.advance();
-                return expr;
+                return Ok(expr);
             }
         };
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(current_save, expr));
     }
 
     pub(crate) fn if_else_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::If));
         let mut expr = self.if_block()?;
         if self.is_at_end() {
@@ -310,33 +688,36 @@ impl<'a> Parser<'a> {
         if let TokenType::Keyword(Keyword::Else) = self.curr().token_type {
             self.advance();
             if self.is_at_end() {
-                return Err(ParserError::MissingBrace(self.tokens[self.current - 1].clone()));
+                return Err(ParserError::MissingBrace(self.curr().clone()));
             }
             match self.curr().token_type {
                 TokenType::Keyword(Keyword::If) => {
-                    return Ok(Node::Else(
+                    let else_if = self.if_else_block()?;
+                    return Ok(self.node(start, NodeType::Else(
                         Rc::new(expr),
-                        Rc::new(self.if_else_block()?)
-                    ));
+                        Rc::new(else_if)
+                    )));
                 },
                 TokenType::LBrace => {
                     self.advance();
-                    expr = Node::Else(
+                    let else_block = self.statement_block()?;
+                    expect!(self, TokenType::RBrace);
+                    self.advance();
+                    expr = self.node(start, NodeType::Else(
                         Rc::new(expr),
-                        Rc::new(self.statement_block()?)
-                    );
+                        Rc::new(else_block)
+                    ));
                 },
                 _ => return Err(ParserError::MissingBrace(self.curr().clone()))
             }
-            expect!(self, TokenType::RBrace);
-            self.advance();
         }
         return Ok(expr);
     }
 
     pub(crate) fn inline_dfasm_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::DFASM));
-        let expr = Node::DFASM(
+        let expr = NodeType::DFASM(
             {  // DFasm parameters
                 self.advance();
                 expect!(self, TokenType::LParen);
@@ -351,28 +732,32 @@ impl<'a> Parser<'a> {
                             _ => Rc::new(self.primary()?)
                         }
                     },
-                    _ => Rc::new(Node::None)
+                    _ => Rc::new(self.node(self.current, NodeType::None))
                 }
             },
             {  // Function body
                 expect!(self, TokenType::LBrace);
                 self.advance();
                 expect!(self, TokenType::DFASM(..));
-                Rc::new(Node::Primary(self.curr().clone()))
+                Rc::new(Node::new(NodeType::Primary(self.curr().clone()), self.curr().range.clone()))
             },
         );
         self.advance();
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
     }
 
     /// Returns the current if statement
     pub(crate) fn if_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::If));
-        let expr = Node::If(
+        self.advance();
+        if self.curr().token_type == TokenType::Keyword(Keyword::Let) {
+            return self.if_let_block(start);
+        }
+        let expr = NodeType::If(
             {    // If statement expression
-                self.advance();
                 Rc::new(self.logic()?)
             },
             {   // If statement body
@@ -383,16 +768,41 @@ impl<'a> Parser<'a> {
         );
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
+    }
+
+    /// Returns the current `if let`, which runs its body with the value of an optional when it isn't `none`
+    pub(crate) fn if_let_block(&mut self, start: usize) -> Result<Node, ParserError> {
+        expect!(self, TokenType::Keyword(Keyword::Let));
+        let expr = NodeType::IfLet(
+            {   // Unwrapped variable
+                self.advance();
+                Rc::new(self.ident()?)
+            },
+            {   // Optional value
+                expect!(self, TokenType::Assign);
+                self.advance();
+                Rc::new(self.with_no_construct(true, Self::expression)?)
+            },
+            {   // If let body
+                expect!(self, TokenType::LBrace);
+                self.advance();
+                Rc::new(self.statement_block()?)
+            },
+        );
+        expect!(self, TokenType::RBrace);
+        self.advance();
+        return Ok(self.node(start, expr));
     }
 
     /// Returns the current while statement
     pub(crate) fn while_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::Keyword(Keyword::While));
-        let expr = Node::While(
+        let expr = NodeType::While(
             {    // While statement expression
                 self.advance();
-                Rc::new(self.logic()?)
+                Rc::new(self.with_no_construct(true, Self::logic)?)
             },
             {   // While statement body
                 expect!(self, TokenType::LBrace);
@@ -402,20 +812,173 @@ impl<'a> Parser<'a> {
         );
         expect!(self, TokenType::RBrace);
         self.advance();
-        return Ok(expr);
+        return Ok(self.node(start, expr));
+    }
+
+    /// Returns the current `break` or `continue`, along with the label of the loop it is for (if any)
+    pub(crate) fn loop_control(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let is_break = self.curr().token_type == TokenType::Keyword(Keyword::Break);
+        self.advance();
+        let label = match self.curr().token_type {
+            TokenType::Label(_) => {
+                self.advance();
+                Node::new(NodeType::Primary(self.prev().clone()), self.prev().range.clone())
+            },
+            _ => self.node(self.current, NodeType::None),
+        };
+        Ok(self.node(start, match is_break {
+            true => NodeType::Break(Rc::new(label)),
+            false => NodeType::Continue(Rc::new(label)),
+        }))
+    }
+
+    /// Returns the current labeled loop
+    pub(crate) fn labeled_loop(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let label = Node::new(NodeType::Primary(self.curr().clone()), self.curr().range.clone());
+        self.advance();
+        expect!(self, TokenType::Colon);
+        self.advance();
+        let labeled = match self.curr().token_type {
+            TokenType::Keyword(Keyword::While) => self.while_block()?,
+            TokenType::Keyword(Keyword::For) => self.for_block()?,
+            _ => return Err(ParserError::InvalidStatement(self.curr().clone())),
+        };
+        Ok(self.node(start, NodeType::Labeled(Rc::new(label), Rc::new(labeled))))
+    }
+
+    /// Returns the current for loop, over either a list or a range of numbers
+    pub(crate) fn for_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        expect!(self, TokenType::Keyword(Keyword::For));
+        let expr = NodeType::For(
+            {   // Loop variable, its type always comes from what's iterated so `let` is optional
+                self.advance();
+                if self.curr().token_type == TokenType::Keyword(Keyword::Let) {
+                    self.advance();
+                }
+                Rc::new(self.ident()?)
+            },
+            {   // Iterated list or range
+                expect!(self, TokenType::Keyword(Keyword::In));
+                self.advance();
+                Rc::new(self.with_no_construct(true, Self::for_iterated)?)
+            },
+            {   // For loop body
+                expect!(self, TokenType::LBrace);
+                self.advance();
+                Rc::new(self.statement_block()?)
+            },
+        );
+        expect!(self, TokenType::RBrace);
+        self.advance();
+        return Ok(self.node(start, expr));
+    }
+
+    /// Returns what a for loop iterates over, either a list expression or a `start..end step n` range
+    pub(crate) fn for_iterated(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let expr = self.expression()?;
+        if self.curr().token_type != TokenType::DotDot {
+            return Ok(expr);
+        }
+        self.advance();
+        let end = self.expression()?;
+        // `step` is only a keyword here, so it can still be used as a name everywhere else.
+        let step = match &self.curr().token_type {
+            TokenType::Ident(ident) if ident == "step" => {
+                self.advance();
+                self.expression()?
+            },
+            _ => self.node(self.current, NodeType::None),
+        };
+        Ok(self.node(start, NodeType::Range(Rc::new(expr), Rc::new(end), Rc::new(step))))
+    }
+
+    /// Returns the current match statement
+    pub(crate) fn match_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        expect!(self, TokenType::Keyword(Keyword::Match));
+        self.advance();
+        let value = self.with_no_construct(true, Self::expression)?;
+        expect!(self, TokenType::LBrace);
+        self.advance();
+        let mut arms = Vec::new();
+        while self.curr().token_type != TokenType::RBrace && !self.is_at_end() {
+            arms.push(Rc::new(self.match_arm()?));
+            if self.curr().token_type == TokenType::Comma {
+                self.advance();
+            }
+        }
+        expect!(self, TokenType::RBrace);
+        self.advance();
+        Ok(self.node(start, NodeType::Match(Rc::new(value), arms)))
+    }
+
+    /// Returns the current match arm
+    pub(crate) fn match_arm(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        // Patterns skip the bitwise or, `|` separates them instead.
+        let mut patterns = vec![Rc::new(self.bit_xor()?)];
+        while self.curr().token_type == TokenType::Bar {
+            self.advance();
+            patterns.push(Rc::new(self.bit_xor()?));
+        }
+        expect!(self, TokenType::FatArrow);
+        self.advance();
+        expect!(self, TokenType::LBrace);
+        self.advance();
+        let block = self.statement_block()?;
+        expect!(self, TokenType::RBrace);
+        self.advance();
+        Ok(self.node(start, NodeType::MatchArm(patterns, Rc::new(block))))
     }
 
     /// Returns the current assignment statement
     pub(crate) fn assignment(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let expr = self.declaration()?;
-        if self.is_at_end() || self.curr().token_type != TokenType::Assign {
+        if self.is_at_end() {
             return Ok(expr);
         }
-        self.advance();
-        return Ok(Node::Assignment(
-            Rc::new(expr),
-            Rc::new(self.expression()?),
-        ));
+        let operation: fn(Rc<Node>, Rc<Node>) -> NodeType = match self.curr().token_type {
+            TokenType::Assign => {
+                self.advance();
+                let value = self.expression()?;
+                return Ok(self.node(start, NodeType::Assignment(
+                    Rc::new(expr),
+                    Rc::new(value),
+                )));
+            },
+            TokenType::PlusAssign | TokenType::Increment => NodeType::Sum,
+            TokenType::DashAssign | TokenType::Decrement => NodeType::Difference,
+            TokenType::AsteriskAssign => NodeType::Product,
+            TokenType::SlashAssign => NodeType::Quotient,
+            TokenType::PercAssign => NodeType::Modulo,
+            _ => return Ok(expr),
+        };
+        let expr = Rc::new(expr);
+        let value = match self.curr().token_type {
+            TokenType::Increment | TokenType::Decrement => {
+                // x++ is x += 1, with the 1 sitting on the ++ itself
+                let one = Rc::new(Token {
+                    token_type: TokenType::Number(1.0),
+                    range: self.curr().range.clone(),
+                });
+                self.advance();
+                Node::new(NodeType::Primary(one.clone()), one.range.clone())
+            },
+            _ => {
+                self.advance();
+                self.expression()?
+            },
+        };
+        let operation = self.node(start, operation(expr.clone(), Rc::new(value)));
+        return Ok(self.node(start, NodeType::CompoundAssignment(
+            expr,
+            Rc::new(operation),
+        )));
     }
 
     /// Returns the current variable declaration
@@ -423,14 +986,27 @@ impl<'a> Parser<'a> {
         let start = self.current;
         match self.curr().token_type {
             TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => (),
+            TokenType::Keyword(Keyword::Let) => {
+                // The type is left as the `let` keyword, for codegen to infer from the assigned value.
+                let inferred_type = Node::new(NodeType::Primary(self.curr().clone()), self.curr().range.clone());
+                self.advance();
+                let ident = self.ident()?;
+                return Ok(self.node(start, NodeType::Declaration(
+                    Rc::new(inferred_type),
+                    Rc::new(ident),
+                )))
+            },
             _ => return self.expression(),
         }
         let expr = self.list_call()?;
         match self.curr().token_type {
-            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => return Ok(Node::Declaration(
-                Rc::new(expr),
-                Rc::new(self.ident()?),
-            )),
+            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => {
+                let ident = self.ident()?;
+                return Ok(self.node(start, NodeType::Declaration(
+                    Rc::new(expr),
+                    Rc::new(ident),
+                )))
+            },
             _ => {
                 self.current = start;
                 return self.expression();
@@ -438,38 +1014,122 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Returns the current constant declaration, or function run at compile time
+    pub(crate) fn const_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        self.advance();
+        if self.curr().token_type == TokenType::Keyword(Keyword::Func) {
+            // Same as with visibility, the doc comment sits above `const` but documents the function.
+            if let Some(doc) = self.doc_comments.remove(&start) {
+                self.doc_comments.insert(self.current, doc);
+            }
+            let function = self.func()?;
+            return Ok(self.node(start, NodeType::ConstFunc(Rc::new(function))));
+        }
+        expect!(self, TokenType::Ident(_) | TokenType::Keyword(Keyword::Let));
+        let declaration = self.declaration()?;
+        if !matches!(declaration.node_type, NodeType::Declaration(..)) {
+            return Err(ParserError::InvalidStatement(self.tokens[start].clone()));
+        }
+        expect!(self, TokenType::Assign);
+        self.advance();
+        let value = self.expression()?;
+        Ok(self.node(start, NodeType::Const(Rc::new(declaration), Rc::new(value))))
+    }
+
+    /// Returns the current package declaration or import statement
+    pub(crate) fn import_statement(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let keyword = self.curr().token_type.clone();
+        self.advance();
+        let path = self.access()?;
+        match keyword {
+            TokenType::Keyword(Keyword::Package) => {
+                Ok(self.node(start, NodeType::Package(Rc::new(path))))
+            },
+            TokenType::Keyword(Keyword::From) => {
+                expect!(self, TokenType::Keyword(Keyword::Import));
+                let mut names = Vec::new();
+                loop {
+                    self.advance();
+                    let name_start = self.current;
+                    let name = self.ident()?;
+                    let alias = self.import_alias()?;
+                    names.push(Rc::new(self.node(name_start, NodeType::Import(Rc::new(name), Rc::new(alias)))));
+                    if self.curr().token_type != TokenType::Comma {
+                        break;
+                    }
+                }
+                Ok(self.node(start, NodeType::FromImport(Rc::new(path), names)))
+            },
+            _ => {
+                let alias = self.import_alias()?;
+                Ok(self.node(start, NodeType::Import(Rc::new(path), Rc::new(alias))))
+            }
+        }
+    }
+
+    /// Returns the name given to an import with `as`, or an empty node if there isn't one
+    pub(crate) fn import_alias(&mut self) -> Result<Node, ParserError> {
+        if self.curr().token_type != TokenType::Keyword(Keyword::As) {
+            return Ok(self.node(self.current, NodeType::None));
+        }
+        self.advance();
+        self.ident()
+    }
+
     /// Returns the current return statement
     pub(crate) fn return_block(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         self.advance();
-        match self.curr().token_type {
-            TokenType::Semicolon => Ok(Node::Return(Rc::new(Node::None))),
-            _ => Ok(Node::Return(Rc::new(self.expression()?)))
-        }
+        let value = match self.curr().token_type {
+            TokenType::Semicolon => self.node(self.current, NodeType::None),
+            _ => self.expression()?
+        };
+        Ok(self.node(start, NodeType::Return(Rc::new(value))))
     }
 
     /// Returns the current expression
     pub(crate) fn expression(&mut self) -> Result<Node, ParserError> {
-        self.logic()
+        self.coalesce()
+    }
+
+    /// Returns the current `??`, which falls back to the right side when the left side is `none`
+    pub(crate) fn coalesce(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let expr = self.logic()?;
+        if self.is_at_end() || self.curr().token_type != TokenType::DoubleQuestion {
+            return Ok(expr);
+        }
+        self.advance();
+        let fallback = self.coalesce()?;
+        return Ok(self.node(start, NodeType::Coalesce(
+            Rc::new(expr),
+            Rc::new(fallback),
+        )));
     }
 
     /// Returns the current logic operation
     pub(crate) fn logic(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.equality()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::And => {
                     self.advance();
-                    expr = Node::And( 
+                    let right = self.equality()?;
+                    expr = self.node(start, NodeType::And( 
                         Rc::new(expr), 
-                        Rc::new(self.equality()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::Or => {
                     self.advance();
-                    expr = Node::Or(
+                    let right = self.equality()?;
+                    expr = self.node(start, NodeType::Or(
                         Rc::new(expr), 
-                        Rc::new(self.equality()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 _ => break
             }
@@ -479,22 +1139,25 @@ impl<'a> Parser<'a> {
 
     /// Returns the current equality
     pub(crate) fn equality(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.comparison()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::Equal => {
                     self.advance();
-                    expr = Node::Equal( 
+                    let right = self.comparison()?;
+                    expr = self.node(start, NodeType::Equal( 
                         Rc::new(expr), 
-                        Rc::new(self.comparison()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::NotEqual => {
                     self.advance();
-                    expr = Node::NotEqual(
+                    let right = self.comparison()?;
+                    expr = self.node(start, NodeType::NotEqual(
                         Rc::new(expr), 
-                        Rc::new(self.comparison()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 _ => break
             }
@@ -504,23 +1167,28 @@ impl<'a> Parser<'a> {
 
     /// Returns the current comparison
     pub(crate) fn comparison(&mut self) -> Result<Node, ParserError> {
-        let mut expr = self.term()?;
+        let start = self.current;
+        let mut expr = self.bit_or()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::LAngle => {
                     self.advance();
-                    expr = Node::LessThan(
+                    let right = self.bit_or()?;
+                    expr = self.node(start, NodeType::LessThan(
                         Rc::new(expr), 
-                        Rc::new(self.term()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::RAngle => {
                     self.advance();
                     match self.curr().token_type {
-                        TokenType::Ident(_) | TokenType::Number(_) | TokenType::Dash | TokenType::Keyword(Keyword::Value(_)) => expr = Node::GreaterThan(
-                            Rc::new(expr), 
-                            Rc::new(self.term()?),
-                        ),
+                        TokenType::Ident(_) | TokenType::Number(_) | TokenType::Dash | TokenType::Keyword(Keyword::Value(_)) => {
+                            let right = self.bit_or()?;
+                            expr = self.node(start, NodeType::GreaterThan(
+                                Rc::new(expr), 
+                                Rc::new(right),
+                            ))
+                        },
                         _ => {
                             self.current -= 1;
                             break;
@@ -529,17 +1197,107 @@ impl<'a> Parser<'a> {
                 },
                 TokenType::LTEqual => {
                     self.advance();
-                    expr = Node::LessThanOrEqualTo(
+                    let right = self.bit_or()?;
+                    expr = self.node(start, NodeType::LessThanOrEqualTo(
                         Rc::new(expr), 
-                        Rc::new(self.term()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::GTEqual => {
                     self.advance();
-                    expr = Node::GreaterThanOrEqualTo( 
+                    let right = self.bit_or()?;
+                    expr = self.node(start, NodeType::GreaterThanOrEqualTo( 
                         Rc::new(expr), 
-                        Rc::new(self.term()?),
-                    )
+                        Rc::new(right),
+                    ))
+                },
+                _ => break
+            }
+        }
+        return Ok(expr);
+    }
+
+    /// Returns the current bitwise or
+    pub(crate) fn bit_or(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.bit_xor()?;
+        while !self.is_at_end() {
+            match self.curr().token_type {
+                TokenType::Bar => {
+                    self.advance();
+                    let right = self.bit_xor()?;
+                    expr = self.node(start, NodeType::BitOr(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
+                },
+                _ => break
+            }
+        }
+        return Ok(expr);
+    }
+
+    /// Returns the current bitwise xor
+    pub(crate) fn bit_xor(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.bit_and()?;
+        while !self.is_at_end() {
+            match self.curr().token_type {
+                TokenType::Caret => {
+                    self.advance();
+                    let right = self.bit_and()?;
+                    expr = self.node(start, NodeType::BitXor(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
+                },
+                _ => break
+            }
+        }
+        return Ok(expr);
+    }
+
+    /// Returns the current bitwise and
+    pub(crate) fn bit_and(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.shift()?;
+        while !self.is_at_end() {
+            match self.curr().token_type {
+                TokenType::Ampersand => {
+                    self.advance();
+                    let right = self.shift()?;
+                    expr = self.node(start, NodeType::BitAnd(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
+                },
+                _ => break
+            }
+        }
+        return Ok(expr);
+    }
+
+    /// Returns the current bit shift
+    pub(crate) fn shift(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.term()?;
+        while !self.is_at_end() {
+            match self.curr().token_type {
+                TokenType::LShift => {
+                    self.advance();
+                    let right = self.term()?;
+                    expr = self.node(start, NodeType::ShiftLeft(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
+                },
+                TokenType::RShift => {
+                    self.advance();
+                    let right = self.term()?;
+                    expr = self.node(start, NodeType::ShiftRight(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
                 },
                 _ => break
             }
@@ -549,22 +1307,25 @@ impl<'a> Parser<'a> {
 
     /// Returns the current term operation
     pub(crate) fn term(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.factor()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::Plus => {
                     self.advance();
-                    expr = Node::Sum(
+                    let right = self.factor()?;
+                    expr = self.node(start, NodeType::Sum(
                         Rc::new(expr), 
-                        Rc::new(self.factor()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::Dash => {
                     self.advance();
-                    expr = Node::Difference(
+                    let right = self.factor()?;
+                    expr = self.node(start, NodeType::Difference(
                         Rc::new(expr), 
-                        Rc::new(self.factor()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 _ => break
             }
@@ -574,29 +1335,51 @@ impl<'a> Parser<'a> {
 
     /// Returns the current factor operation
     pub(crate) fn factor(&mut self) -> Result<Node, ParserError> {
-        let mut expr = self.unary()?;
+        let start = self.current;
+        let mut expr = self.cast()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::Asterisk => {
                     self.advance();
-                    expr = Node::Product(
+                    let right = self.cast()?;
+                    expr = self.node(start, NodeType::Product(
                         Rc::new(expr), 
-                        Rc::new(self.unary()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::Slash => {
                     self.advance();
-                    expr = Node::Quotient(
+                    let right = self.cast()?;
+                    expr = self.node(start, NodeType::Quotient(
                         Rc::new(expr), 
-                        Rc::new(self.unary()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 TokenType::Perc => {
                     self.advance();
-                    expr = Node::Modulo(
+                    let right = self.cast()?;
+                    expr = self.node(start, NodeType::Modulo(
+                        Rc::new(expr), 
+                        Rc::new(right),
+                    ))
+                },
+                TokenType::DoubleSlash => {
+                    // Followed by a `{` or anything else that can't be divided by, the `//` was most likely meant as a comment.
+                    // The divisor can't take a construct body either, so a header like `if a == b // note {` keeps its block.
+                    let slashes = self.curr().clone();
+                    self.advance();
+                    let divisor = self.curr().clone();
+                    if divisor.token_type == TokenType::LBrace {
+                        return Err(ParserError::CommentAfterOperand(slashes));
+                    }
+                    let right = self.with_no_construct(true, Self::cast).map_err(|err| match err {
+                        ParserError::InvalidToken(token) if Rc::ptr_eq(&token, &divisor) => ParserError::CommentAfterOperand(slashes),
+                        err => err,
+                    })?;
+                    expr = self.node(start, NodeType::FloorQuotient(
                         Rc::new(expr), 
-                        Rc::new(self.unary()?),
-                    )
+                        Rc::new(right),
+                    ))
                 },
                 _ => break
             }
@@ -604,35 +1387,68 @@ impl<'a> Parser<'a> {
         return Ok(expr);
     }
 
+    /// Returns the current cast, which binds tighter than the factors but looser than a unary
+    pub(crate) fn cast(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.unary()?;
+        while !self.is_at_end() && self.curr().token_type == TokenType::Keyword(Keyword::As) {
+            self.advance();
+            let cast_type = self.list_call()?;
+            expr = self.node(start, NodeType::Cast(
+                Rc::new(expr), 
+                Rc::new(cast_type),
+            ))
+        }
+        return Ok(expr);
+    }
+
     /// Returns the current unary operation
     pub(crate) fn unary(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         match self.curr().token_type {
             TokenType::Bang => {
                 self.advance();
-                Ok(Node::Not(Rc::new(self.unary()?)))
+                let expr = self.unary()?;
+                Ok(self.node(start, NodeType::Not(Rc::new(expr))))
             },
             TokenType::Dash => {
                 self.advance();
-                Ok(Node::Negative(Rc::new(self.unary()?)))
+                let expr = self.unary()?;
+                Ok(self.node(start, NodeType::Negative(Rc::new(expr))))
             },
-            _ => self.list_call(),
+            _ => self.exponent(),
+        }
+    }
+
+    /// Returns the current exponent, which binds tighter than a unary on its left and groups to the right
+    pub(crate) fn exponent(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let expr = self.list_call()?;
+        if self.is_at_end() || self.curr().token_type != TokenType::DoubleAsterisk {
+            return Ok(expr);
         }
+        self.advance();
+        let right = self.unary()?;
+        Ok(self.node(start, NodeType::Exponent(
+            Rc::new(expr), 
+            Rc::new(right),
+        )))
     }
 
     /// Returns the current list call
     pub(crate) fn list_call(&mut self) -> Result<Node, ParserError> {
         match self.curr().token_type {
-            TokenType::Ident(_) => (),
+            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(ValuedKeyword::SelfIdentity)) => (),
             _ => return self.primary(),
         }
         let start = self.current;
-        let mut expr = self.ident()?;
+        let mut expr = self.access()?;
         if self.is_at_end() {
             return Ok(expr)
         }
         match self.curr().token_type {
             TokenType::Ident(_) => return Ok(expr),
-            TokenType::LBracket => (),
+            TokenType::LBracket | TokenType::Question => (),
             _ => {
                 self.current = start;
                 return self.primary();
@@ -640,26 +1456,32 @@ impl<'a> Parser<'a> {
         }
         while !self.is_at_end() {
             match &self.curr().token_type {
+                TokenType::Question => {
+                    self.advance();
+                    expr = self.node(start, NodeType::Optional(Rc::new(expr)));
+                },
                 TokenType::LBracket => {
                     self.advance();
                     match self.curr().token_type {
                         TokenType::RBracket => {
-                            expr = Node::ListCall(
-                                Rc::new(expr), 
-                                Rc::new(Node::None)
-                            );
+                            let index = self.node(self.current, NodeType::None);
                             self.advance();
+                            expr = self.node(start, NodeType::ListCall(
+                                Rc::new(expr), 
+                                Rc::new(index)
+                            ));
                         }
                         _ => {
-                            expr = Node::ListCall(
-                                Rc::new(expr), 
-                                Rc::new(self.expression()?)
-                            );
+                            let index = self.with_no_construct(false, Self::expression)?;
                             if let TokenType::RBracket = self.curr().token_type {
                                 self.advance();
                             } else {
                                 return Err(ParserError::MissingBracket(self.curr().clone()))
                             }
+                            expr = self.node(start, NodeType::ListCall(
+                                Rc::new(expr), 
+                                Rc::new(index)
+                            ));
                         }
                     }
                 },
@@ -677,36 +1499,22 @@ impl<'a> Parser<'a> {
             },
             TokenType::Number(_) | TokenType::String(_) | TokenType::Keyword(Keyword::Value(_)) => {
                 self.advance();
-                Ok(Node::Primary(self.prev().clone()))
+                Ok(Node::new(NodeType::Primary(self.prev().clone()), self.prev().range.clone()))
             },
             TokenType::Keyword(Keyword::DFASM) => {
                 Ok(self.inline_dfasm_block()?)
             },
+            TokenType::Template(_) => {
+                self.template()
+            },
             TokenType::LParen => {
-                let start = self.current;
-                self.advance();
-                let expr = match self.curr().token_type {
-                    TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => self.declaration()?,
-                    TokenType::RParen => {
-                        self.advance();
-                        return Ok(Node::None)
-                    },
-                    _ => self.expression()?
-                };
-                match self.curr().token_type {
-                    TokenType::RParen => {
-                        self.advance();
-                        Ok(expr)
-                    },
-                    TokenType::Comma => {
-                        self.current = start;
-                        return self.tuple();
-                    },
-                    _ => Err(ParserError::MissingParenthesis(self.curr().clone()))
-                }
+                self.with_no_construct(false, Self::parenthesized)
             },
             TokenType::LBracket => {
-                self.list()
+                self.with_no_construct(false, Self::list)
+            },
+            TokenType::LBrace => {
+                self.map()
             },
             TokenType::LAngle => {
                 self.vector()
@@ -717,20 +1525,82 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Returns the current parenthesized expression, or the tuple it turns out to be
+    pub(crate) fn parenthesized(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        self.advance();
+        let expr = match self.curr().token_type {
+            TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)) => self.declaration()?,
+            TokenType::RParen => {
+                self.advance();
+                return Ok(self.node(start, NodeType::None))
+            },
+            _ => self.expression()?
+        };
+        match self.curr().token_type {
+            TokenType::RParen => {
+                self.advance();
+                Ok(expr)
+            },
+            TokenType::Comma => {
+                self.current = start;
+                return self.tuple();
+            },
+            _ => Err(ParserError::MissingParenthesis(self.curr().clone()))
+        }
+    }
+
+    /// Returns the current template literal, with its text segments as string primaries
+    pub(crate) fn template(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let template = self.curr().clone();
+        let TokenType::Template(segments) = &template.token_type else {
+            return Err(ParserError::InvalidToken(template.clone()));
+        };
+        self.advance();
+        let mut parts = vec![];
+        for segment in segments {
+            match segment {
+                TemplateSegment::Literal(text) => {
+                    let literal = Rc::new(Token {
+                        token_type: TokenType::String(text.clone()),
+                        range: template.range.clone(),
+                    });
+                    parts.push(Rc::new(Node::new(NodeType::Primary(literal), template.range.clone())));
+                },
+                TemplateSegment::Expression(tokens) => {
+                    if tokens.is_empty() {
+                        return Err(ParserError::InvalidToken(template.clone()));
+                    }
+                    let tokens: Vec<Rc<Token>> = tokens.iter().cloned().map(Rc::new).collect();
+                    let mut parser = Parser::new(&tokens);
+                    let expr = parser.expression()?;
+                    if !parser.is_at_end() {
+                        return Err(ParserError::InvalidToken(parser.curr().clone()));
+                    }
+                    parts.push(Rc::new(expr));
+                }
+            }
+        }
+        Ok(self.node(start, NodeType::Template(parts)))
+    }
+
     /// Returns the current construct expression
     pub(crate) fn construct(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.function_call()?;
         match self.curr().token_type {
-            TokenType::LBrace => {
-                expr = Node::Construct(
-                    Rc::new(expr),
-                    {  // Construct body
-                        self.advance();
-                        Rc::new(self.statement_block()?)
-                    },
-                );
+            TokenType::LBrace if !self.no_construct => {
+                let body = {  // Construct body
+                    self.advance();
+                    self.statement_block()?
+                };
                 expect!(self, TokenType::RBrace);
                 self.advance();
+                expr = self.node(start, NodeType::Construct(
+                    Rc::new(expr),
+                    Rc::new(body),
+                ));
             },
             _ => ()
         }
@@ -739,28 +1609,51 @@ impl<'a> Parser<'a> {
 
     /// Returns the current function call
     pub(crate) fn function_call(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.access()?;
         match self.curr().token_type {
-            TokenType::LParen => expr = Node::FunctionCall(
+            TokenType::LParen => {
+                let params = self.with_no_construct(false, Self::tuple)?;
+                expr = self.node(start, NodeType::FunctionCall(
                     Rc::new(expr),
-                    Rc::new(self.tuple()?),
-                ),
+                    Rc::new(params),
+                ))
+            },
             _ => ()
         }
         return Ok(expr);
     }
 
-    /// Returns the current access chain
+    /// Returns the current access chain, along with the type arguments given to it
     pub(crate) fn access(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         let mut expr = self.ident()?;
         while !self.is_at_end() {
             match self.curr().token_type {
                 TokenType::Dot => {
                     self.advance();
-                    expr = Node::Access(
+                    let field = self.ident()?;
+                    expr = self.node(start, NodeType::Access(
                         Rc::new(expr), 
-                        Rc::new(self.ident()?),
-                    )
+                        Rc::new(field),
+                    ))
+                },
+                TokenType::LAngle => {
+                    // `<` could just as well be a comparison, so it's only taken as type arguments if it parses as them
+                    // and is followed by something a type can be followed by.
+                    let before = self.current;
+                    match self.type_arguments() {
+                        Ok(arguments) if self.split_shift || matches!(self.curr().token_type,
+                            TokenType::Ident(_) | TokenType::LBrace | TokenType::LParen | TokenType::LBracket | TokenType::Question | TokenType::RAngle
+                            | TokenType::RShift | TokenType::RParen | TokenType::Comma | TokenType::Semicolon) => {
+                            expr = self.node(start, NodeType::Generic(Rc::new(expr), arguments));
+                        },
+                        _ => {
+                            self.current = before;
+                            self.split_shift = false;
+                        }
+                    }
+                    break;
                 },
                 _ => break
             }
@@ -768,14 +1661,85 @@ impl<'a> Parser<'a> {
         return Ok(expr);
     }
 
+    /// Returns the current list of type arguments, like `<num, string[]>`
+    pub(crate) fn type_arguments(&mut self) -> Result<Vec<Rc<Node>>, ParserError> {
+        expect!(self, TokenType::LAngle);
+        self.type_argument_depth += 1;
+        let mut arguments = Vec::new();
+        let result = loop {
+            self.advance();
+            match self.type_expression() {
+                Ok(argument) => arguments.push(Rc::new(argument)),
+                Err(e) => break Err(e),
+            }
+            match self.curr().token_type {
+                TokenType::Comma => continue,
+                TokenType::RAngle => {
+                    self.advance();
+                    break Ok(arguments);
+                },
+                // `>>` closes two lists of type arguments at once, the inner one leaves it for the outer one to take
+                TokenType::RShift if self.split_shift => {
+                    self.split_shift = false;
+                    self.advance();
+                    break Ok(arguments);
+                },
+                TokenType::RShift if self.type_argument_depth > 1 => {
+                    self.split_shift = true;
+                    break Ok(arguments);
+                },
+                _ => break Err(ParserError::MissingAngleBracket(self.curr().clone())),
+            }
+        };
+        self.type_argument_depth -= 1;
+        result
+    }
+
+    /// Returns the current type, which is an access chain with type arguments, followed by any number of `[]`, `[type]` or `?`
+    pub(crate) fn type_expression(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let mut expr = self.access()?;
+        while !self.split_shift && matches!(self.curr().token_type, TokenType::LBracket | TokenType::Question) {
+            if self.curr().token_type == TokenType::Question {
+                self.advance();
+                expr = self.node(start, NodeType::Optional(Rc::new(expr)));
+                continue;
+            }
+            self.advance();
+            let index = match self.curr().token_type {
+                TokenType::RBracket => self.node(self.current, NodeType::None),
+                _ => self.type_expression()?,
+            };
+            expect!(self, TokenType::RBracket);
+            self.advance();
+            expr = self.node(start, NodeType::ListCall(
+                Rc::new(expr),
+                Rc::new(index),
+            ));
+        }
+        Ok(expr)
+    }
+
+    /// Returns the name of a struct or function declaration, along with the names of its type parameters
+    pub(crate) fn generic_ident(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        let ident = self.ident()?;
+        if self.curr().token_type != TokenType::LAngle {
+            return Ok(ident);
+        }
+        let parameters = self.type_arguments()?;
+        Ok(self.node(start, NodeType::Generic(Rc::new(ident), parameters)))
+    }
+
     /// Returns the current tuple
     pub(crate) fn tuple(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::LParen);
         self.advance();
         let mut block = vec![];
         if self.curr().token_type == TokenType::RParen {
             self.advance();
-            return Ok(Node::Tuple(block));
+            return Ok(self.node(start, NodeType::Tuple(block)));
         }
         while !self.is_at_end() {
             block.push(Rc::new(self.declaration()?));
@@ -789,17 +1753,18 @@ impl<'a> Parser<'a> {
             }
             self.advance();
         }
-        return Ok(Node::Tuple(block));
+        return Ok(self.node(start, NodeType::Tuple(block)));
     }
 
     /// Returns the current list
     pub(crate) fn list(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::LBracket);
         self.advance();
         let mut block = vec![];
         if self.curr().token_type == TokenType::RBracket {
             self.advance();
-            return Ok(Node::List(block));
+            return Ok(self.node(start, NodeType::List(block)));
         }
         while !self.is_at_end() {
             block.push(Rc::new(self.declaration()?));
@@ -813,11 +1778,41 @@ impl<'a> Parser<'a> {
             }
             self.advance();
         }
-        return Ok(Node::List(block));
+        return Ok(self.node(start, NodeType::List(block)));
+    }
+
+    /// Returns the current map literal
+    pub(crate) fn map(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
+        expect!(self, TokenType::LBrace);
+        self.advance();
+        let mut entries = vec![];
+        if self.curr().token_type == TokenType::RBrace {
+            self.advance();
+            return Ok(self.node(start, NodeType::Map(entries)));
+        }
+        while !self.is_at_end() {
+            let key = self.expression()?;
+            expect!(self, TokenType::Colon);
+            self.advance();
+            let value = self.expression()?;
+            entries.push((Rc::new(key), Rc::new(value)));
+            match self.curr().token_type {
+                TokenType::Comma => (),
+                TokenType::RBrace => {
+                    self.advance();
+                    break;
+                },
+                _ => return Err(ParserError::MissingBrace(self.curr().clone()))
+            }
+            self.advance();
+        }
+        return Ok(self.node(start, NodeType::Map(entries)));
     }
 
     /// Returns the current vector or location
     pub(crate) fn vector(&mut self) -> Result<Node, ParserError> {
+        let start = self.current;
         expect!(self, TokenType::LAngle);
         self.advance();
         let expr1 = self.expression()?;
@@ -830,11 +1825,11 @@ impl<'a> Parser<'a> {
         match self.curr().token_type {
             TokenType::RAngle =>  {
                 self.advance();
-                return Ok(Node::Vector(
+                return Ok(self.node(start, NodeType::Vector(
                     Rc::new(expr1), 
                     Rc::new(expr2), 
                     Rc::new(expr3),
-                ));
+                )));
             },
             TokenType::Comma => (),
             _ => return Err(ParserError::MissingAngleBracket(self.curr().clone()))
@@ -846,19 +1841,19 @@ impl<'a> Parser<'a> {
         let expr5 = self.expression()?;
         expect!(self, TokenType::RAngle);
         self.advance();
-        return Ok(Node::Location(
+        return Ok(self.node(start, NodeType::Location(
             Rc::new(expr1), 
             Rc::new(expr2), 
             Rc::new(expr3),
             Rc::new(expr4), 
             Rc::new(expr5),
-        ));
+        )));
     }
 
     /// Returns the current identifier
     pub(crate) fn ident(&mut self) -> Result<Node, ParserError> {
         expect!(self, TokenType::Ident(_) | TokenType::Keyword(Keyword::Value(_)));
         self.advance();
-        Ok(Node::Primary(self.prev().clone()))
+        Ok(Node::new(NodeType::Primary(self.prev().clone()), self.prev().range.clone()))
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,19 @@
 use crate::parser::*;
 use std::rc::Rc;
-use lexer::types::{Keyword, Token, TokenType, Range, ValuedKeyword};
+use lexer::types::{Keyword, TemplateSegment, Token, TokenType, Range, ValuedKeyword};
+
+// A token on the first line, from column `start` to column `end`
+fn token(token_type: TokenType, start: usize, end: usize) -> Rc<Token> {
+    Rc::new(Token {
+        token_type,
+        range: Range::new((0, start), (0, end)),
+    })
+}
+
+// The primary node of `input[index]`
+fn primary(input: &[Rc<Token>], index: usize) -> Rc<Node> {
+    Rc::new(Node::new(NodeType::Primary(input[index].clone()), input[index].range.clone()))
+}
 
 #[test]
 pub fn expression_test() {
@@ -35,28 +48,28 @@ pub fn expression_test() {
             range: Range::new((0, 12), (0, 12)),
         }),
     ];
-    let expected = Node::Sum(
-        Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Sum(
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Ident("x".to_string()),
             range: Range::new((0, 0), (0, 0)),
-        }))),
-        Rc::new(Node::Product(
-            Rc::new(Node::Quotient(
-                Rc::new(Node::Primary(Rc::new(Token {
-                        token_type: TokenType::Number(8f64),
-                        range: Range::new((0, 4), (0, 4)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 0), (0, 0)))),
+        Rc::new(Node::new(NodeType::Product(
+            Rc::new(Node::new(NodeType::Quotient(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Number(8f64),
+                    range: Range::new((0, 4), (0, 4)),
+                })), Range::new((0, 4), (0, 4)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(2f64),
                     range: Range::new((0, 8), (0, 8)),
-                }))),
-            )),
-            Rc::new(Node::Primary(Rc::new(Token {
-                    token_type: TokenType::Number(4f64),
-                    range: Range::new((0, 12), (0, 12)),
-            }))),
-        )),
-    );
+                })), Range::new((0, 8), (0, 8)))),
+            ), Range::new((0, 4), (0, 8)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Number(4f64),
+                range: Range::new((0, 12), (0, 12)),
+            })), Range::new((0, 12), (0, 12)))),
+        ), Range::new((0, 4), (0, 12)))),
+    ), Range::new((0, 0), (0, 12)));
     let mut parser = Parser::new(&input);
     match parser.expression() {
         Ok(output) => assert_eq!(expected, output),
@@ -116,28 +129,28 @@ pub fn parenthesis_test() {
             range: Range::new((0, 16), (0, 16)),
         }),
     ];
-    let expected = Node::Quotient(
-        Rc::new(Node::Sum(
-            Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Quotient(
+        Rc::new(Node::new(NodeType::Sum(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("x".to_string()),
                 range: Range::new((0, 1), (0, 1)),
-            }))),
-            Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 1), (0, 1)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Number(8f64),
                 range: Range::new((0, 5), (0, 5)),
-            }))),
-        )),
-        Rc::new(Node::Product(
-            Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 5), (0, 5)))),
+        ), Range::new((0, 1), (0, 5)))),
+        Rc::new(Node::new(NodeType::Product(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Number(2f64),
                 range: Range::new((0, 11), (0, 11)),
-            }))),
-            Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 11), (0, 11)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Number(4f64),
                 range: Range::new((0, 15), (0, 15)),
-            }))),
-        ))
-    );
+            })), Range::new((0, 15), (0, 15)))),
+        ), Range::new((0, 11), (0, 15)))),
+    ), Range::new((0, 0), (0, 16)));
     let mut parser = Parser::new(&input);
     match parser.expression() {
         Ok(output) => assert_eq!(expected, output),
@@ -181,20 +194,20 @@ pub fn tuple_test() {
             range: Range::new((0, 13), (0, 13)),
         }),
     ];
-    let expected = Node::Tuple(vec![
-        Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Tuple(vec![
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Ident("x".to_string()),
             range: Range::new((0, 1), (0, 1)),
-        }))),
-        Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 1), (0, 1)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Number(3f64),
             range: Range::new((0, 4), (0, 4)),
-        }))),
-        Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 4), (0, 4)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::String("test".to_string()),
             range: Range::new((0, 7), (0, 12)),
-        }))),
-    ]);
+        })), Range::new((0, 7), (0, 12)))),
+    ]), Range::new((0, 0), (0, 13)));
     let mut parser = Parser::new(&input);
     match parser.expression() {
         Ok(output) => assert_eq!(expected, output),
@@ -230,22 +243,22 @@ pub fn assignment_test() {
             range: Range::new((0, 9), (0, 9)),
         }),
     ];
-    let expected = Node::Assignment(
-        Rc::new(Node::Declaration(
-            Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Assignment(
+        Rc::new(Node::new(NodeType::Declaration(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("num".to_string()),
                 range: Range::new((0, 0), (0, 2)),
-            }))),
-            Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 0), (0, 2)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("x".to_string()),
                 range: Range::new((0, 4), (0, 4)),
-            }))),
-        )),
-        Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 4), (0, 4)))),
+        ), Range::new((0, 0), (0, 4)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Number(5f64),
             range: Range::new((0, 8), (0, 8)),
-        }))),
-    );
+        })), Range::new((0, 8), (0, 8)))),
+    ), Range::new((0, 0), (0, 8)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -354,59 +367,59 @@ pub fn list_test() {
             range: Range::new((1, 15), (1, 15)),
         }),
     ];
-    let expected = Node::Block(vec![
-        Rc::new(Node::Assignment(
-            Rc::new(Node::Declaration(
-                Rc::new(Node::ListCall(
-                    Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::ListCall(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("num".to_string()),
                         range: Range::new((0, 0), (0, 2)),
-                    }))),
-                    Rc::new(Node::None),
-                )),
-                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((0, 0), (0, 2)))),
+                    Rc::new(Node::new(NodeType::None, Range::new((0, 4), (0, 4)))),
+                ), Range::new((0, 0), (0, 4)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("arr".to_string()),
                     range: Range::new((0, 6), (0, 8)),
-                }))),
-            )),
-            Rc::new(Node::List(vec![
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 6), (0, 8)))),
+            ), Range::new((0, 0), (0, 8)))),
+            Rc::new(Node::new(NodeType::List(vec![
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(1f64),
                     range: Range::new((0, 13), (0, 13)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 13), (0, 13)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(2f64),
                     range: Range::new((0, 16), (0, 16)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 16), (0, 16)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(3f64),
                     range: Range::new((0, 19), (0, 19)),
-                }))),
-            ])),
-        )),
-        Rc::new(Node::Assignment(
-            Rc::new(Node::ListCall(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 19), (0, 19)))),
+            ]), Range::new((0, 12), (0, 20)))),
+        ), Range::new((0, 0), (0, 20)))),
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::ListCall(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("arr".to_string()),
                     range: Range::new((1, 0), (1, 2)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 0), (1, 2)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((1, 4), (1, 4)),
-                }))),
-            )),
-            Rc::new(Node::ListCall(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 4), (1, 4)))),
+            ), Range::new((1, 0), (1, 5)))),
+            Rc::new(Node::new(NodeType::ListCall(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("arr".to_string()),
                     range: Range::new((1, 9), (1, 11)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 9), (1, 11)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(2f64),
                     range: Range::new((1, 13), (1, 13)),
-                }))),
-            )),
-        )),
-    ]);
+                })), Range::new((1, 13), (1, 13)))),
+            ), Range::new((1, 9), (1, 14)))),
+        ), Range::new((1, 0), (1, 14)))),
+    ]), Range::new((0, 0), (1, 15)));
     let mut parser = Parser::new(&input);
     match parser.statement_block() {
         Ok(output) => assert_eq!(expected, output),
@@ -547,82 +560,82 @@ pub fn location_test() {
             range: Range::new((0, 42), (0, 42)),
         }),
     ];
-    let expected = Node::Block(vec![
-        Rc::new(Node::Assignment(
-            Rc::new(Node::Declaration(
-                Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("vec".to_string()),
                     range: Range::new((0, 0), (0, 2)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 0), (0, 2)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("spawn".to_string()),
                     range: Range::new((0, 4), (0, 8)),
-                }))),
-            )),
-            Rc::new(Node::Vector(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 4), (0, 8)))),
+            ), Range::new((0, 0), (0, 8)))),
+            Rc::new(Node::new(NodeType::Vector(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 13), (0, 13)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 13), (0, 13)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 16), (0, 16)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 16), (0, 16)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 19), (0, 19)),
-                }))),
-            )),
-        )),
-        Rc::new(Node::Assignment(
-            Rc::new(Node::Declaration(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 19), (0, 19)))),
+            ), Range::new((0, 12), (0, 20)))),
+        ), Range::new((0, 0), (0, 20)))),
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("loc".to_string()),
                     range: Range::new((0, 0), (0, 2)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 0), (0, 2)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("playerLoc".to_string()),
                     range: Range::new((0, 4), (0, 12)),
-                }))),
-            )),
-            Rc::new(Node::Location(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 4), (0, 12)))),
+            ), Range::new((0, 0), (0, 12)))),
+            Rc::new(Node::new(NodeType::Location(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 17), (0, 17)),
-                }))),
-                Rc::new(Node::Product(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 17), (0, 17)))),
+                Rc::new(Node::new(NodeType::Product(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Number(25f64),
                         range: Range::new((0, 20), (0, 21)),
-                    }))),
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((0, 20), (0, 21)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Number(2f64),
                         range: Range::new((0, 25), (0, 25)),
-                    }))),
-                )),
-                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((0, 25), (0, 25)))),
+                ), Range::new((0, 20), (0, 25)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 28), (0, 28)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 28), (0, 28)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(0f64),
                     range: Range::new((0, 31), (0, 31)),
-                }))),
-                Rc::new(Node::FunctionCall(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 31), (0, 31)))),
+                Rc::new(Node::new(NodeType::FunctionCall(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("sin".to_string()),
                         range: Range::new((0, 34), (0, 36)),
-                    }))),
-                    Rc::new(Node::Tuple(vec![
-                        Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((0, 34), (0, 36)))),
+                    Rc::new(Node::new(NodeType::Tuple(vec![
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Number(30f64),
                             range: Range::new((0, 38), (0, 39)),
-                        })))
-                    ]))
-                )),
-            )),
-        )),
-    ]);
+                        })), Range::new((0, 38), (0, 39)))),
+                    ]), Range::new((0, 37), (0, 40)))),
+                ), Range::new((0, 34), (0, 40)))),
+            ), Range::new((0, 16), (0, 41)))),
+        ), Range::new((0, 0), (0, 41)))),
+    ]), Range::new((0, 0), (0, 42)));
     let mut parser = Parser::new(&input);
     match parser.statement_block() {
         Ok(output) => assert_eq!(expected, output),
@@ -671,34 +684,34 @@ pub fn statement_block_test() {
             range: Range::new((1, 15), (1, 15)),
         }),
     ];
-    let expected = Node::Block(vec![
-        Rc::new(Node::Declaration(
-            Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Declaration(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("num".to_string()),
                 range: Range::new((0, 0), (0, 2)),
-            }))),
-            Rc::new(Node::Primary(Rc::new(Token {
-                    token_type: TokenType::Ident("x".to_string()),
-                    range: Range::new((0, 4), (0, 4)),
-            }))),
-        )),
-        Rc::new(Node::Assignment(
-            Rc::new(Node::Declaration(
-                Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 0), (0, 2)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("x".to_string()),
+                range: Range::new((0, 4), (0, 4)),
+            })), Range::new((0, 4), (0, 4)))),
+        ), Range::new((0, 0), (0, 4)))),
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("str".to_string()),
                     range: Range::new((1, 0), (1, 2)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 0), (1, 2)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("y".to_string()),
                     range: Range::new((1, 4), (1, 4)),
-                }))),
-            )),
-            Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 4), (1, 4)))),
+            ), Range::new((1, 0), (1, 4)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::String("hello".to_string()),
                 range: Range::new((1, 8), (1, 14)),
-            }))),
-        )),
-    ]);
+            })), Range::new((1, 8), (1, 14)))),
+        ), Range::new((1, 0), (1, 14)))),
+    ]), Range::new((0, 0), (1, 15)));
     let mut parser = Parser::new(&input);
     match parser.statement_block() {
         Ok(output) => assert_eq!(expected, output),
@@ -776,48 +789,48 @@ pub fn while_test() {
             range: Range::new((2, 0), (2, 0)),
         }),
     ];
-    let expected = Node::While(
-        Rc::new(Node::And(
-            Rc::new(Node::Equal(
-                Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::While(
+        Rc::new(Node::new(NodeType::And(
+            Rc::new(Node::new(NodeType::Equal(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((0, 6), (0, 6)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 6), (0, 6)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(5f64),
                     range: Range::new((0, 11), (0, 11)),
-                }))),
-            )),
-            Rc::new(Node::NotEqual(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 11), (0, 11)))),
+            ), Range::new((0, 6), (0, 11)))),
+            Rc::new(Node::new(NodeType::NotEqual(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Keyword(Keyword::Value(ValuedKeyword::True)),
                     range: Range::new((0, 16), (0, 19)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 16), (0, 19)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Keyword(Keyword::Value(ValuedKeyword::False)),
                     range: Range::new((0, 24), (0, 28)),
-                }))),
-            )),
-        )),
-        Rc::new(Node::Block(vec![
-            Rc::new(Node::Assignment(
-                Rc::new(Node::Declaration(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 24), (0, 28)))),
+            ), Range::new((0, 16), (0, 28)))),
+        ), Range::new((0, 6), (0, 28)))),
+        Rc::new(Node::new(NodeType::Block(vec![
+            Rc::new(Node::new(NodeType::Assignment(
+                Rc::new(Node::new(NodeType::Declaration(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("str".to_string()),
                         range: Range::new((1, 0), (1, 2)),
-                    }))),
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 0), (1, 2)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("y".to_string()),
                         range: Range::new((1, 4), (1, 4)),
-                    }))),
-                )),
-                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 4), (1, 4)))),
+                ), Range::new((1, 0), (1, 4)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("hello".to_string()),
                     range: Range::new((1, 8), (1, 14)),
-                }))),
-            )),
-        ])),
-    );
+                })), Range::new((1, 8), (1, 14)))),
+            ), Range::new((1, 0), (1, 14)))),
+        ]), Range::new((1, 0), (1, 15)))),
+    ), Range::new((0, 0), (2, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -895,48 +908,48 @@ pub fn if_statement_test() {
             range: Range::new((2, 0), (2, 0)),
         }),
     ];
-    let expected = Node::If(
-        Rc::new(Node::And(
-            Rc::new(Node::Equal(
-                Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::If(
+        Rc::new(Node::new(NodeType::And(
+            Rc::new(Node::new(NodeType::Equal(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((0, 3), (0, 3)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 3), (0, 3)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(5f64),
                     range: Range::new((0, 8), (0, 8)),
-                }))),
-            )),
-            Rc::new(Node::NotEqual(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 8), (0, 8)))),
+            ), Range::new((0, 3), (0, 8)))),
+            Rc::new(Node::new(NodeType::NotEqual(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Keyword(Keyword::Value(ValuedKeyword::True)),
                     range: Range::new((0, 13), (0, 16)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 13), (0, 16)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Keyword(Keyword::Value(ValuedKeyword::False)),
                     range: Range::new((0, 21), (0, 25)),
-                }))),
-            )),
-        )),
-        Rc::new(Node::Block(vec![
-            Rc::new(Node::Assignment(
-                Rc::new(Node::Declaration(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 21), (0, 25)))),
+            ), Range::new((0, 13), (0, 25)))),
+        ), Range::new((0, 3), (0, 25)))),
+        Rc::new(Node::new(NodeType::Block(vec![
+            Rc::new(Node::new(NodeType::Assignment(
+                Rc::new(Node::new(NodeType::Declaration(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("str".to_string()),
                         range: Range::new((1, 0), (1, 2)),
-                    }))),
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 0), (1, 2)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("y".to_string()),
                         range: Range::new((1, 4), (1, 4)),
-                    }))),
-                )),
-                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 4), (1, 4)))),
+                ), Range::new((1, 0), (1, 4)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("hello".to_string()),
                     range: Range::new((1, 8), (1, 14)),
-                }))),
-            )),
-        ])),
-    );
+                })), Range::new((1, 8), (1, 14)))),
+            ), Range::new((1, 0), (1, 14)))),
+        ]), Range::new((1, 0), (1, 15)))),
+    ), Range::new((0, 0), (2, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -1048,68 +1061,68 @@ pub fn if_else_test() {
             range: Range::new((4, 0), (4, 0)),
         }),
     ];
-    let expected = Node::Else(
-        Rc::new(Node::If(
-            Rc::new(Node::Equal(
-                Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Else(
+        Rc::new(Node::new(NodeType::If(
+            Rc::new(Node::new(NodeType::Equal(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((0, 3), (0, 3)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 3), (0, 3)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(5f64),
                     range: Range::new((0, 8), (0, 8)),
-                }))),
-            )),
-            Rc::new(Node::Block(vec![
-                Rc::new(Node::Assignment(
-                    Rc::new(Node::Declaration(
-                        Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 8), (0, 8)))),
+            ), Range::new((0, 3), (0, 8)))),
+            Rc::new(Node::new(NodeType::Block(vec![
+                Rc::new(Node::new(NodeType::Assignment(
+                    Rc::new(Node::new(NodeType::Declaration(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("str".to_string()),
                             range: Range::new((1, 0), (1, 2)),
-                        }))),
-                        Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((1, 0), (1, 2)))),
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("y".to_string()),
                             range: Range::new((1, 4), (1, 4)),
-                        }))),
-                    )),
-                    Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((1, 4), (1, 4)))),
+                    ), Range::new((1, 0), (1, 4)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("hello".to_string()),
                         range: Range::new((1, 8), (1, 14)),
-                    }))),
-                )),
-            ])),
-        )),
-        Rc::new(Node::If(
-            Rc::new(Node::GreaterThan(
-                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 8), (1, 14)))),
+                ), Range::new((1, 0), (1, 14)))),
+            ]), Range::new((1, 0), (1, 15)))),
+        ), Range::new((0, 0), (2, 0)))),
+        Rc::new(Node::new(NodeType::If(
+            Rc::new(Node::new(NodeType::GreaterThan(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((2, 10), (2, 10)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((2, 10), (2, 10)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Number(5f64),
                     range: Range::new((2, 14), (2, 14)),
-                }))),
-            )),
-            Rc::new(Node::Block(vec![
-                Rc::new(Node::Assignment(
-                    Rc::new(Node::Declaration(
-                        Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((2, 14), (2, 14)))),
+            ), Range::new((2, 10), (2, 14)))),
+            Rc::new(Node::new(NodeType::Block(vec![
+                Rc::new(Node::new(NodeType::Assignment(
+                    Rc::new(Node::new(NodeType::Declaration(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("str".to_string()),
                             range: Range::new((3, 0), (3, 2)),
-                        }))), 
-                        Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((3, 0), (3, 2)))),
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("y".to_string()),
                             range: Range::new((3, 4), (3, 4)),
-                        }))),
-                    )), 
-                    Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((3, 4), (3, 4)))),
+                    ), Range::new((3, 0), (3, 4)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("evil hello".to_string()),
                         range: Range::new((3, 8), (3, 19)),
-                    }))),
-                ))
-            ])),
-        )),
-    );
+                    })), Range::new((3, 8), (3, 19)))),
+                ), Range::new((3, 0), (3, 19)))),
+            ]), Range::new((3, 0), (3, 20)))),
+        ), Range::new((2, 7), (4, 0)))),
+    ), Range::new((0, 0), (4, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -1188,40 +1201,43 @@ pub fn no_return_function_test() {
             range: Range::new((3, 0), (3, 0)),
         }),
     ];
-    let expected = Node::Func(
-        Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Func(
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Ident("foo".to_string()),
             range: Range::new((0, 5), (0, 7)),
-        }))),
-        Rc::new(Node::Tuple(vec![
-            Rc::new(Node::Declaration(
-                Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 5), (0, 7)))),
+        Rc::new(Node::new(NodeType::Tuple(vec![
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("num".to_string()),
                     range: Range::new((0, 9), (0, 11)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 9), (0, 11)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((0, 13), (0, 13)),
-                }))),
-            )),
-        ])),
-        Rc::new(Node::None),
-        Rc::new(Node::Block(vec![
-            Rc::new(Node::FunctionCall(
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 13), (0, 13)))),
+            ), Range::new((0, 9), (0, 13)))),
+        ]), Range::new((0, 8), (0, 14)))),
+        Rc::new(Node::new(NodeType::None, Range::new((0, 16), (0, 16)))),
+        Rc::new(Node::new(NodeType::Block(vec![
+            Rc::new(Node::new(NodeType::FunctionCall(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("bar".to_string()),
                     range: Range::new((1, 0), (1, 2)),
-                }))),
-                Rc::new(Node::Tuple(vec![
-                    Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((1, 0), (1, 2)))),
+                Rc::new(Node::new(NodeType::Tuple(vec![
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("x".to_string()),
                         range: Range::new((1, 4), (1, 4)),
-                    }))),
-                ])),
-            )),
-            Rc::new(Node::Return(Rc::new(Node::None))),
-        ])),
-    );
+                    })), Range::new((1, 4), (1, 4)))),
+                ]), Range::new((1, 3), (1, 5)))),
+            ), Range::new((1, 0), (1, 5)))),
+            Rc::new(Node::new(NodeType::Return(
+                Rc::new(Node::new(NodeType::None, Range::new((2, 6), (2, 6)))),
+            ), Range::new((2, 0), (2, 5)))),
+        ]), Range::new((1, 0), (2, 6)))),
+        None,
+    ), Range::new((0, 0), (3, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -1311,50 +1327,51 @@ pub fn return_function_test() {
             range: Range::new((2, 0), (2, 0)),
         }),
     ];
-    let expected = Node::Func(
-        Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Func(
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Ident("foo".to_string()),
             range: Range::new((0, 5), (0, 7)),
-        }))),
-        Rc::new(Node::Tuple(vec![
-            Rc::new(Node::Declaration(
-                Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 5), (0, 7)))),
+        Rc::new(Node::new(NodeType::Tuple(vec![
+            Rc::new(Node::new(NodeType::Declaration(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("num".to_string()),
                     range: Range::new((0, 9), (0, 11)),
-                }))),
-                Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 9), (0, 11)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                     token_type: TokenType::Ident("x".to_string()),
                     range: Range::new((0, 13), (0, 13)),
-                }))),
-            ))
-        ])),
-        Rc::new(Node::Primary(Rc::new(Token {
+                })), Range::new((0, 13), (0, 13)))),
+            ), Range::new((0, 9), (0, 13)))),
+        ]), Range::new((0, 8), (0, 14)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
             token_type: TokenType::Ident("num".to_string()),
             range: Range::new((0, 16), (0, 18)),
-        }))),
-        Rc::new(Node::Block(vec![
-            Rc::new(Node::Return(
-                Rc::new(Node::Product(
-                    Rc::new(Node::Primary(Rc::new(Token {
+        })), Range::new((0, 16), (0, 18)))),
+        Rc::new(Node::new(NodeType::Block(vec![
+            Rc::new(Node::new(NodeType::Return(
+                Rc::new(Node::new(NodeType::Product(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("x".to_string()),
                         range: Range::new((1, 7), (1, 7)),
-                    }))),
-                    Rc::new(Node::FunctionCall(
-                        Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 7), (1, 7)))),
+                    Rc::new(Node::new(NodeType::FunctionCall(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("bar".to_string()),
                             range: Range::new((1, 11), (1, 13)),
-                        }))),
-                        Rc::new(Node::Tuple(vec![
-                            Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((1, 11), (1, 13)))),
+                        Rc::new(Node::new(NodeType::Tuple(vec![
+                            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                 token_type: TokenType::Number(2f64),
                                 range: Range::new((1, 15), (1, 15)),
-                            }))),
-                        ])),
-                    )),
-                )),
-            )),
-        ])),
-    );
+                            })), Range::new((1, 15), (1, 15)))),
+                        ]), Range::new((1, 14), (1, 16)))),
+                    ), Range::new((1, 11), (1, 16)))),
+                ), Range::new((1, 7), (1, 16)))),
+            ), Range::new((1, 0), (1, 16)))),
+        ]), Range::new((1, 0), (1, 17)))),
+        None,
+    ), Range::new((0, 0), (2, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement() {
         Ok(output) => assert_eq!(expected, output),
@@ -1620,138 +1637,144 @@ pub fn struct_test() {
             range: Range::new((13, 0), (13, 0)),
         }),
     ];
-    let expected = Node::Block(vec![
-        Rc::new(Node::Struct(
-            Rc::new(Node::Primary(Rc::new(Token {
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Struct(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("foo".to_string()),
                 range: Range::new((0, 7), (0, 9)),
-            }))), 
-            Rc::new(Node::Block(vec![
-                Rc::new(Node::Declaration(
-                    Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((0, 7), (0, 9)))),
+            Rc::new(Node::new(NodeType::Block(vec![
+                Rc::new(Node::new(NodeType::Declaration(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("num".to_string()),
                         range: Range::new((1, 0), (1, 2)),
-                    }))), 
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 0), (1, 2)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("x".to_string()),
                         range: Range::new((1, 4), (1, 4)),
-                    }))),
-                )), 
-                Rc::new(Node::Declaration(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((1, 4), (1, 4)))),
+                ), Range::new((1, 0), (1, 4)))),
+                Rc::new(Node::new(NodeType::Declaration(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("str".to_string()),
                         range: Range::new((2, 0), (2, 2)),
-                    }))), 
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((2, 0), (2, 2)))),
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("y".to_string()),
                         range: Range::new((2, 4), (2, 4)),
-                    }))),
-                )), 
-                Rc::new(Node::Func(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((2, 4), (2, 4)))),
+                ), Range::new((2, 0), (2, 4)))),
+                Rc::new(Node::new(NodeType::Func(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("bar".to_string()),
                         range: Range::new((3, 5), (3, 7)),
-                    }))), 
-                    Rc::new(Node::Tuple(vec![])), 
-                    Rc::new(Node::Tuple(vec![
-                        Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((3, 5), (3, 7)))),
+                    Rc::new(Node::new(NodeType::Tuple(vec![
+                    ]), Range::new((3, 8), (3, 9)))),
+                    Rc::new(Node::new(NodeType::Tuple(vec![
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("num".to_string()),
                             range: Range::new((3, 15), (3, 17)),
-                        }))), 
-                        Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((3, 15), (3, 17)))),
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("str".to_string()),
                             range: Range::new((3, 19), (3, 21)),
-                        }))),
-                    ])), 
-                    Rc::new(Node::Block(vec![
-                        Rc::new(Node::Return(
-                            Rc::new(Node::Tuple(vec![
-                                Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((3, 19), (3, 21)))),
+                    ]), Range::new((3, 14), (3, 22)))),
+                    Rc::new(Node::new(NodeType::Block(vec![
+                        Rc::new(Node::new(NodeType::Return(
+                            Rc::new(Node::new(NodeType::Tuple(vec![
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("x".to_string()),
                                     range: Range::new((4, 8), (4, 8)),
-                                }))), 
-                                Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((4, 8), (4, 8)))),
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("y".to_string()),
                                     range: Range::new((4, 11), (4, 11)),
-                                }))),
-                            ])),
-                        )),
-                    ])),
-                )),
-            ])),
-        )),
-        Rc::new(Node::Func(
-            Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((4, 11), (4, 11)))),
+                            ]), Range::new((4, 7), (4, 12)))),
+                        ), Range::new((4, 0), (4, 12)))),
+                    ]), Range::new((4, 0), (4, 13)))),
+                    None,
+                ), Range::new((3, 0), (5, 0)))),
+            ]), Range::new((1, 0), (5, 0)))),
+            None,
+        ), Range::new((0, 0), (6, 0)))),
+        Rc::new(Node::new(NodeType::Func(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                 token_type: TokenType::Ident("main".to_string()),
                 range: Range::new((7, 5), (7, 8)),
-            }))),
-            Rc::new(Node::Tuple(vec![])), 
-            Rc::new(Node::None),
-            Rc::new(Node::Block(vec![
-                Rc::new(Node::Assignment(
-                    Rc::new(Node::Declaration(
-                        Rc::new(Node::Primary(Rc::new(Token {
+            })), Range::new((7, 5), (7, 8)))),
+            Rc::new(Node::new(NodeType::Tuple(vec![
+            ]), Range::new((7, 9), (7, 10)))),
+            Rc::new(Node::new(NodeType::None, Range::new((7, 12), (7, 12)))),
+            Rc::new(Node::new(NodeType::Block(vec![
+                Rc::new(Node::new(NodeType::Assignment(
+                    Rc::new(Node::new(NodeType::Declaration(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("foo".to_string()),
                             range: Range::new((8, 0), (8, 2)),
-                        }))),
-                        Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((8, 0), (8, 2)))),
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("z".to_string()),
                             range: Range::new((8, 4), (8, 4)),
-                        }))),
-                    )),
-                    Rc::new(Node::Construct(
-                        Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((8, 4), (8, 4)))),
+                    ), Range::new((8, 0), (8, 4)))),
+                    Rc::new(Node::new(NodeType::Construct(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                             token_type: TokenType::Ident("foo".to_string()),
                             range: Range::new((8, 8), (8, 10)),
-                        }))),
-                        Rc::new(Node::Block(vec![
-                            Rc::new(Node::Assignment(
-                                Rc::new(Node::Primary(Rc::new(Token {
+                        })), Range::new((8, 8), (8, 10)))),
+                        Rc::new(Node::new(NodeType::Block(vec![
+                            Rc::new(Node::new(NodeType::Assignment(
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("x".to_string()),
                                     range: Range::new((9, 0), (9, 0)),
-                                }))),
-                                Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((9, 0), (9, 0)))),
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Number(1f64),
                                     range: Range::new((9, 4), (9, 4)),
-                                }))),
-                            )),
-                            Rc::new(Node::Assignment(
-                                Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((9, 4), (9, 4)))),
+                            ), Range::new((9, 0), (9, 4)))),
+                            Rc::new(Node::new(NodeType::Assignment(
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("y".to_string()),
                                     range: Range::new((10, 0), (10, 0)),
-                                }))),
-                                Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((10, 0), (10, 0)))),
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::String("a".to_string()),
                                     range: Range::new((10, 4), (10, 4)),
-                                }))),
-                            )),
-                        ])),
-                    )),
-                )),
-                Rc::new(Node::FunctionCall(
-                    Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((10, 4), (10, 4)))),
+                            ), Range::new((10, 0), (10, 4)))),
+                        ]), Range::new((9, 0), (10, 5)))),
+                    ), Range::new((8, 8), (11, 0)))),
+                ), Range::new((8, 0), (11, 0)))),
+                Rc::new(Node::new(NodeType::FunctionCall(
+                    Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                         token_type: TokenType::Ident("print".to_string()),
                         range: Range::new((12, 0), (12, 4)),
-                    }))),
-                    Rc::new(Node::Tuple(vec![
-                        Rc::new(Node::FunctionCall(
-                            Rc::new(Node::Access(
-                                Rc::new(Node::Primary(Rc::new(Token {
+                    })), Range::new((12, 0), (12, 4)))),
+                    Rc::new(Node::new(NodeType::Tuple(vec![
+                        Rc::new(Node::new(NodeType::FunctionCall(
+                            Rc::new(Node::new(NodeType::Access(
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("z".to_string()),
                                     range: Range::new((12, 6), (12, 6)),
-                                }))),
-                                Rc::new(Node::Primary(Rc::new(Token {
+                                })), Range::new((12, 6), (12, 6)))),
+                                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
                                     token_type: TokenType::Ident("bar".to_string()),
                                     range: Range::new((12, 8), (12, 10)),
-                                }))),
-                            )),
-                            Rc::new(Node::Tuple(vec![])),
-                        )),
-                    ])), 
-                )),
-            ])),
-        )),
-    ]);
+                                })), Range::new((12, 8), (12, 10)))),
+                            ), Range::new((12, 6), (12, 10)))),
+                            Rc::new(Node::new(NodeType::Tuple(vec![
+                            ]), Range::new((12, 11), (12, 12)))),
+                        ), Range::new((12, 6), (12, 12)))),
+                    ]), Range::new((12, 5), (12, 13)))),
+                ), Range::new((12, 0), (12, 13)))),
+            ]), Range::new((8, 0), (12, 14)))),
+            None,
+        ), Range::new((7, 0), (13, 0)))),
+    ]), Range::new((0, 0), (13, 0)));
     let mut parser = Parser::new(&input);
     match parser.statement_block() {
         Ok(output) => assert_eq!(expected, output),
@@ -1763,3 +1786,1066 @@ pub fn struct_test() {
 }
 
 
+
+#[test]
+pub fn truncated_input_test() {
+    // x = 5
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Ident("x".to_string()),
+            range: Range::new((0, 0), (0, 0)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Assign,
+            range: Range::new((0, 2), (0, 2)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(5f64),
+            range: Range::new((0, 4), (0, 4)),
+        }),
+    ];
+    let expected = ParserError::MissingSemicolon(Rc::new(Token {
+        token_type: TokenType::Eof,
+        range: Range::new((0, 5), (0, 5)),
+    }));
+    let mut parser = Parser::new(&input);
+    match parser.parse() {
+        Ok(output) => {
+            dbg!(output);
+            panic!()
+        },
+        Err(e) => assert_eq!(expected, e),
+    }
+}
+
+#[test]
+pub fn error_recovery_test() {
+    // x = ; y = 2; } z = ;
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Ident("x".to_string()),
+            range: Range::new((0, 0), (0, 0)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Assign,
+            range: Range::new((0, 2), (0, 2)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 4), (0, 4)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("y".to_string()),
+            range: Range::new((0, 6), (0, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Assign,
+            range: Range::new((0, 8), (0, 8)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 10), (0, 10)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 11), (0, 11)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 13), (0, 13)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("z".to_string()),
+            range: Range::new((0, 15), (0, 15)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Assign,
+            range: Range::new((0, 17), (0, 17)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 19), (0, 19)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Eof,
+            range: Range::new((0, 20), (0, 20)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("y".to_string()),
+                range: Range::new((0, 6), (0, 6)),
+            })), Range::new((0, 6), (0, 6)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Number(2f64),
+                range: Range::new((0, 10), (0, 10)),
+            })), Range::new((0, 10), (0, 10)))),
+        ), Range::new((0, 6), (0, 10)))),
+    ]), Range::new((0, 0), (0, 19)));
+    let expected_errors = vec![
+        ParserError::InvalidToken(Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 4), (0, 4)),
+        })),
+        ParserError::InvalidStatement(Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 13), (0, 13)),
+        })),
+        ParserError::InvalidToken(Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 19), (0, 19)),
+        })),
+    ];
+    let mut parser = Parser::new(&input);
+    let (output, errors) = parser.parse_recovering();
+    assert_eq!(expected, output);
+    assert_eq!(expected_errors, errors);
+}
+
+#[test]
+pub fn template_test() {
+    // `hp: {x + 1}!`
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Template(vec![
+                TemplateSegment::Literal("hp: ".to_string()),
+                TemplateSegment::Expression(vec![
+                    Token {
+                        token_type: TokenType::Ident("x".to_string()),
+                        range: Range::new((0, 6), (0, 6)),
+                    },
+                    Token {
+                        token_type: TokenType::Plus,
+                        range: Range::new((0, 8), (0, 8)),
+                    },
+                    Token {
+                        token_type: TokenType::Number(1f64),
+                        range: Range::new((0, 10), (0, 10)),
+                    },
+                ]),
+                TemplateSegment::Literal("!".to_string()),
+            ]),
+            range: Range::new((0, 0), (0, 13)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Template(vec![
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+            token_type: TokenType::String("hp: ".to_string()),
+            range: Range::new((0, 0), (0, 13)),
+        })), Range::new((0, 0), (0, 13)))),
+        Rc::new(Node::new(NodeType::Sum(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("x".to_string()),
+                range: Range::new((0, 6), (0, 6)),
+            })), Range::new((0, 6), (0, 6)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Number(1f64),
+                range: Range::new((0, 10), (0, 10)),
+            })), Range::new((0, 10), (0, 10)))),
+        ), Range::new((0, 6), (0, 10)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+            token_type: TokenType::String("!".to_string()),
+            range: Range::new((0, 0), (0, 13)),
+        })), Range::new((0, 0), (0, 13)))),
+    ]), Range::new((0, 0), (0, 13)));
+    let mut parser = Parser::new(&input);
+    match parser.expression() {
+        Ok(output) => assert_eq!(expected, output),
+        Err(e) => {
+            dbg!(e);
+            panic!()
+        }
+    }
+}
+
+#[test]
+pub fn doc_comment_test() {
+    // /// Holds nothing
+    // /// at all.
+    // struct S {}
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Comment("Holds nothing".to_string()),
+            range: Range::new((0, 0), (0, 16)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Comment("at all.".to_string()),
+            range: Range::new((1, 0), (1, 10)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Struct),
+            range: Range::new((2, 0), (2, 5)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("S".to_string()),
+            range: Range::new((2, 7), (2, 7)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((2, 9), (2, 9)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((2, 10), (2, 10)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Struct(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("S".to_string()),
+                range: Range::new((2, 7), (2, 7)),
+            })), Range::new((2, 7), (2, 7)))),
+            Rc::new(Node::new(NodeType::Block(vec![]), Range::new((2, 10), (2, 10)))),
+            Some("Holds nothing\nat all.".to_string()),
+        ), Range::new((2, 0), (2, 10)))),
+    ]), Range::new((2, 0), (2, 10)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn compound_assignment_test() {
+    // x *= 2; i++;
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Ident("x".to_string()),
+            range: Range::new((0, 0), (0, 0)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::AsteriskAssign,
+            range: Range::new((0, 2), (0, 3)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 5), (0, 5)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 6), (0, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("i".to_string()),
+            range: Range::new((0, 8), (0, 8)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Increment,
+            range: Range::new((0, 9), (0, 10)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 11), (0, 11)),
+        }),
+    ];
+    let x = Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+        token_type: TokenType::Ident("x".to_string()),
+        range: Range::new((0, 0), (0, 0)),
+    })), Range::new((0, 0), (0, 0))));
+    let i = Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+        token_type: TokenType::Ident("i".to_string()),
+        range: Range::new((0, 8), (0, 8)),
+    })), Range::new((0, 8), (0, 8))));
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::CompoundAssignment(
+            x.clone(),
+            Rc::new(Node::new(NodeType::Product(
+                x.clone(),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Number(2f64),
+                    range: Range::new((0, 5), (0, 5)),
+                })), Range::new((0, 5), (0, 5)))),
+            ), Range::new((0, 0), (0, 5)))),
+        ), Range::new((0, 0), (0, 5)))),
+        Rc::new(Node::new(NodeType::CompoundAssignment(
+            i.clone(),
+            Rc::new(Node::new(NodeType::Sum(
+                i.clone(),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Number(1f64),
+                    range: Range::new((0, 9), (0, 10)),
+                })), Range::new((0, 9), (0, 10)))),
+            ), Range::new((0, 8), (0, 10)))),
+        ), Range::new((0, 8), (0, 10)))),
+    ]), Range::new((0, 0), (0, 11)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn access_list_call_test() {
+    // b.state[2]
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Ident("b".to_string()),
+            range: Range::new((0, 0), (0, 0)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Dot,
+            range: Range::new((0, 1), (0, 1)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("state".to_string()),
+            range: Range::new((0, 2), (0, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBracket,
+            range: Range::new((0, 7), (0, 7)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 8), (0, 8)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBracket,
+            range: Range::new((0, 9), (0, 9)),
+        }),
+    ];
+    let expected = Node::new(NodeType::ListCall(
+        Rc::new(Node::new(NodeType::Access(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("b".to_string()),
+                range: Range::new((0, 0), (0, 0)),
+            })), Range::new((0, 0), (0, 0)))),
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("state".to_string()),
+                range: Range::new((0, 2), (0, 6)),
+            })), Range::new((0, 2), (0, 6)))),
+        ), Range::new((0, 0), (0, 6)))),
+        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 8), (0, 8)),
+        })), Range::new((0, 8), (0, 8)))),
+    ), Range::new((0, 0), (0, 9)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.expression().unwrap());
+}
+
+#[test]
+pub fn visibility_test() {
+    // /// Hidden.
+    // private struct S {}
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Comment("Hidden.".to_string()),
+            range: Range::new((0, 0), (0, 10)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Private),
+            range: Range::new((1, 0), (1, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Struct),
+            range: Range::new((1, 8), (1, 13)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("S".to_string()),
+            range: Range::new((1, 15), (1, 15)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((1, 17), (1, 17)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((1, 18), (1, 18)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Visibility(
+            Visibility::Private,
+            Rc::new(Node::new(NodeType::Struct(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Ident("S".to_string()),
+                    range: Range::new((1, 15), (1, 15)),
+                })), Range::new((1, 15), (1, 15)))),
+                Rc::new(Node::new(NodeType::Block(vec![]), Range::new((1, 18), (1, 18)))),
+                Some("Hidden.".to_string()),
+            ), Range::new((1, 8), (1, 18)))),
+        ), Range::new((1, 0), (1, 18)))),
+    ]), Range::new((1, 0), (1, 18)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn for_test() {
+    // for i in 0..n step 2 {}
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::For),
+            range: Range::new((0, 0), (0, 2)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("i".to_string()),
+            range: Range::new((0, 4), (0, 4)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::In),
+            range: Range::new((0, 6), (0, 7)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(0f64),
+            range: Range::new((0, 9), (0, 9)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::DotDot,
+            range: Range::new((0, 10), (0, 11)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("n".to_string()),
+            range: Range::new((0, 12), (0, 12)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("step".to_string()),
+            range: Range::new((0, 14), (0, 17)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 19), (0, 19)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((0, 21), (0, 21)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 22), (0, 22)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::For(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("i".to_string()),
+                range: Range::new((0, 4), (0, 4)),
+            })), Range::new((0, 4), (0, 4)))),
+            Rc::new(Node::new(NodeType::Range(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Number(0f64),
+                    range: Range::new((0, 9), (0, 9)),
+                })), Range::new((0, 9), (0, 9)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Ident("n".to_string()),
+                    range: Range::new((0, 12), (0, 12)),
+                })), Range::new((0, 12), (0, 12)))),
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Number(2f64),
+                    range: Range::new((0, 19), (0, 19)),
+                })), Range::new((0, 19), (0, 19)))),
+            ), Range::new((0, 9), (0, 19)))),
+            Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 22), (0, 22)))),
+        ), Range::new((0, 0), (0, 22)))),
+    ]), Range::new((0, 0), (0, 22)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn labeled_loop_test() {
+    // 'outer: while x { break 'outer; continue; }
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Label("outer".to_string()),
+            range: Range::new((0, 0), (0, 5)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Colon,
+            range: Range::new((0, 6), (0, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::While),
+            range: Range::new((0, 8), (0, 12)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("x".to_string()),
+            range: Range::new((0, 14), (0, 14)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((0, 16), (0, 16)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Break),
+            range: Range::new((0, 18), (0, 22)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Label("outer".to_string()),
+            range: Range::new((0, 24), (0, 29)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 30), (0, 30)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Continue),
+            range: Range::new((0, 32), (0, 39)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Semicolon,
+            range: Range::new((0, 40), (0, 40)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 42), (0, 42)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Labeled(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Label("outer".to_string()),
+                range: Range::new((0, 0), (0, 5)),
+            })), Range::new((0, 0), (0, 5)))),
+            Rc::new(Node::new(NodeType::While(
+                Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                    token_type: TokenType::Ident("x".to_string()),
+                    range: Range::new((0, 14), (0, 14)),
+                })), Range::new((0, 14), (0, 14)))),
+                Rc::new(Node::new(NodeType::Block(vec![
+                    Rc::new(Node::new(NodeType::Break(
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                            token_type: TokenType::Label("outer".to_string()),
+                            range: Range::new((0, 24), (0, 29)),
+                        })), Range::new((0, 24), (0, 29)))),
+                    ), Range::new((0, 18), (0, 29)))),
+                    Rc::new(Node::new(NodeType::Continue(
+                        Rc::new(Node::new(NodeType::None, Range::new((0, 40), (0, 40)))),
+                    ), Range::new((0, 32), (0, 39)))),
+                ]), Range::new((0, 18), (0, 40)))),
+            ), Range::new((0, 8), (0, 42)))),
+        ), Range::new((0, 0), (0, 42)))),
+    ]), Range::new((0, 0), (0, 42)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn match_test() {
+    // match x { 1 | 2 => {}, _ => {} }
+    let input = [
+        Rc::new(Token {
+            token_type: TokenType::Keyword(Keyword::Match),
+            range: Range::new((0, 0), (0, 4)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("x".to_string()),
+            range: Range::new((0, 6), (0, 6)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((0, 8), (0, 8)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(1f64),
+            range: Range::new((0, 10), (0, 10)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Bar,
+            range: Range::new((0, 12), (0, 12)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Number(2f64),
+            range: Range::new((0, 14), (0, 14)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::FatArrow,
+            range: Range::new((0, 16), (0, 17)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((0, 19), (0, 19)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 20), (0, 20)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Comma,
+            range: Range::new((0, 21), (0, 21)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::Ident("_".to_string()),
+            range: Range::new((0, 23), (0, 23)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::FatArrow,
+            range: Range::new((0, 25), (0, 26)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::LBrace,
+            range: Range::new((0, 28), (0, 28)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 29), (0, 29)),
+        }),
+        Rc::new(Token {
+            token_type: TokenType::RBrace,
+            range: Range::new((0, 31), (0, 31)),
+        }),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Match(
+            Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                token_type: TokenType::Ident("x".to_string()),
+                range: Range::new((0, 6), (0, 6)),
+            })), Range::new((0, 6), (0, 6)))),
+            vec![
+                Rc::new(Node::new(NodeType::MatchArm(
+                    vec![
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                            token_type: TokenType::Number(1f64),
+                            range: Range::new((0, 10), (0, 10)),
+                        })), Range::new((0, 10), (0, 10)))),
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                            token_type: TokenType::Number(2f64),
+                            range: Range::new((0, 14), (0, 14)),
+                        })), Range::new((0, 14), (0, 14)))),
+                    ],
+                    Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 20), (0, 20)))),
+                ), Range::new((0, 10), (0, 20)))),
+                Rc::new(Node::new(NodeType::MatchArm(
+                    vec![
+                        Rc::new(Node::new(NodeType::Primary(Rc::new(Token {
+                            token_type: TokenType::Ident("_".to_string()),
+                            range: Range::new((0, 23), (0, 23)),
+                        })), Range::new((0, 23), (0, 23)))),
+                    ],
+                    Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 29), (0, 29)))),
+                ), Range::new((0, 23), (0, 29)))),
+            ],
+        ), Range::new((0, 0), (0, 31)))),
+    ]), Range::new((0, 0), (0, 31)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn bitwise_exponent_test() {
+    // a | b & c << -d ** 2
+    let input = [
+        token(TokenType::Ident("a".to_string()), 0, 0),
+        token(TokenType::Bar, 2, 2),
+        token(TokenType::Ident("b".to_string()), 4, 4),
+        token(TokenType::Ampersand, 6, 6),
+        token(TokenType::Ident("c".to_string()), 8, 8),
+        token(TokenType::LShift, 10, 11),
+        token(TokenType::Dash, 13, 13),
+        token(TokenType::Ident("d".to_string()), 14, 14),
+        token(TokenType::DoubleAsterisk, 16, 17),
+        token(TokenType::Number(2f64), 19, 19),
+    ];
+    let expected = Node::new(NodeType::BitOr(
+        primary(&input, 0),
+        Rc::new(Node::new(NodeType::BitAnd(
+            primary(&input, 2),
+            Rc::new(Node::new(NodeType::ShiftLeft(
+                primary(&input, 4),
+                Rc::new(Node::new(NodeType::Negative(
+                    Rc::new(Node::new(NodeType::Exponent(
+                        primary(&input, 7),
+                        primary(&input, 9),
+                    ), Range::new((0, 14), (0, 19)))),
+                ), Range::new((0, 13), (0, 19)))),
+            ), Range::new((0, 8), (0, 19)))),
+        ), Range::new((0, 4), (0, 19)))),
+    ), Range::new((0, 0), (0, 19)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.expression().unwrap());
+}
+
+#[test]
+pub fn cast_test() {
+    // x as num[] * 2
+    let input = [
+        token(TokenType::Ident("x".to_string()), 0, 0),
+        token(TokenType::Keyword(Keyword::As), 2, 3),
+        token(TokenType::Ident("num".to_string()), 5, 7),
+        token(TokenType::LBracket, 8, 8),
+        token(TokenType::RBracket, 9, 9),
+        token(TokenType::Asterisk, 11, 11),
+        token(TokenType::Number(2f64), 13, 13),
+    ];
+    let expected = Node::new(NodeType::Product(
+        Rc::new(Node::new(NodeType::Cast(
+            primary(&input, 0),
+            Rc::new(Node::new(NodeType::ListCall(
+                primary(&input, 2),
+                Rc::new(Node::new(NodeType::None, Range::new((0, 9), (0, 9)))),
+            ), Range::new((0, 5), (0, 9)))),
+        ), Range::new((0, 0), (0, 9)))),
+        primary(&input, 6),
+    ), Range::new((0, 0), (0, 13)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.expression().unwrap());
+}
+
+#[test]
+pub fn let_test() {
+    // let x = 1;
+    let input = [
+        token(TokenType::Keyword(Keyword::Let), 0, 2),
+        token(TokenType::Ident("x".to_string()), 4, 4),
+        token(TokenType::Assign, 6, 6),
+        token(TokenType::Number(1f64), 8, 8),
+        token(TokenType::Semicolon, 9, 9),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Assignment(
+            Rc::new(Node::new(NodeType::Declaration(
+                primary(&input, 0),
+                primary(&input, 1),
+            ), Range::new((0, 0), (0, 4)))),
+            primary(&input, 3),
+        ), Range::new((0, 0), (0, 8)))),
+    ]), Range::new((0, 0), (0, 9)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn const_test() {
+    // const num x = 1;
+    let input = [
+        token(TokenType::Keyword(Keyword::Const), 0, 4),
+        token(TokenType::Ident("num".to_string()), 6, 8),
+        token(TokenType::Ident("x".to_string()), 10, 10),
+        token(TokenType::Assign, 12, 12),
+        token(TokenType::Number(1f64), 14, 14),
+        token(TokenType::Semicolon, 15, 15),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Const(
+            Rc::new(Node::new(NodeType::Declaration(
+                primary(&input, 1),
+                primary(&input, 2),
+            ), Range::new((0, 6), (0, 10)))),
+            primary(&input, 4),
+        ), Range::new((0, 0), (0, 14)))),
+    ]), Range::new((0, 0), (0, 15)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn const_func_test() {
+    // const func f() -> num { return 1; }
+    let input = [
+        token(TokenType::Keyword(Keyword::Const), 0, 4),
+        token(TokenType::Keyword(Keyword::Func), 6, 9),
+        token(TokenType::Ident("f".to_string()), 11, 11),
+        token(TokenType::LParen, 12, 12),
+        token(TokenType::RParen, 13, 13),
+        token(TokenType::Arrow, 15, 16),
+        token(TokenType::Ident("num".to_string()), 18, 20),
+        token(TokenType::LBrace, 22, 22),
+        token(TokenType::Keyword(Keyword::Return), 24, 29),
+        token(TokenType::Number(1f64), 31, 31),
+        token(TokenType::Semicolon, 32, 32),
+        token(TokenType::RBrace, 34, 34),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::ConstFunc(
+            Rc::new(Node::new(NodeType::Func(
+                primary(&input, 2),
+                Rc::new(Node::new(NodeType::Tuple(vec![]), Range::new((0, 12), (0, 13)))),
+                primary(&input, 6),
+                Rc::new(Node::new(NodeType::Block(vec![
+                    Rc::new(Node::new(NodeType::Return(primary(&input, 9)), Range::new((0, 24), (0, 31)))),
+                ]), Range::new((0, 24), (0, 32)))),
+                None,
+            ), Range::new((0, 6), (0, 34)))),
+        ), Range::new((0, 0), (0, 34)))),
+    ]), Range::new((0, 0), (0, 34)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn import_test() {
+    // from a.b import c as d, e;
+    let input = [
+        token(TokenType::Keyword(Keyword::From), 0, 3),
+        token(TokenType::Ident("a".to_string()), 5, 5),
+        token(TokenType::Dot, 6, 6),
+        token(TokenType::Ident("b".to_string()), 7, 7),
+        token(TokenType::Keyword(Keyword::Import), 9, 14),
+        token(TokenType::Ident("c".to_string()), 16, 16),
+        token(TokenType::Keyword(Keyword::As), 18, 19),
+        token(TokenType::Ident("d".to_string()), 21, 21),
+        token(TokenType::Comma, 22, 22),
+        token(TokenType::Ident("e".to_string()), 24, 24),
+        token(TokenType::Semicolon, 25, 25),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::FromImport(
+            Rc::new(Node::new(NodeType::Access(primary(&input, 1), primary(&input, 3)), Range::new((0, 5), (0, 7)))),
+            vec![
+                Rc::new(Node::new(NodeType::Import(primary(&input, 5), primary(&input, 7)), Range::new((0, 16), (0, 21)))),
+                Rc::new(Node::new(NodeType::Import(
+                    primary(&input, 9),
+                    Rc::new(Node::new(NodeType::None, Range::new((0, 25), (0, 25)))),
+                ), Range::new((0, 24), (0, 24)))),
+            ],
+        ), Range::new((0, 0), (0, 24)))),
+    ]), Range::new((0, 0), (0, 25)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn generic_test() {
+    // Box<Box<num>> b;
+    let input = [
+        token(TokenType::Ident("Box".to_string()), 0, 2),
+        token(TokenType::LAngle, 3, 3),
+        token(TokenType::Ident("Box".to_string()), 4, 6),
+        token(TokenType::LAngle, 7, 7),
+        token(TokenType::Ident("num".to_string()), 8, 10),
+        token(TokenType::RShift, 11, 12),
+        token(TokenType::Ident("b".to_string()), 14, 14),
+        token(TokenType::Semicolon, 15, 15),
+    ];
+    let expected = Node::new(NodeType::Block(vec![
+        Rc::new(Node::new(NodeType::Declaration(
+            Rc::new(Node::new(NodeType::Generic(
+                primary(&input, 0),
+                vec![Rc::new(Node::new(NodeType::Generic(primary(&input, 2), vec![primary(&input, 4)]), Range::new((0, 4), (0, 10))))],
+            ), Range::new((0, 0), (0, 12)))),
+            primary(&input, 6),
+        ), Range::new((0, 0), (0, 14)))),
+    ]), Range::new((0, 0), (0, 15)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.parse().unwrap());
+}
+
+#[test]
+pub fn map_test() {
+    // m = { "a": 1 };
+    let input = [
+        token(TokenType::Ident("m".to_string()), 0, 0),
+        token(TokenType::Assign, 2, 2),
+        token(TokenType::LBrace, 4, 4),
+        token(TokenType::String("a".to_string()), 6, 8),
+        token(TokenType::Colon, 9, 9),
+        token(TokenType::Number(1f64), 11, 11),
+        token(TokenType::RBrace, 13, 13),
+        token(TokenType::Semicolon, 14, 14),
+    ];
+    let expected = Node::new(NodeType::Assignment(
+        primary(&input, 0),
+        Rc::new(Node::new(NodeType::Map(vec![(primary(&input, 3), primary(&input, 5))]), Range::new((0, 4), (0, 13)))),
+    ), Range::new((0, 0), (0, 13)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+}
+
+#[test]
+pub fn optional_test() {
+    // num? x = a ?? none;
+    let input = [
+        token(TokenType::Ident("num".to_string()), 0, 2),
+        token(TokenType::Question, 3, 3),
+        token(TokenType::Ident("x".to_string()), 5, 5),
+        token(TokenType::Assign, 7, 7),
+        token(TokenType::Ident("a".to_string()), 9, 9),
+        token(TokenType::DoubleQuestion, 11, 12),
+        token(TokenType::Keyword(Keyword::Value(ValuedKeyword::None)), 14, 17),
+        token(TokenType::Semicolon, 18, 18),
+    ];
+    let expected = Node::new(NodeType::Assignment(
+        Rc::new(Node::new(NodeType::Declaration(
+            Rc::new(Node::new(NodeType::Optional(primary(&input, 0)), Range::new((0, 0), (0, 3)))),
+            primary(&input, 2),
+        ), Range::new((0, 0), (0, 5)))),
+        Rc::new(Node::new(NodeType::Coalesce(primary(&input, 4), primary(&input, 6)), Range::new((0, 9), (0, 17)))),
+    ), Range::new((0, 0), (0, 17)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+}
+
+#[test]
+pub fn enum_test() {
+    // enum S { A, B(num n) }
+    let input = [
+        token(TokenType::Keyword(Keyword::Enum), 0, 3),
+        token(TokenType::Ident("S".to_string()), 5, 5),
+        token(TokenType::LBrace, 7, 7),
+        token(TokenType::Ident("A".to_string()), 9, 9),
+        token(TokenType::Comma, 10, 10),
+        token(TokenType::Ident("B".to_string()), 12, 12),
+        token(TokenType::LParen, 13, 13),
+        token(TokenType::Ident("num".to_string()), 14, 16),
+        token(TokenType::Ident("n".to_string()), 18, 18),
+        token(TokenType::RParen, 19, 19),
+        token(TokenType::RBrace, 21, 21),
+    ];
+    let expected = Node::new(NodeType::Enum(
+        primary(&input, 1),
+        vec![
+            Rc::new(Node::new(NodeType::EnumVariant(
+                primary(&input, 3),
+                Rc::new(Node::new(NodeType::None, Range::new((0, 10), (0, 10)))),
+            ), Range::new((0, 9), (0, 9)))),
+            Rc::new(Node::new(NodeType::EnumVariant(
+                primary(&input, 5),
+                Rc::new(Node::new(NodeType::Tuple(vec![
+                    Rc::new(Node::new(NodeType::Declaration(primary(&input, 7), primary(&input, 8)), Range::new((0, 14), (0, 18)))),
+                ]), Range::new((0, 13), (0, 19)))),
+            ), Range::new((0, 12), (0, 19)))),
+        ],
+        None,
+    ), Range::new((0, 0), (0, 21)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+}
+
+#[test]
+pub fn comment_after_operand_test() {
+    // while running // poll {}
+    let input = [
+        token(TokenType::Keyword(Keyword::While), 0, 4),
+        token(TokenType::Ident("running".to_string()), 6, 12),
+        token(TokenType::DoubleSlash, 14, 15),
+        token(TokenType::Ident("poll".to_string()), 17, 20),
+        token(TokenType::LBrace, 22, 22),
+        token(TokenType::RBrace, 23, 23),
+    ];
+    let expected = Node::new(NodeType::While(
+        Rc::new(Node::new(NodeType::FloorQuotient(
+            primary(&input, 1),
+            primary(&input, 3),
+        ), Range::new((0, 6), (0, 20)))),
+        Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 23), (0, 23)))),
+    ), Range::new((0, 0), (0, 23)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+
+    // if a == b // note {}
+    let input = [
+        token(TokenType::Keyword(Keyword::If), 0, 1),
+        token(TokenType::Ident("a".to_string()), 3, 3),
+        token(TokenType::Equal, 5, 6),
+        token(TokenType::Ident("b".to_string()), 8, 8),
+        token(TokenType::DoubleSlash, 10, 11),
+        token(TokenType::Ident("note".to_string()), 13, 16),
+        token(TokenType::LBrace, 18, 18),
+        token(TokenType::RBrace, 19, 19),
+    ];
+    let expected = Node::new(NodeType::If(
+        Rc::new(Node::new(NodeType::Equal(
+            primary(&input, 1),
+            Rc::new(Node::new(NodeType::FloorQuotient(
+                primary(&input, 3),
+                primary(&input, 5),
+            ), Range::new((0, 8), (0, 16)))),
+        ), Range::new((0, 3), (0, 16)))),
+        Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 19), (0, 19)))),
+    ), Range::new((0, 0), (0, 19)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+
+    // func f() -> num // note {}
+    let input = [
+        token(TokenType::Keyword(Keyword::Func), 0, 3),
+        token(TokenType::Ident("f".to_string()), 5, 5),
+        token(TokenType::LParen, 6, 6),
+        token(TokenType::RParen, 7, 7),
+        token(TokenType::Arrow, 9, 10),
+        token(TokenType::Ident("num".to_string()), 12, 14),
+        token(TokenType::DoubleSlash, 16, 17),
+        token(TokenType::Ident("note".to_string()), 19, 22),
+        token(TokenType::LBrace, 24, 24),
+        token(TokenType::RBrace, 25, 25),
+    ];
+    let mut parser = Parser::new(&input);
+    assert_eq!(Err(ParserError::CommentAfterOperand(input[6].clone())), parser.statement());
+
+    // if a == b // {}
+    let input = [
+        token(TokenType::Keyword(Keyword::If), 0, 1),
+        token(TokenType::Ident("a".to_string()), 3, 3),
+        token(TokenType::Equal, 5, 6),
+        token(TokenType::Ident("b".to_string()), 8, 8),
+        token(TokenType::DoubleSlash, 10, 11),
+        token(TokenType::LBrace, 13, 13),
+        token(TokenType::RBrace, 14, 14),
+    ];
+    let mut parser = Parser::new(&input);
+    assert_eq!(Err(ParserError::CommentAfterOperand(input[4].clone())), parser.statement());
+
+    // x = a // ;
+    let input = [
+        token(TokenType::Ident("x".to_string()), 0, 0),
+        token(TokenType::Assign, 2, 2),
+        token(TokenType::Ident("a".to_string()), 4, 4),
+        token(TokenType::DoubleSlash, 6, 7),
+        token(TokenType::Semicolon, 9, 9),
+    ];
+    let mut parser = Parser::new(&input);
+    assert_eq!(Err(ParserError::CommentAfterOperand(input[3].clone())), parser.statement());
+}
+
+#[test]
+pub fn header_construct_test() {
+    // while check(P { x = 1; }) {}
+    let input = [
+        token(TokenType::Keyword(Keyword::While), 0, 4),
+        token(TokenType::Ident("check".to_string()), 6, 10),
+        token(TokenType::LParen, 11, 11),
+        token(TokenType::Ident("P".to_string()), 12, 12),
+        token(TokenType::LBrace, 14, 14),
+        token(TokenType::Ident("x".to_string()), 16, 16),
+        token(TokenType::Assign, 18, 18),
+        token(TokenType::Number(1f64), 20, 20),
+        token(TokenType::Semicolon, 21, 21),
+        token(TokenType::RBrace, 23, 23),
+        token(TokenType::RParen, 24, 24),
+        token(TokenType::LBrace, 26, 26),
+        token(TokenType::RBrace, 27, 27),
+    ];
+    let expected = Node::new(NodeType::While(
+        Rc::new(Node::new(NodeType::FunctionCall(
+            primary(&input, 1),
+            Rc::new(Node::new(NodeType::Tuple(vec![
+                Rc::new(Node::new(NodeType::Construct(
+                    primary(&input, 3),
+                    Rc::new(Node::new(NodeType::Block(vec![
+                        Rc::new(Node::new(NodeType::Assignment(
+                            primary(&input, 5),
+                            primary(&input, 7),
+                        ), Range::new((0, 16), (0, 20)))),
+                    ]), Range::new((0, 16), (0, 21)))),
+                ), Range::new((0, 12), (0, 23)))),
+            ]), Range::new((0, 11), (0, 24)))),
+        ), Range::new((0, 6), (0, 24)))),
+        Rc::new(Node::new(NodeType::Block(vec![]), Range::new((0, 27), (0, 27)))),
+    ), Range::new((0, 0), (0, 27)));
+    let mut parser = Parser::new(&input);
+    assert_eq!(expected, parser.statement().unwrap());
+
+    // Inside of parentheses, brackets and indexes a `{` can't open the body, so it's a construct again
+    let parse = |source: &str| -> Node {
+        let tokens: Vec<Rc<Token>> = lexer::Lexer::new(source).map(|token| Rc::new(token.unwrap())).collect();
+        Parser::new(&tokens).statement().unwrap()
+    };
+    let NodeType::For(_, iterated, _) = parse("for p in make(Point { x = 1; }) {}").node_type else { panic!("Expected a for loop") };
+    let NodeType::FunctionCall(_, arguments) = &iterated.node_type else { panic!("Expected a call") };
+    let NodeType::Tuple(arguments) = &arguments.node_type else { panic!("Expected arguments") };
+    assert!(matches!(arguments[0].node_type, NodeType::Construct(..)));
+
+    let NodeType::Match(value, _) = parse("match f(P { x = 1; }) {}").node_type else { panic!("Expected a match") };
+    assert!(matches!(value.node_type, NodeType::FunctionCall(..)));
+
+    let NodeType::While(condition, _) = parse("while (P { x = 1; }) == p {}").node_type else { panic!("Expected a while loop") };
+    assert!(matches!(condition.node_type, NodeType::Equal(..)));
+
+    let NodeType::While(condition, _) = parse("while contains([P { x = 1; }], p) {}").node_type else { panic!("Expected a while loop") };
+    assert!(matches!(condition.node_type, NodeType::FunctionCall(..)));
+
+    let NodeType::While(condition, _) = parse("while points[index(P { x = 1; })] {}").node_type else { panic!("Expected a while loop") };
+    assert!(matches!(condition.node_type, NodeType::ListCall(..)));
+
+    // Once the parentheses close, `ident {` is the loop body again
+    let NodeType::While(condition, body) = parse("while check(P { x = 1; }) == ready {}").node_type else { panic!("Expected a while loop") };
+    assert!(matches!(condition.node_type, NodeType::Equal(..)));
+    assert_eq!(body.node_type, NodeType::Block(vec![]));
+}
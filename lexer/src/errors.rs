@@ -13,4 +13,10 @@ pub enum LexerErrorKind {
     InvalidCharacter,
     #[error("unterminated string")]
     UnterminatedString,
+    #[error("malformed number")]
+    MalformedNumber,
+    #[error("unknown escape sequence")]
+    UnknownEscape,
+    #[error("invalid unicode escape")]
+    InvalidUnicodeEscape,
 }
@@ -1,9 +1,10 @@
 use std::{iter::Peekable, str::Chars};
 
 use errors::{LexerError, LexerErrorKind};
-use types::{Keyword, Position, Range, Token, TokenType, ValuedKeyword};
+use types::{Keyword, Position, Range, TemplateSegment, Token, TokenType, ValuedKeyword};
 
-mod errors;
+pub mod errors;
+pub mod lossless;
 pub mod types;
 pub use compiler;
 
@@ -14,6 +15,9 @@ pub struct Lexer<'a> {
     current_char: char,
     position: Position,
 
+    /// How many bytes of the input have been consumed so far.
+    offset: usize,
+
     /// Used to ensure that when there is a new line, we do not increment
     /// [Self.position.line](Position::line) right away. This way, we can have the position start
     /// at 0 and not 1
@@ -24,19 +28,35 @@ pub struct Lexer<'a> {
     
     /// Used to denote whether the lexer is currently inside inline dfasm.
     inline_dfasm: bool,
+
+    /// Used to make sure the [TokenType::Eof] token is only given out once.
+    reached_end: bool,
+
+    /// Used to tell floor division apart from comments, `//` right after an operand is [TokenType::DoubleSlash].
+    /// Whitespace, line breaks included, doesn't reset it, so a comment can only follow a token that doesn't end an operand,
+    /// like `;`, `{`, `}` or `,`. Whatever follows the `//` on that line is then lexed as the divisor.
+    after_operand: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::new_at(input, Position { line: 0, char: 0 })
+    }
+
+    /// Creates a lexer whose first character sits at `position`, used for code embedded in other code.
+    pub fn new_at(input: &'a str, position: Position) -> Self {
         Self {
             input: input.chars().peekable(),
-            position: Position { line: 0, char: 0 },
+            position,
             // Initialze as '\0', it gets set right when the lexer starts so it doesn't really
             // matter
             current_char: '\0',
+            offset: 0,
             started_line: true,
             dfasm_keyword_met: false,
             inline_dfasm: false,
+            reached_end: false,
+            after_operand: false,
         }
     }
 
@@ -74,6 +94,7 @@ impl<'a> Lexer<'a> {
         }
         self.started_line = false;
         self.current_char = self.input.next()?;
+        self.offset += self.current_char.len_utf8();
         if self.current_char == '\n' {
             self.position.line += 1;
             self.position.char = 0;
@@ -122,15 +143,29 @@ impl<'a> Lexer<'a> {
         match input {
             "func" => Some(Keyword::Func),
             "struct" => Some(Keyword::Struct),
+            "enum" => Some(Keyword::Enum),
             "domain" => Some(Keyword::Domain),
+            "let" => Some(Keyword::Let),
+            "const" => Some(Keyword::Const),
+
+            "package" => Some(Keyword::Package),
+            "import" => Some(Keyword::Import),
+            "from" => Some(Keyword::From),
+
+            "pub" => Some(Keyword::Pub),
+            "private" => Some(Keyword::Private),
 
             "if" => Some(Keyword::If),
             "else" => Some(Keyword::Else),
             "for" => Some(Keyword::For),
+            "in" => Some(Keyword::In),
+            "as" => Some(Keyword::As),
             "while" => Some(Keyword::While),
+            "match" => Some(Keyword::Match),
             
             "return" => Some(Keyword::Return),
             "break" => Some(Keyword::Break),
+            "continue" => Some(Keyword::Continue),
 
             "dfasm" => Some(Keyword::DFASM),
 
@@ -138,93 +173,396 @@ impl<'a> Lexer<'a> {
             "self" => Some(Keyword::Value(ValuedKeyword::SelfIdentity)),
             "event" => Some(Keyword::Value(ValuedKeyword::Event)),
             "false" => Some(Keyword::Value(ValuedKeyword::False)),
+            "none" => Some(Keyword::Value(ValuedKeyword::None)),
 
             _ => None,
         }
     }
 
+    /// Parse a loop label, like `'outer`
+    fn parse_label(&mut self) -> Result<Token, LexerError> {
+        let start = self.position.clone();
+        let mut label = String::new();
+        while let Some(char @ ('a'..='z' | 'A'..='Z' | '0'..='9' | '_')) = self.input.peek().copied() {
+            label.push(char);
+            let _ = self.next_char();
+        }
+        if label.is_empty() {
+            return Err(self.err(start, LexerErrorKind::InvalidCharacter));
+        }
+        Ok(Token {
+            range: Range {
+                start,
+                end: self.position.clone(),
+            },
+            token_type: TokenType::Label(label),
+        })
+    }
+
+    /// Parse a number, be it decimal, hexadecimal (`0x`) or binary (`0b`)
     fn parse_number(&mut self) -> Result<Token, LexerError> {
         let start = self.position.clone();
+        let radix = match (self.current_char, self.input.peek()) {
+            ('0', Some('x' | 'X')) => 16,
+            ('0', Some('b' | 'B')) => 2,
+            _ => 10,
+        };
+        let number = if radix == 10 {
+            self.parse_decimal_digits()
+        } else {
+            // Skip over the prefix
+            let _ = self.next_char();
+            self.parse_radix_digits(radix)
+        };
+
+        // A number running straight into an ident or another decimal point is malformed, so eat
+        // all of it to have the error cover the whole thing.
+        if self.number_continues() {
+            while self.number_continues() {
+                let _ = self.next_char();
+            }
+            return Err(self.err(start, LexerErrorKind::MalformedNumber));
+        }
+        let Some(number) = number else {
+            return Err(self.err(start, LexerErrorKind::MalformedNumber));
+        };
+        Ok(Token {
+            range: Range {
+                start: start.clone(),
+                end: self.position.clone(),
+            },
+            token_type: TokenType::Number(number),
+        })
+    }
+
+    /// Parse the digits of a decimal number, starting with the current character.
+    ///
+    /// Supports `_` separators, a decimal point and an exponent (`1.5e3`, `2e-4`).
+    fn parse_decimal_digits(&mut self) -> Option<f64> {
         let mut decimal = false;
+        let mut exponent = false;
         let mut string = String::from(self.current_char);
 
         loop {
-            let Some(char) = self.input.peek() else {
+            let Some(char) = self.input.peek().copied() else {
                 break;
             };
 
             match char {
-                '0'..='9' => string.push(*char),
-                '.' if !decimal => {
+                '0'..='9' => string.push(char),
+                '_' => (),
+                // Only a dot followed by a digit is a decimal point, `1.foo` or `0..10` are not
+                '.' if !decimal && !exponent && self.peek_second().is_some_and(|c| c.is_ascii_digit()) => {
                     decimal = true;
-                    string.push(*char);
+                    string.push(char);
+                }
+                'e' | 'E' if !exponent => {
+                    exponent = true;
+                    string.push(char);
+                    let _ = self.next_char();
+                    if let Some(sign @ ('+' | '-')) = self.input.peek().copied() {
+                        string.push(sign);
+                        let _ = self.next_char();
+                    }
+                    // The exponent needs at least one digit
+                    if !self.input.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        return None;
+                    }
+                    continue;
                 }
-                // If we meet any invalid character, we can break.
-                //
-                // This will create a problem in the parser if there are numbers followed by an
-                // ident/string with no space but that is not my problem :smiling_imp:
-                //
-                // TODO fix this later because it is really stupid
                 _ => break,
             };
             let _ = self.next_char();
         }
+        string.parse::<f64>().ok()
+    }
+
+    /// Parse the digits of a hexadecimal or binary number, after its prefix.
+    fn parse_radix_digits(&mut self, radix: u32) -> Option<f64> {
+        let mut string = String::new();
+        loop {
+            let Some(char) = self.input.peek().copied() else {
+                break;
+            };
+            match char {
+                '_' => (),
+                _ if char.is_digit(radix) => string.push(char),
+                _ => break,
+            }
+            let _ = self.next_char();
+        }
+        u64::from_str_radix(&string, radix).ok().map(|number| number as f64)
+    }
+
+    /// If the number being lexed is directly followed by something that would make it malformed
+    fn number_continues(&mut self) -> bool {
+        match self.input.peek() {
+            Some(char) if char.is_alphanumeric() || *char == '_' => true,
+            Some('.') => self.peek_second().is_some_and(|c| c.is_ascii_digit()),
+            _ => false,
+        }
+    }
+
+    /// Peeks at the character after the next one
+    fn peek_second(&self) -> Option<char> {
+        self.input.clone().nth(1)
+    }
+
+    /// parse out a string, or a multi-line text block if it starts with `"""`
+    ///
+    /// Text blocks have the indentation shared by all of their lines stripped, along with the
+    /// line breaks right after the opening and right before the closing `"""`.
+    fn parse_string(&mut self) -> Result<Token, LexerError> {
+        let start = self.position.clone();
+        let text_block = self.input.peek() == Some(&'"') && self.peek_second() == Some('"');
+        if text_block {
+            let _ = self.next_char();
+            let _ = self.next_char();
+        }
+        let mut raw = String::new();
+        // Quotes in a row, a text block ends on the third one
+        let mut quotes = 0;
+
+        loop {
+            let char = self
+                .next_char()
+                .ok_or_else(|| self.err(start.clone(), LexerErrorKind::UnterminatedString))?;
+
+            match char {
+                // Keep escapes as they are for now, but make sure an escaped quote doesn't end the string
+                '\\' => {
+                    let escaped = self
+                        .next_char()
+                        .ok_or_else(|| self.err(start.clone(), LexerErrorKind::UnterminatedString))?;
+                    raw.push(char);
+                    raw.push(escaped);
+                }
+                '"' if !text_block => break,
+                '"' => {
+                    quotes += 1;
+                    if quotes == 3 {
+                        raw.truncate(raw.len() - 2);
+                        break;
+                    }
+                    raw.push(char);
+                    continue;
+                }
+                _ => raw.push(char),
+            }
+            quotes = 0;
+        }
+
+        if text_block {
+            raw = Self::strip_indentation(&raw);
+        }
+        let string = Self::unescape(&raw).map_err(|kind| self.err(start.clone(), kind))?;
+
         Ok(Token {
             range: Range {
                 start: start.clone(),
                 end: self.position.clone(),
             },
-            token_type: TokenType::Number(
-                string.parse::<f64>().expect("We made sure it's a number"),
-            ),
+            token_type: TokenType::String(string),
         })
     }
 
-    /// parse out a string
-    fn parse_string(&mut self) -> Result<Token, LexerError> {
+    /// Parse out a template literal, `` `text {expression} text` ``
+    ///
+    /// Embedded expressions are lexed into their own tokens, braces inside them are matched so
+    /// they can contain blocks and strings of their own.
+    fn parse_template(&mut self) -> Result<Token, LexerError> {
         let start = self.position.clone();
-        let mut string = String::new();
-        let mut backslashed = false;
+        let mut segments = Vec::new();
+        let mut raw = String::new();
 
         loop {
             let char = self
                 .next_char()
                 .ok_or_else(|| self.err(start.clone(), LexerErrorKind::UnterminatedString))?;
 
-            match (char, backslashed) {
-                // Backslashes
-                ('\\', false) => {
-                    backslashed = true;
-                    // continue here so we don't set backslashed back to false
+            match char {
+                '\\' => {
+                    let escaped = self
+                        .next_char()
+                        .ok_or_else(|| self.err(start.clone(), LexerErrorKind::UnterminatedString))?;
+                    raw.push(char);
+                    raw.push(escaped);
+                }
+                '`' => break,
+                '{' => {
+                    if !raw.is_empty() {
+                        let literal = Self::unescape(&raw).map_err(|kind| self.err(start.clone(), kind))?;
+                        segments.push(TemplateSegment::Literal(literal));
+                        raw.clear();
+                    }
+                    segments.push(TemplateSegment::Expression(self.template_expression(&start)?));
+                }
+                _ => raw.push(char),
+            }
+        }
+        if !raw.is_empty() {
+            let literal = Self::unescape(&raw).map_err(|kind| self.err(start.clone(), kind))?;
+            segments.push(TemplateSegment::Literal(literal));
+        }
+
+        Ok(Token {
+            range: Range {
+                start,
+                end: self.position.clone(),
+            },
+            token_type: TokenType::Template(segments),
+        })
+    }
+
+    /// Lexes the expression embedded in a template literal, right after its opening `{`, up to and including its closing `}`.
+    fn template_expression(&mut self, template_start: &Position) -> Result<Vec<Token>, LexerError> {
+        // The expression gets lexed starting from the opening brace, which is swapped for a space
+        // so that every token keeps its position in the file.
+        let brace_position = self.position.clone();
+        let mut expression = String::from(' ');
+        let mut depth = 0;
+        let mut in_string = false;
+
+        loop {
+            let char = self
+                .next_char()
+                .ok_or_else(|| self.err(template_start.clone(), LexerErrorKind::UnterminatedString))?;
+
+            match (char, in_string) {
+                ('}', false) if depth == 0 => break,
+                ('{', false) => depth += 1,
+                ('}', false) => depth -= 1,
+                ('"', _) => in_string = !in_string,
+                ('\\', true) => {
+                    expression.push(char);
+                    let escaped = self
+                        .next_char()
+                        .ok_or_else(|| self.err(template_start.clone(), LexerErrorKind::UnterminatedString))?;
+                    expression.push(escaped);
                     continue;
                 }
-                ('\\', true) => string.push('\\'),
-                // Backslashed quotes
-                ('"', false) => break,
-                ('"', true) => string.push('"'),
-                // Backslashed single characters
-                ('n', true) => string.push('\n'),
-                ('t', true) => string.push('\t'),
-                (_, true) => {
-                    // TODO this prob;ably isnt a good handler for backslashes? idk we should maybe
-                    // think about how this should be handled
-                    string.push('\\');
-                    string.push(char)
+                _ => (),
+            }
+            expression.push(char);
+        }
+
+        let mut tokens = Vec::new();
+        let lexer = Lexer::new_at(&expression, brace_position);
+        for token in lexer {
+            let token = token?;
+            if token.token_type != TokenType::Eof {
+                tokens.push(token);
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Parse out a raw string, `r"..."` or `r#"..."#`, in which nothing gets escaped
+    ///
+    /// Any amount of `#` can be used, as long as the closing quote is followed by the same amount.
+    fn parse_raw_string(&mut self) -> Result<Token, LexerError> {
+        let start = self.position.clone();
+        let mut hashes = 0;
+        while self.input.peek() == Some(&'#') {
+            let _ = self.next_char();
+            hashes += 1;
+        }
+        if self.next_char() != Some('"') {
+            return Err(self.err(start, LexerErrorKind::InvalidCharacter));
+        }
+        let mut string = String::new();
+
+        loop {
+            let char = self
+                .next_char()
+                .ok_or_else(|| self.err(start.clone(), LexerErrorKind::UnterminatedString))?;
+
+            if char == '"' && self.input.clone().take(hashes).filter(|c| *c == '#').count() == hashes {
+                for _ in 0..hashes {
+                    let _ = self.next_char();
                 }
-                _ => string.push(char),
+                break;
             }
-            backslashed = false;
+            string.push(char);
         }
 
         Ok(Token {
             range: Range {
-                start: start.clone(),
+                start,
                 end: self.position.clone(),
             },
             token_type: TokenType::String(string),
         })
     }
 
+    /// Strips the indentation shared by every non-blank line of a text block
+    fn strip_indentation(raw: &str) -> String {
+        let mut lines: Vec<&str> = raw.split('\n').collect();
+        // The line breaks after the opening and before the closing quotes aren't part of the text
+        if lines.len() > 1 && lines[0].trim().is_empty() {
+            lines.remove(0);
+        }
+        if lines.len() > 1 && lines[lines.len() - 1].trim().is_empty() {
+            lines.pop();
+        }
+        let indentation = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        lines
+            .iter()
+            .map(|line| line.get(indentation..).unwrap_or(line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Resolves the escape sequences inside a string
+    fn unescape(raw: &str) -> Result<String, LexerErrorKind> {
+        let mut string = String::new();
+        let mut chars = raw.chars();
+
+        while let Some(char) = chars.next() {
+            if char != '\\' {
+                string.push(char);
+                continue;
+            }
+            match chars.next() {
+                Some('\\') => string.push('\\'),
+                Some('"') => string.push('"'),
+                Some('`') => string.push('`'),
+                Some('{') => string.push('{'),
+                Some('}') => string.push('}'),
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                Some('r') => string.push('\r'),
+                Some('0') => string.push('\0'),
+                // Unicode escapes, `\u{a7}`
+                Some('u') => {
+                    if chars.next() != Some('{') {
+                        return Err(LexerErrorKind::InvalidUnicodeEscape);
+                    }
+                    let mut hex = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(digit) if digit.is_ascii_hexdigit() && hex.len() < 6 => hex.push(digit),
+                            _ => return Err(LexerErrorKind::InvalidUnicodeEscape),
+                        }
+                    }
+                    let unicode = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(LexerErrorKind::InvalidUnicodeEscape)?;
+                    string.push(unicode);
+                }
+                _ => return Err(LexerErrorKind::UnknownEscape),
+            }
+        }
+        Ok(string)
+    }
+
     /// Converts a [TokenType] into a [Token]
     fn type_to_token(&self, token_type: TokenType) -> Token {
         Token {
@@ -242,7 +580,17 @@ impl<'a> Lexer<'a> {
         token_type: TokenType,
         lookahead: (char, TokenType),
     ) -> Result<Token, LexerError> {
-        if self.input.peek() == Some(&lookahead.0) {
+        self.parse_char_lookaheads(token_type, vec![lookahead])
+    }
+
+    /// Same as [Self::parse_char_lookahead], but picks whichever of the `lookaheads` matches the next character.
+    fn parse_char_lookaheads(
+        &mut self,
+        token_type: TokenType,
+        lookaheads: Vec<(char, TokenType)>,
+    ) -> Result<Token, LexerError> {
+        let next = self.input.peek().copied();
+        if let Some((_, lookahead_type)) = lookaheads.into_iter().find(|(char, _)| Some(*char) == next) {
             // Skip over the character :grin:
             let start = self.position.clone();
             let _ = self.next_char();
@@ -251,7 +599,7 @@ impl<'a> Lexer<'a> {
                     start,
                     end: self.position.clone(),
                 },
-                token_type: lookahead.1,
+                token_type: lookahead_type,
             })
         } else {
             Ok(Token {
@@ -264,6 +612,43 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Goes past a `//` line comment, giving back a [TokenType::Comment] if it was a `///` doc comment.
+    fn parse_comment(&mut self) -> Option<Token> {
+        let start = self.position.clone();
+        let _ = self.next_char();
+        // `////` and longer are separators, not documentation.
+        let doc = self.input.peek() == Some(&'/') && self.peek_second() != Some('/');
+        let mut text = String::new();
+        while let Some(char) = self.input.peek().copied() {
+            if char == '\n' {
+                break;
+            }
+            let _ = self.next_char();
+            text.push(char);
+        }
+        if !doc {
+            return None;
+        }
+        // Drop the third slash and the space that usually follows it.
+        let text = text[1..].strip_prefix(' ').unwrap_or(&text[1..]).trim_end().to_owned();
+        Some(Token {
+            token_type: TokenType::Comment(text),
+            range: Range {
+                start,
+                end: self.position.clone(),
+            },
+        })
+    }
+
+    /// Gives out the [TokenType::Eof] token the first time the end of the input is reached, and nothing after that.
+    fn end_of_file(&mut self) -> Option<Result<Token, LexerError>> {
+        if self.reached_end {
+            return None;
+        }
+        self.reached_end = true;
+        Some(Ok(self.type_to_token(TokenType::Eof)))
+    }
+
     fn inline_dfasm(&mut self) -> Token {
         let mut input_string = String::new();
         let start_pos = self.position.clone();
@@ -290,21 +675,24 @@ impl<'a> Lexer<'a> {
     }
 }
 
-impl<'a> Iterator for Lexer<'a> {
-    type Item = Result<Token, LexerError>;
-
-    fn next(&mut self) -> Option<Self::Item> {
+impl<'a> Lexer<'a> {
+    fn next_token(&mut self) -> Option<Result<Token, LexerError>> {
         if self.inline_dfasm {
             self.inline_dfasm = false;
             return Some(Ok(self.inline_dfasm()))
         }
         self.skip_whitespace();
 
-        let char = self.next_char()?;
+        let Some(char) = self.next_char() else {
+            return self.end_of_file();
+        };
         match char {
             '"' => Some(self.parse_string()),
+            '`' => Some(self.parse_template()),
+            'r' if matches!(self.input.peek(), Some('"' | '#')) => Some(self.parse_raw_string()),
             'a'..='z' | 'A'..='Z' | '_' => Some(self.parse_ident()),
             '0'..='9' => Some(self.parse_number()),
+            '\'' => Some(self.parse_label()),
 
             // Single character tokens
             '(' => Some(Ok(self.type_to_token(TokenType::LParen))),
@@ -319,29 +707,46 @@ impl<'a> Iterator for Lexer<'a> {
             '}' => Some(Ok(self.type_to_token(TokenType::RBrace))),
             '[' => Some(Ok(self.type_to_token(TokenType::LBracket))),
             ']' => Some(Ok(self.type_to_token(TokenType::RBracket))),
-            '.' => Some(Ok(self.type_to_token(TokenType::Dot))),
+            '.' => Some(self.parse_char_lookahead(TokenType::Dot, ('.', TokenType::DotDot))),
             ',' => Some(Ok(self.type_to_token(TokenType::Comma))),
             ';' => Some(Ok(self.type_to_token(TokenType::Semicolon))),
             ':' => Some(Ok(self.type_to_token(TokenType::Colon))),
-            // TODO Make these guys have a += and maybe even a ++??!!
-            // '-' => Some(Ok(self.type_to_token(TokenType::Dash))),
-            '-' => Some(self.parse_char_lookahead(TokenType::Dash, ('>', TokenType::Arrow))),
-            '+' => Some(Ok(self.type_to_token(TokenType::Plus))),
-            '*' => Some(Ok(self.type_to_token(TokenType::Asterisk))),
-            '/' => Some(Ok(self.type_to_token(TokenType::Slash))),
-            '%' => Some(Ok(self.type_to_token(TokenType::Perc))),
+            '-' => Some(self.parse_char_lookaheads(TokenType::Dash, vec![('>', TokenType::Arrow), ('=', TokenType::DashAssign), ('-', TokenType::Decrement)])),
+            '+' => Some(self.parse_char_lookaheads(TokenType::Plus, vec![('=', TokenType::PlusAssign), ('+', TokenType::Increment)])),
+            '*' => Some(self.parse_char_lookaheads(TokenType::Asterisk, vec![('=', TokenType::AsteriskAssign), ('*', TokenType::DoubleAsterisk)])),
+            '/' if self.input.peek() == Some(&'/') && self.after_operand => Some(self.parse_char_lookahead(TokenType::DoubleSlash, ('/', TokenType::DoubleSlash))),
+            '/' if self.input.peek() == Some(&'/') => match self.parse_comment() {
+                Some(doc_comment) => Some(Ok(doc_comment)),
+                None => self.next_token(),
+            },
+            '/' => Some(self.parse_char_lookahead(TokenType::Slash, ('=', TokenType::SlashAssign))),
+            '%' => Some(self.parse_char_lookahead(TokenType::Perc, ('=', TokenType::PercAssign))),
+            '^' => Some(Ok(self.type_to_token(TokenType::Caret))),
+            '?' => Some(self.parse_char_lookahead(TokenType::Question, ('?', TokenType::DoubleQuestion))),
 
             // <, >, =, or ! can be interpreted as <=, >=, ==, or != (separate tokens!!!)
-            '<' => Some(self.parse_char_lookahead(TokenType::LAngle, ('=', TokenType::LTEqual))),
-            '>' => Some(self.parse_char_lookahead(TokenType::RAngle, ('=', TokenType::GTEqual))),
-            '=' => Some(self.parse_char_lookahead(TokenType::Assign, ('=', TokenType::Equal))),
+            '<' => Some(self.parse_char_lookaheads(TokenType::LAngle, vec![('=', TokenType::LTEqual), ('<', TokenType::LShift)])),
+            '>' => Some(self.parse_char_lookaheads(TokenType::RAngle, vec![('=', TokenType::GTEqual), ('>', TokenType::RShift)])),
+            '=' => Some(self.parse_char_lookaheads(TokenType::Assign, vec![('=', TokenType::Equal), ('>', TokenType::FatArrow)])),
             '!' => Some(self.parse_char_lookahead(TokenType::Bang, ('=', TokenType::NotEqual))),
             '|' => Some(self.parse_char_lookahead(TokenType::Bar, ('|', TokenType::Or))),
             '&' => Some(self.parse_char_lookahead(TokenType::Ampersand, ('&', TokenType::And))),
 
-            '\0' => None,
-            _ => todo!(),
+            '\0' => self.end_of_file(),
+            _ => Some(Err(self.err(self.position.clone(), LexerErrorKind::InvalidCharacter))),
+        }
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.next_token();
+        if let Some(Ok(token)) = &token {
+            self.after_operand = token.token_type.ends_operand();
         }
+        token
     }
 }
 
@@ -538,4 +943,504 @@ func test(number x, Player p) {
             .zip(actual.map(|v| v.unwrap().token_type))
             .for_each(|(exp, act)| assert_eq!(exp, dbg!(&act)));
     }
+
+    #[test]
+    pub fn test_eof() {
+        let input = "foo";
+
+        let mut actual = Lexer::new(input);
+
+        assert_eq!(
+            actual.next().unwrap().unwrap().token_type,
+            TokenType::Ident("foo".to_string())
+        );
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::Eof,
+                range: Range::new((0, 3), (0, 3)),
+            }
+        );
+        assert_eq!(actual.next(), None);
+    }
+
+    #[test]
+    pub fn test_numbers() {
+        let input = "0xFF 0b1010 1_000_000 1.5e3 2E-2 3.25 0..10 1.foo";
+
+        use TokenType::*;
+        let expected = [
+            Number(255.0),
+            Number(10.0),
+            Number(1_000_000.0),
+            Number(1500.0),
+            Number(0.02),
+            Number(3.25),
+            Number(0.0),
+            DotDot,
+            Number(10.0),
+            Number(1.0),
+            Dot,
+            Ident("foo".to_string()),
+            Eof,
+        ];
+
+        let actual = Lexer::new(input);
+        expected
+            .iter()
+            .zip(actual.map(|v| v.unwrap().token_type))
+            .for_each(|(exp, act)| assert_eq!(exp, dbg!(&act)));
+    }
+
+    #[test]
+    pub fn test_malformed_numbers() {
+        let input = "12abc 1.2.3 0x 0b102 1e+ 5";
+
+        let mut actual = Lexer::new(input);
+
+        for range in [
+            Range::new((0, 0), (0, 4)),
+            Range::new((0, 6), (0, 10)),
+            Range::new((0, 12), (0, 13)),
+            Range::new((0, 15), (0, 19)),
+            Range::new((0, 21), (0, 23)),
+        ] {
+            assert_eq!(
+                actual.next().unwrap(),
+                Err(LexerError {
+                    range,
+                    source: LexerErrorKind::MalformedNumber,
+                })
+            );
+        }
+        assert_eq!(actual.next().unwrap().unwrap().token_type, TokenType::Number(5.0));
+    }
+
+    #[test]
+    pub fn test_escapes() {
+        let input = r#""a\r\0\u{a7}\u{1F600}" "bad \q escape" "\u{110000}" "after""#;
+
+        let mut actual = Lexer::new(input);
+
+        assert_eq!(
+            actual.next().unwrap().unwrap().token_type,
+            TokenType::String("a\r\0§😀".to_string())
+        );
+        assert_eq!(
+            actual.next().unwrap(),
+            Err(LexerError {
+                range: Range::new((0, 23), (0, 37)),
+                source: LexerErrorKind::UnknownEscape,
+            })
+        );
+        assert_eq!(
+            actual.next().unwrap(),
+            Err(LexerError {
+                range: Range::new((0, 39), (0, 50)),
+                source: LexerErrorKind::InvalidUnicodeEscape,
+            })
+        );
+        assert_eq!(
+            actual.next().unwrap().unwrap().token_type,
+            TokenType::String("after".to_string())
+        );
+    }
+
+    #[test]
+    pub fn test_raw_strings() {
+        let input = r###"r"C:\path\n" r#"<gradient:"red">"#"###;
+
+        let mut actual = Lexer::new(input);
+
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::String(r"C:\path\n".to_string()),
+                range: Range::new((0, 0), (0, 11)),
+            }
+        );
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::String(r#"<gradient:"red">"#.to_string()),
+                range: Range::new((0, 13), (0, 33)),
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_text_block() {
+        let input = "\
+lore = \"\"\"
+    First line
+      indented \\\"quote\\\"
+
+    Last line\\n
+    \"\"\";";
+
+        let mut actual = Lexer::new(input);
+
+        actual.next();
+        actual.next();
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::String(
+                    "First line\n  indented \"quote\"\n\nLast line\n".to_string()
+                ),
+                range: Range::new((0, 7), (5, 6)),
+            }
+        );
+        assert_eq!(actual.next().unwrap().unwrap().token_type, TokenType::Semicolon);
+    }
+
+    #[test]
+    pub fn test_template() {
+        let input = r#"`<red>-{loss} score, {p.name("\"}")}\{}`"#;
+
+        let mut actual = Lexer::new(input);
+
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::Template(vec![
+                    TemplateSegment::Literal("<red>-".to_string()),
+                    TemplateSegment::Expression(vec![Token {
+                        token_type: TokenType::Ident("loss".to_string()),
+                        range: Range::new((0, 8), (0, 11)),
+                    }]),
+                    TemplateSegment::Literal(" score, ".to_string()),
+                    TemplateSegment::Expression(vec![
+                        Token {
+                            token_type: TokenType::Ident("p".to_string()),
+                            range: Range::new((0, 22), (0, 22)),
+                        },
+                        Token {
+                            token_type: TokenType::Dot,
+                            range: Range::new((0, 23), (0, 23)),
+                        },
+                        Token {
+                            token_type: TokenType::Ident("name".to_string()),
+                            range: Range::new((0, 24), (0, 27)),
+                        },
+                        Token {
+                            token_type: TokenType::LParen,
+                            range: Range::new((0, 28), (0, 28)),
+                        },
+                        Token {
+                            token_type: TokenType::String("\"}".to_string()),
+                            range: Range::new((0, 29), (0, 33)),
+                        },
+                        Token {
+                            token_type: TokenType::RParen,
+                            range: Range::new((0, 34), (0, 34)),
+                        },
+                    ]),
+                    TemplateSegment::Literal("{}".to_string()),
+                ]),
+                range: Range::new((0, 0), (0, 39)),
+            }
+        );
+        assert_eq!(actual.next().unwrap().unwrap().token_type, TokenType::Eof);
+    }
+
+    #[test]
+    pub fn test_comments() {
+        let input = "// plain\n/// Makes a sword.\n///second line\n//// separator\nfunc a / b; // trailing";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Comment("Makes a sword.".to_string()),
+                TokenType::Comment("second line".to_string()),
+                TokenType::Keyword(Keyword::Func),
+                TokenType::Ident("a".to_string()),
+                TokenType::Slash,
+                TokenType::Ident("b".to_string()),
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+
+        let mut doc = Lexer::new("  /// hi");
+        assert_eq!(
+            doc.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::Comment("hi".to_string()),
+                range: Range::new((0, 2), (0, 7)),
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_comment_after_operand() {
+        let lex = |input: &str| -> Vec<TokenType> { Lexer::new(input).map(|token| token.unwrap().token_type).collect() };
+        let ident = |name: &str| TokenType::Ident(name.to_string());
+
+        // `//` right after an operand is floor division, in headers just like in expressions.
+        assert_eq!(
+            lex("if a == b // note\n{"),
+            vec![TokenType::Keyword(Keyword::If), ident("a"), TokenType::Equal, ident("b"), TokenType::DoubleSlash, ident("note"), TokenType::LBrace, TokenType::Eof]
+        );
+        assert_eq!(
+            lex("func f() -> num // note\n{"),
+            vec![
+                TokenType::Keyword(Keyword::Func), ident("f"), TokenType::LParen, TokenType::RParen, TokenType::Arrow, ident("num"),
+                TokenType::DoubleSlash, ident("note"), TokenType::LBrace, TokenType::Eof,
+            ]
+        );
+        assert_eq!(
+            lex("while running // poll"),
+            vec![TokenType::Keyword(Keyword::While), ident("running"), TokenType::DoubleSlash, ident("poll"), TokenType::Eof]
+        );
+
+        // A line break doesn't end the operand, only a token that can't end one does.
+        assert_eq!(lex("a\n// b"), vec![ident("a"), TokenType::DoubleSlash, ident("b"), TokenType::Eof]);
+        assert_eq!(lex("a;\n// b"), vec![ident("a"), TokenType::Semicolon, TokenType::Eof]);
+        assert_eq!(lex("{ // b\n}"), vec![TokenType::LBrace, TokenType::RBrace, TokenType::Eof]);
+    }
+
+    #[test]
+    pub fn test_compound_operators() {
+        let input = "a += 1; b -= c; d *= e /= f %= g; h++; i--; j -> k - -l";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Ident("a".to_string()),
+                TokenType::PlusAssign,
+                TokenType::Number(1.0),
+                TokenType::Semicolon,
+                TokenType::Ident("b".to_string()),
+                TokenType::DashAssign,
+                TokenType::Ident("c".to_string()),
+                TokenType::Semicolon,
+                TokenType::Ident("d".to_string()),
+                TokenType::AsteriskAssign,
+                TokenType::Ident("e".to_string()),
+                TokenType::SlashAssign,
+                TokenType::Ident("f".to_string()),
+                TokenType::PercAssign,
+                TokenType::Ident("g".to_string()),
+                TokenType::Semicolon,
+                TokenType::Ident("h".to_string()),
+                TokenType::Increment,
+                TokenType::Semicolon,
+                TokenType::Ident("i".to_string()),
+                TokenType::Decrement,
+                TokenType::Semicolon,
+                TokenType::Ident("j".to_string()),
+                TokenType::Arrow,
+                TokenType::Ident("k".to_string()),
+                TokenType::Dash,
+                TokenType::Dash,
+                TokenType::Ident("l".to_string()),
+                TokenType::Eof,
+            ]
+        );
+
+        let mut increment = Lexer::new("x++");
+        increment.next();
+        assert_eq!(
+            increment.next().unwrap().unwrap(),
+            Token {
+                token_type: TokenType::Increment,
+                range: Range::new((0, 1), (0, 2)),
+            }
+        );
+    }
+
+    #[test]
+    pub fn test_for_range() {
+        let input = "for i in 0..n.length step 2";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Keyword(Keyword::For),
+                TokenType::Ident("i".to_string()),
+                TokenType::Keyword(Keyword::In),
+                TokenType::Number(0.0),
+                TokenType::DotDot,
+                TokenType::Ident("n".to_string()),
+                TokenType::Dot,
+                TokenType::Ident("length".to_string()),
+                TokenType::Ident("step".to_string()),
+                TokenType::Number(2.0),
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_labels() {
+        let input = "'outer: while x { continue; break 'outer; } '";
+
+        let actual: Vec<Result<TokenType, LexerErrorKind>> = Lexer::new(input)
+            .map(|token| token.map(|token| token.token_type).map_err(|e| e.source))
+            .collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                Ok(TokenType::Label("outer".to_string())),
+                Ok(TokenType::Colon),
+                Ok(TokenType::Keyword(Keyword::While)),
+                Ok(TokenType::Ident("x".to_string())),
+                Ok(TokenType::LBrace),
+                Ok(TokenType::Keyword(Keyword::Continue)),
+                Ok(TokenType::Semicolon),
+                Ok(TokenType::Keyword(Keyword::Break)),
+                Ok(TokenType::Label("outer".to_string())),
+                Ok(TokenType::Semicolon),
+                Ok(TokenType::RBrace),
+                Err(LexerErrorKind::InvalidCharacter),
+                Ok(TokenType::Eof),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_match() {
+        let input = "match x { 1 | 2 => {} _ => {} } a == b = c";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Keyword(Keyword::Match),
+                TokenType::Ident("x".to_string()),
+                TokenType::LBrace,
+                TokenType::Number(1.0),
+                TokenType::Bar,
+                TokenType::Number(2.0),
+                TokenType::FatArrow,
+                TokenType::LBrace,
+                TokenType::RBrace,
+                TokenType::Ident("_".to_string()),
+                TokenType::FatArrow,
+                TokenType::LBrace,
+                TokenType::RBrace,
+                TokenType::RBrace,
+                TokenType::Ident("a".to_string()),
+                TokenType::Equal,
+                TokenType::Ident("b".to_string()),
+                TokenType::Assign,
+                TokenType::Ident("c".to_string()),
+                TokenType::Eof,
+            ]
+        );
+    }
+    #[test]
+    pub fn test_arithmetic_operators() {
+        let input = "a ** b // c % d << e >> f & g ^ h | i *= 2; // comment";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Ident("a".to_string()),
+                TokenType::DoubleAsterisk,
+                TokenType::Ident("b".to_string()),
+                TokenType::DoubleSlash,
+                TokenType::Ident("c".to_string()),
+                TokenType::Perc,
+                TokenType::Ident("d".to_string()),
+                TokenType::LShift,
+                TokenType::Ident("e".to_string()),
+                TokenType::RShift,
+                TokenType::Ident("f".to_string()),
+                TokenType::Ampersand,
+                TokenType::Ident("g".to_string()),
+                TokenType::Caret,
+                TokenType::Ident("h".to_string()),
+                TokenType::Bar,
+                TokenType::Ident("i".to_string()),
+                TokenType::AsteriskAssign,
+                TokenType::Number(2.0),
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+    }
+    #[test]
+    pub fn test_imports() {
+        let input = "package game.ui; from std import Player as P;";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Keyword(Keyword::Package),
+                TokenType::Ident("game".to_string()),
+                TokenType::Dot,
+                TokenType::Ident("ui".to_string()),
+                TokenType::Semicolon,
+                TokenType::Keyword(Keyword::From),
+                TokenType::Ident("std".to_string()),
+                TokenType::Keyword(Keyword::Import),
+                TokenType::Ident("Player".to_string()),
+                TokenType::Keyword(Keyword::As),
+                TokenType::Ident("P".to_string()),
+                TokenType::Semicolon,
+                TokenType::Eof,
+            ]
+        );
+    }
+    #[test]
+    pub fn test_optionals() {
+        let input = "num? a = none; a ?? 1";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Ident("num".to_string()),
+                TokenType::Question,
+                TokenType::Ident("a".to_string()),
+                TokenType::Assign,
+                TokenType::Keyword(Keyword::Value(ValuedKeyword::None)),
+                TokenType::Semicolon,
+                TokenType::Ident("a".to_string()),
+                TokenType::DoubleQuestion,
+                TokenType::Number(1.0),
+                TokenType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    pub fn test_enum() {
+        let input = "enum State { Waiting, Playing(num round) }";
+
+        let actual: Vec<TokenType> = Lexer::new(input).map(|token| token.unwrap().token_type).collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                TokenType::Keyword(Keyword::Enum),
+                TokenType::Ident("State".to_string()),
+                TokenType::LBrace,
+                TokenType::Ident("Waiting".to_string()),
+                TokenType::Comma,
+                TokenType::Ident("Playing".to_string()),
+                TokenType::LParen,
+                TokenType::Ident("num".to_string()),
+                TokenType::Ident("round".to_string()),
+                TokenType::RParen,
+                TokenType::RBrace,
+                TokenType::Eof,
+            ]
+        );
+    }
 }
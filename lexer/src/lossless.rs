@@ -0,0 +1,127 @@
+use crate::errors::LexerError;
+use crate::types::{LosslessToken, TokenType, Trivia};
+use crate::Lexer;
+
+/// Lexes the same tokens as [Lexer], but keeps the whitespace and comments around them as [Trivia].
+///
+/// Writing out every [LosslessToken] in order, up to and including the [TokenType::Eof] one, gives back the input
+/// byte for byte, as long as no errors were given out.
+#[derive(Debug)]
+pub struct LosslessLexer<'a> {
+    lexer: Lexer<'a>,
+    source: &'a str,
+    finished: bool,
+}
+
+impl<'a> LosslessLexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            lexer: Lexer::new(input),
+            source: input,
+            finished: false,
+        }
+    }
+
+    /// Puts the tokens back together into the source they were lexed from.
+    pub fn write_source(tokens: &[LosslessToken]) -> String {
+        let mut output = String::new();
+        for token in tokens {
+            token.write_source(&mut output);
+        }
+        output
+    }
+
+    /// Takes in whitespace and `//` comments, stopping at the first line break if `same_line` is set.
+    fn trivia(&mut self, same_line: bool) -> Vec<Trivia> {
+        let mut trivia = Vec::new();
+        loop {
+            let start = self.lexer.offset;
+            match self.lexer.input.peek().copied() {
+                Some('\n') if same_line => break,
+                Some(char) if char.is_whitespace() => {
+                    while self.lexer.input.peek().is_some_and(|c| c.is_whitespace() && !(same_line && *c == '\n')) {
+                        let _ = self.lexer.next_char();
+                    }
+                    trivia.push(Trivia::Whitespace(self.source[start..self.lexer.offset].to_owned()));
+                },
+                Some('/') if self.lexer.peek_second() == Some('/') && !self.lexer.after_operand => {
+                    while self.lexer.input.peek().is_some_and(|c| *c != '\n') {
+                        let _ = self.lexer.next_char();
+                    }
+                    trivia.push(Trivia::Comment(self.source[start..self.lexer.offset].to_owned()));
+                },
+                _ => break,
+            }
+        }
+        trivia
+    }
+}
+
+impl<'a> Iterator for LosslessLexer<'a> {
+    type Item = Result<LosslessToken, LexerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        // Inline dfasm keeps its own whitespace, right after the brace.
+        let leading_trivia = if self.lexer.inline_dfasm { Vec::new() } else { self.trivia(false) };
+        let start = self.lexer.offset;
+        let token = match self.lexer.next()? {
+            Ok(token) => token,
+            Err(e) => return Some(Err(e)),
+        };
+        let text = self.source[start..self.lexer.offset].to_owned();
+        let trailing_trivia = if self.lexer.inline_dfasm { Vec::new() } else { self.trivia(true) };
+        self.finished = token.token_type == TokenType::Eof;
+        Some(Ok(LosslessToken {
+            leading_trivia,
+            token,
+            text,
+            trailing_trivia,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Range, Token};
+
+    #[test]
+    pub fn test_round_trip() {
+        let input = "/// Doc.\r\nfunc main() {\t// hi\n    x += `a{ b }c`; y = \"é\\n\" // 2;  \n\n}\n// end";
+
+        let tokens: Vec<LosslessToken> = LosslessLexer::new(input).map(|token| token.unwrap()).collect();
+
+        assert_eq!(LosslessLexer::write_source(&tokens), input);
+        assert_eq!(tokens.last().unwrap().token.token_type, TokenType::Eof);
+        assert!(tokens.iter().any(|token| token.token.token_type == TokenType::DoubleSlash));
+    }
+
+    #[test]
+    pub fn test_trivia() {
+        let input = "  { // same line\n  b";
+
+        let mut actual = LosslessLexer::new(input);
+
+        assert_eq!(
+            actual.next().unwrap().unwrap(),
+            LosslessToken {
+                leading_trivia: vec![Trivia::Whitespace("  ".to_string())],
+                token: Token {
+                    token_type: TokenType::LBrace,
+                    range: Range::new((0, 2), (0, 2)),
+                },
+                text: "{".to_string(),
+                trailing_trivia: vec![Trivia::Whitespace(" ".to_string()), Trivia::Comment("// same line".to_string())],
+            }
+        );
+        assert_eq!(
+            actual.next().unwrap().unwrap().leading_trivia,
+            vec![Trivia::Whitespace("\n  ".to_string())]
+        );
+        assert_eq!(actual.next().unwrap().unwrap().text, "");
+        assert_eq!(actual.next(), None);
+    }
+}
@@ -38,14 +38,22 @@ impl Range {
     }
 }
 
+/// A piece of a template literal, either text or the tokens of an embedded expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateSegment {
+    Literal(String),
+    Expression(Vec<Token>),
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenType {
     Ident(String),
     String(String),
     DFASM(String),
     Number(f64),
-    // Comments probably don't need to contain what's in the comment, but I'll leave this for now
-    Comment(String),
+    Template(Vec<TemplateSegment>), // `text {expr} text`
+    Label(String), // 'label
+    Comment(String), // /// text, only doc comments become tokens
 
     Assign,   // =
     Equal,    // ==
@@ -55,6 +63,20 @@ pub enum TokenType {
     Or,       // ||
     And,      // &&
     Arrow,    // ->
+    FatArrow, // =>
+    DotDot,   // ..
+    DoubleAsterisk, // **
+    DoubleSlash,    // //, only right after an operand
+    LShift,         // <<
+    RShift,         // >>
+
+    PlusAssign,     // +=
+    DashAssign,     // -=
+    AsteriskAssign, // *=
+    SlashAssign,    // /=
+    PercAssign,     // %=
+    Increment,      // ++
+    Decrement,      // --
 
     Plus,      // +
     Dash,      // -
@@ -67,7 +89,10 @@ pub enum TokenType {
     Semicolon, // ;
     Bar,       // |
     Ampersand, // &
+    Caret,     // ^
     Colon,     // :
+    Question,  // ?
+    DoubleQuestion, // ??
 
     LBrace,   // {
     RBrace,   // }
@@ -79,21 +104,47 @@ pub enum TokenType {
     RBracket, // ]
 
     Keyword(Keyword),
+
+    Eof, // end of file
+}
+
+impl TokenType {
+    /// Whether an operand can end with this token, which makes a `//` right after it floor division instead of a comment.
+    pub fn ends_operand(&self) -> bool {
+        matches!(self,
+            TokenType::Ident(..) | TokenType::String(..) | TokenType::Number(..) | TokenType::Template(..)
+            | TokenType::RParen | TokenType::RBracket | TokenType::Keyword(Keyword::Value(..))
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Keyword {
     Func,   // functions
     Struct, // struct definition
+    Enum,   // enum definition
     Domain, // domain definition
+    Let,    // inferred declaration
+    Const,  // compile-time constant
+
+    Package, // package declaration
+    Import,  // import
+    From,    // from package import
+
+    Pub,     // public visibility
+    Private, // private visibility
 
     If,     // ifs
     Else,   // else
     For,    // for loop
+    In,     // for x in
+    As,     // expr as type
     While,    // while loop
+    Match,  // match
 
     Return, // return
     Break,  // break
+    Continue, // continue
 
     DFASM, // dfasm
 
@@ -106,6 +157,7 @@ pub enum ValuedKeyword {
     False,  // false (boolean)
     SelfIdentity, // "self" identity
     Event, // event keyword / definition
+    None,  // none (the absence of an optional value)
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -113,3 +165,42 @@ pub struct Token {
     pub token_type: TokenType,
     pub range: Range,
 }
+
+/// Source text between tokens that doesn't change what the code means
+#[derive(Clone, Debug, PartialEq)]
+pub enum Trivia {
+    Whitespace(String),
+    Comment(String), // the whole comment, slashes included, without the line break
+}
+
+impl Trivia {
+    pub fn text(&self) -> &str {
+        match self {
+            Trivia::Whitespace(text) | Trivia::Comment(text) => text,
+        }
+    }
+}
+
+/// A [Token] along with the exact text it was lexed from and the [Trivia] around it.
+///
+/// Trailing trivia runs until the end of the token's line, everything else before a token is its leading trivia.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LosslessToken {
+    pub leading_trivia: Vec<Trivia>,
+    pub token: Token,
+    pub text: String,
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+impl LosslessToken {
+    /// Writes the token back out exactly as it was in the source, trivia included.
+    pub fn write_source(&self, output: &mut String) {
+        for trivia in &self.leading_trivia {
+            output.push_str(trivia.text());
+        }
+        output.push_str(&self.text);
+        for trivia in &self.trailing_trivia {
+            output.push_str(trivia.text());
+        }
+    }
+}